@@ -39,16 +39,35 @@ pub enum Stmt {
     Expr(Expr),
     Retry(Span),
     Throw { expr: Expr, span: Span },
-    Try { body: Vec<Stmt>, catch_name: String, catch_body: Vec<Stmt>, span: Span },
-    While { cond: Expr, body: Vec<Stmt>, span: Span },
-    DoWhile { body: Vec<Stmt>, cond: Expr, span: Span },
-    ForC { init: Option<Box<Stmt>>, cond: Option<Expr>, post: Option<Box<Stmt>>, body: Vec<Stmt>, span: Span },
-    ForEach { var: String, collection: Expr, body: Vec<Stmt>, span: Span },
+    Rethrow(Span),
+    Break { label: Option<String>, span: Span },
+    Continue { label: Option<String>, span: Span },
+    Try { body: Vec<Stmt>, catches: Vec<CatchClause>, finally: Option<Vec<Stmt>>, retry: RetryPolicy, span: Span },
+    While { label: Option<String>, cond: Expr, body: Vec<Stmt>, span: Span },
+    DoWhile { label: Option<String>, body: Vec<Stmt>, cond: Expr, span: Span },
+    ForC { label: Option<String>, init: Option<Box<Stmt>>, cond: Option<Expr>, post: Option<Box<Stmt>>, body: Vec<Stmt>, span: Span },
+    ForEach { label: Option<String>, var: String, collection: Expr, body: Vec<Stmt>, span: Span },
     If { cond: Expr, then_body: Vec<Stmt>, else_body: Vec<Stmt>, span: Span },
     Return { expr: Expr, span: Span },
     Match { expr: Expr, arms: Vec<MatchArm>, default: Option<Vec<Stmt>>, span: Span },
 }
 
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    pub name: String,
+    pub ty: Type,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+// `try (attempts: 3, backoff: 100)`: how often `retry` in a catch block may re-run the try body,
+// and the delay in milliseconds before each re-run.
+#[derive(Debug, Clone, Default)]
+pub struct RetryPolicy {
+    pub attempts: Option<Expr>,
+    pub backoff: Option<Expr>,
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     Int(i64, Span),
@@ -67,10 +86,37 @@ pub enum Expr {
     UnaryNeg { expr: Box<Expr>, span: Span },
     UnaryBitNot { expr: Box<Expr>, span: Span },
     Binary { op: BinOp, left: Box<Expr>, right: Box<Expr>, span: Span },
+    // `if c { a } else { b }` in expression position, and `c ? a : b`.
+    Cond { cond: Box<Expr>, then_expr: Box<Expr>, else_expr: Box<Expr>, span: Span },
     Call { name: String, args: Vec<Expr>, span: Span },
     CallCallee { callee: Box<Expr>, args: Vec<Expr>, span: Span },
 }
 
+impl Stmt {
+    pub fn span(&self) -> &Span {
+        match self {
+            Stmt::Expr(e) => e.span(),
+            Stmt::Retry(span) | Stmt::Rethrow(span) => span,
+            Stmt::Let{ span, .. } | Stmt::Assign{ span, .. } | Stmt::MemberAssign{ span, .. } | Stmt::Print{ span, .. }
+            | Stmt::Throw{ span, .. } | Stmt::Try{ span, .. } | Stmt::While{ span, .. } | Stmt::DoWhile{ span, .. }
+            | Stmt::ForC{ span, .. } | Stmt::ForEach{ span, .. } | Stmt::If{ span, .. } | Stmt::Return{ span, .. }
+            | Stmt::Match{ span, .. } | Stmt::Break{ span, .. } | Stmt::Continue{ span, .. } => span,
+        }
+    }
+}
+
+impl Expr {
+    pub fn span(&self) -> &Span {
+        match self {
+            Expr::Int(_, s) | Expr::Flt(_, s) | Expr::Str(_, s) | Expr::Bool(_, s) | Expr::ArrayLit(_, s)
+            | Expr::ObjLit(_, s) | Expr::Var(_, s) | Expr::New(_, _, s) => s,
+            Expr::Lambda{ span, .. } | Expr::Member{ span, .. } | Expr::Index{ span, .. } | Expr::MethodCall{ span, .. }
+            | Expr::UnaryNot{ span, .. } | Expr::UnaryNeg{ span, .. } | Expr::UnaryBitNot{ span, .. }
+            | Expr::Binary{ span, .. } | Expr::Cond{ span, .. } | Expr::Call{ span, .. } | Expr::CallCallee{ span, .. } => span,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinOp {
     Add,
@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::ast::{Item, Program};
+use crate::error::{AxityError, FileId, SourceMap, Warning};
+use crate::lint::LintConfig;
+use crate::runtime::{Runtime, Value};
+use crate::type_checker::HostEnv;
+use crate::{interpreter, lexer, lint, parser, type_checker};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug)]
+pub struct CompiledProgram {
+    id: u64,
+    program: Program,
+    sources: SourceMap,
+    // Natives and sandbox the program was type-checked against.
+    host: HostEnv,
+}
+
+impl CompiledProgram {
+    fn new(program: Program, sources: SourceMap, host: HostEnv) -> Self { Self { id: NEXT_ID.fetch_add(1, Ordering::Relaxed), program, sources, host } }
+    pub fn program(&self) -> &Program { &self.program }
+    pub fn sources(&self) -> &SourceMap { &self.sources }
+    pub fn has_function(&self, name: &str) -> bool {
+        self.program.items.iter().any(|it| matches!(it, Item::Func(f) if f.name == name))
+    }
+    // Lint findings with the program's sources attached for rendering.
+    pub fn lint(&self, config: &LintConfig) -> Vec<Warning> {
+        lint::lint(&self.program, config).into_iter().map(|w| self.sources.attach_warning(w)).collect()
+    }
+    // Indexes are rebuilt only when `rt` last ran a different program. `rt` must offer every native
+    // the type checker saw, with the same signature, and deny no builtin it allowed.
+    fn install(&self, rt: &mut Runtime) -> Result<(), AxityError> {
+        for (name, (params, ret)) in &self.host.natives {
+            if rt.natives.get(name).map(|n| (&n.params, &n.ret)) != Some((params, ret)) {
+                return Err(AxityError::rt(&format!("native `{}` is missing or differs from the one the program was compiled against", name)).with_code("AX0404"));
+            }
+        }
+        if let Some(b) = rt.config.caps.denied_builtins().into_iter().find(|b| !self.host.denied.contains(*b)) {
+            return Err(AxityError::perm(&format!("{} is denied by this runtime but was allowed when the program was compiled", b)));
+        }
+        if rt.program_id != Some(self.id) {
+            interpreter::build_index(&self.program, rt);
+            rt.program_id = Some(self.id);
+        }
+        Ok(())
+    }
+    pub fn run(&self, rt: &mut Runtime) -> Result<String, AxityError> {
+        self.install(rt)?;
+        let mut out = String::new();
+        interpreter::run(&self.program, rt, &mut out).map_err(|e| self.sources.attach(e))?;
+        Ok(out)
+    }
+    pub fn call(&self, rt: &mut Runtime, name: &str, args: &[Value]) -> Result<Value, AxityError> {
+        let mut out = String::new();
+        self.call_with_output(rt, name, args, &mut out)
+    }
+    pub fn call_with_output(&self, rt: &mut Runtime, name: &str, args: &[Value], out: &mut String) -> Result<Value, AxityError> {
+        self.install(rt)?;
+        interpreter::call_function(&self.program, name, args, rt, out).map_err(|e| self.sources.attach(e))
+    }
+}
+
+pub fn compile(source: &str, rt: &Runtime) -> Result<CompiledProgram, AxityError> { compile_named(source, "<input>", rt) }
+
+// Like `compile`, but diagnostics refer to the source as `name`.
+pub fn compile_named(source: &str, name: &str, rt: &Runtime) -> Result<CompiledProgram, AxityError> {
+    let mut sources = SourceMap::new();
+    let file = sources.add(name, source);
+    match build(source, file, rt) {
+        Ok(ast) => Ok(CompiledProgram::new(ast, sources, rt.host_env())),
+        Err(e) => Err(sources.attach(e)),
+    }
+}
+
+pub fn compile_file(path: &str, rt: &Runtime) -> Result<CompiledProgram, AxityError> {
+    let src = rt.fs.read_to_string(Path::new(path)).map_err(|e| AxityError::rt(&format!("read error: {}", e)).with_code("AX0407"))?;
+    let mut sources = SourceMap::new();
+    let file = sources.add(path, &src);
+    match build_file(path, &src, file, rt, &mut sources) {
+        Ok(ast) => Ok(CompiledProgram::new(ast, sources, rt.host_env())),
+        Err(e) => Err(sources.attach(e)),
+    }
+}
+
+fn build(source: &str, file: FileId, rt: &Runtime) -> Result<Program, AxityError> {
+    let tokens = lexer::lex_file(source, file)?;
+    let ast = parser::parse(&tokens)?;
+    type_checker::check_with_host(&ast, &rt.host_env())?;
+    Ok(ast)
+}
+
+fn build_file(path: &str, src: &str, file: FileId, rt: &Runtime, sources: &mut SourceMap) -> Result<Program, AxityError> {
+    let base = Path::new(path).parent().map(|p| p.to_path_buf()).unwrap_or(PathBuf::from("."));
+    let tokens = lexer::lex_file(src, file)?;
+    let mut ast = parser::parse(&tokens)?;
+    let mut visited = HashSet::new();
+    visited.insert(rt.fs.canonicalize(Path::new(path)));
+    crate::resolve_imports(&mut ast, &base, &mut visited, rt, sources)?;
+    type_checker::check_with_host(&ast, &rt.host_env())?;
+    Ok(ast)
+}
@@ -0,0 +1,323 @@
+// Stable error codes. Every `AxityError` carries one; `axity explain <code>` prints the entry.
+// Codes are never reused or renumbered: retire an entry instead of changing its meaning.
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+    // Script that triggers the error and the same script fixed; `None` for codes no script can trigger on its own.
+    pub example: Option<&'static str>,
+    pub fix: Option<&'static str>,
+}
+
+pub fn explain(code: &str) -> Option<&'static ErrorCode> {
+    let code = code.trim().to_ascii_uppercase();
+    CODES.iter().find(|c| c.code == code)
+}
+
+impl ErrorCode {
+    pub fn render(&self) -> String {
+        let indent = |s: &str| s.lines().map(|l| format!("    {}", l)).collect::<Vec<_>>().join("\n");
+        let mut out = format!("{}: {}\n\n{}\n", self.code, self.title, self.explanation);
+        if let Some(example) = self.example { out.push_str(&format!("\nExample:\n\n{}\n", indent(example))); }
+        if let Some(fix) = self.fix { out.push_str(&format!("\nFix:\n\n{}\n", indent(fix))); }
+        out
+    }
+}
+
+pub const CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "AX0100",
+        title: "lex error",
+        explanation: "The source could not be split into tokens. This generic code is the fallback for lexer errors without a more specific code; every error the lexer currently reports has one.",
+        example: None,
+        fix: None,
+    },
+    ErrorCode {
+        code: "AX0101",
+        title: "unexpected character",
+        explanation: "The lexer found a character that does not start any token, such as `@` or `$` outside a string.",
+        example: Some("let price: int = 5$;"),
+        fix: Some("let price: int = 5;"),
+    },
+    ErrorCode {
+        code: "AX0102",
+        title: "unterminated string",
+        explanation: "A string literal was not closed before the end of the line or file. Strings cannot span lines; use `\\n` for line breaks.",
+        example: Some("print(\"hello);"),
+        fix: Some("print(\"hello\");"),
+    },
+    ErrorCode {
+        code: "AX0200",
+        title: "parse error",
+        explanation: "The tokens do not form a valid program. This generic code is used when no more specific parser code applies, for example when a `try` sets the same option twice.",
+        example: Some("try (attempts: 2, attempts: 3) {
+    print(1);
+} catch {
+    print(0);
+}"),
+        fix: Some("try (attempts: 3) {
+    print(1);
+} catch {
+    print(0);
+}"),
+    },
+    ErrorCode {
+        code: "AX0201",
+        title: "unexpected token",
+        explanation: "The parser expected a particular token (named in the message) but found another one. The most common causes are a missing `;`, an unbalanced bracket or a keyword used as a name.",
+        example: Some("let x: int = 1\nprint(x);"),
+        fix: Some("let x: int = 1;\nprint(x);"),
+    },
+    ErrorCode {
+        code: "AX0202",
+        title: "invalid assignment target",
+        explanation: "Only variables and object fields can be assigned to or incremented. Array elements are updated with `set(array, index, value)`.",
+        example: Some("let xs: array<int> = [1, 2];\nxs[0] = 5;"),
+        fix: Some("let xs: array<int> = [1, 2];\nset(xs, 0, 5);"),
+    },
+    ErrorCode {
+        code: "AX0203",
+        title: "module not found",
+        explanation: "An `import` did not resolve to a file. The message lists every path that was searched: the importing file's directory, the project's `src/includes`, each `AXITY_PATH` entry and the stdlib root.",
+        example: Some("import \"utils.ax\";"),
+        fix: Some("import \"includes/utils.ax\";"),
+    },
+    ErrorCode {
+        code: "AX0204",
+        title: "import read error",
+        explanation: "An imported file was found but could not be read, for example because of permissions or invalid UTF-8.",
+        example: Some("import \"locked.ax\";"),
+        fix: Some("import \"readable.ax\";"),
+    },
+    ErrorCode {
+        code: "AX0300",
+        title: "type error",
+        explanation: "The program is syntactically valid but does not type-check. This generic code is the fallback for type errors without a more specific code; every error the type checker currently reports has one.",
+        example: None,
+        fix: None,
+    },
+    ErrorCode {
+        code: "AX0301",
+        title: "undefined variable",
+        explanation: "A name was used that is not declared in any enclosing scope. Variables must be declared with `let` before use; variables declared inside a block are not visible after it.",
+        example: Some("print(total);"),
+        fix: Some("let total: int = 0;\nprint(total);"),
+    },
+    ErrorCode {
+        code: "AX0302",
+        title: "type mismatch",
+        explanation: "A value of one type was used where another was required: assigning to a typed field, pushing into or setting an element of a typed array, passing an argument to a native function, or mixing element types in an array literal. The note shows the expected and found types.",
+        example: Some("let xs: array<int> = [1, 2];\npush(xs, \"three\");"),
+        fix: Some("let xs: array<int> = [1, 2];\npush(xs, 3);"),
+    },
+    ErrorCode {
+        code: "AX0303",
+        title: "wrong number of arguments",
+        explanation: "A script function or native function was called with a different number of arguments than it declares. The label points at the declaration.",
+        example: Some("fn add(a: int, b: int) -> int { return a + b; }\nprint(add(1));"),
+        fix: Some("fn add(a: int, b: int) -> int { return a + b; }\nprint(add(1, 2));"),
+    },
+    ErrorCode {
+        code: "AX0304",
+        title: "wrong number of arguments to builtin",
+        explanation: "A builtin was called with the wrong number of arguments. The message shows the expected parameters.",
+        example: Some("print(substr(\"hello\", 1));"),
+        fix: Some("print(substr(\"hello\", 1, 3));"),
+    },
+    ErrorCode {
+        code: "AX0305",
+        title: "wrong argument type for builtin",
+        explanation: "A builtin was called with an argument of the wrong type. The message shows the expected parameter types.",
+        example: Some("print(strlen(42));"),
+        fix: Some("print(strlen(\"42\"));"),
+    },
+    ErrorCode {
+        code: "AX0306",
+        title: "duplicate definition",
+        explanation: "A function, class, field, method or variable was declared twice in the same scope. The label points at the first definition.",
+        example: Some("fn f() -> int { return 1; }\nfn f() -> int { return 2; }"),
+        fix: Some("fn f() -> int { return 1; }\nfn g() -> int { return 2; }"),
+    },
+    ErrorCode {
+        code: "AX0307",
+        title: "missing return",
+        explanation: "A function declared to return `int` can reach the end of its body without a `return` or `throw`. Both branches of an `if`, every `match` arm and the `default`, and a `try` body together with each of its catch blocks must leave the function; a loop body never counts, since it may not run.",
+        example: Some("fn answer() -> int {\n  print(42);\n}"),
+        fix: Some("fn answer() -> int {\n  return 42;\n}"),
+    },
+    ErrorCode {
+        code: "AX0308",
+        title: "unknown class, field or method",
+        explanation: "A class name, field or method was used that the class does not declare.",
+        example: Some("class P { let x: int; }\nlet p: P = new P;\nprint(p.y);"),
+        fix: Some("class P { let x: int; }\nlet p: P = new P;\nprint(p.x);"),
+    },
+    ErrorCode {
+        code: "AX0309",
+        title: "invalid operand type",
+        explanation: "An operator was applied to a value of the wrong type: `and`/`or` and `!` need `bool`, `~` needs `int`, and array indexes must be `int`.",
+        example: Some("if !1 { print(\"no\"); }"),
+        fix: Some("if !true { print(\"no\"); }"),
+    },
+    ErrorCode {
+        code: "AX0310",
+        title: "value cannot be used this way",
+        explanation: "A value was used as something it is not: member access or method calls on a non-class value, indexing a non-array, calling a non-function, or iterating over something other than an array or map.",
+        example: Some("let n: int = 3;\nprint(n[0]);"),
+        fix: Some("let n: array<int> = [3];\nprint(n[0]);"),
+    },
+    ErrorCode {
+        code: "AX0311",
+        title: "empty array literal needs type context",
+        explanation: "The element type of `[]` cannot be inferred. Start from a non-empty literal or build the array with `push`.",
+        example: Some("print(len([]));"),
+        fix: Some("let xs: array<int> = [0];\nprint(len(xs));"),
+    },
+    ErrorCode {
+        code: "AX0312",
+        title: "builtin disabled by the sandbox",
+        explanation: "The embedding runtime's capabilities do not allow this builtin (for example file access under `Capabilities::read_only()` or `input` without stdin), so the call is rejected before the script runs.",
+        example: Some("write_file(\"out.txt\", \"data\");"),
+        fix: Some("print(\"data\");"),
+    },
+    ErrorCode {
+        code: "AX0313",
+        title: "rethrow outside a catch block",
+        explanation: "A bare `throw;` re-raises the exception the enclosing `catch` block is handling, so it can only appear inside one. The type checker rejects it elsewhere, and the interpreter reports the same code if one is reached with no exception being handled.",
+        example: Some("throw;"),
+        fix: Some("try {\n  throw \"failed\";\n} catch e {\n  throw;\n}"),
+    },
+    ErrorCode {
+        code: "AX0314",
+        title: "retry outside a catch block with attempts",
+        explanation: "`retry` re-runs the body of a `try (attempts: n)` from one of its `catch` blocks. Anywhere else, including directly in a loop or in the catch block of a `try` without `attempts`, it has nothing to act on; use `continue` to skip to the next loop iteration.",
+        example: Some("fn f() -> int {\n  retry;\n  return 0;\n}"),
+        fix: Some("try (attempts: 3) {\n  print(read_file(\"data.txt\"));\n} catch e {\n  retry;\n}"),
+    },
+    ErrorCode {
+        code: "AX0315",
+        title: "break or continue outside a loop",
+        explanation: "`break` leaves a loop and `continue` skips to its next iteration, so both need an enclosing `while`, `do`/`while` or `for` loop. A labelled `break outer;` or `continue outer;` also needs an enclosing loop labelled `outer:`.",
+        example: Some("for x in [1, 2] {\n  print(x);\n}\nbreak outer;"),
+        fix: Some("outer: for x in [1, 2] {\n  for y in [3, 4] {\n    if y == 4 { break outer; }\n  }\n}"),
+    },
+    ErrorCode {
+        code: "AX0400",
+        title: "runtime error",
+        explanation: "The script failed while running. This generic code is used for errors raised by native host functions that do not set their own code with `with_code`, for example a host `fetch(url)` whose request failed. Look at the host function named in the backtrace.",
+        example: None,
+        fix: None,
+    },
+    ErrorCode {
+        code: "AX0401",
+        title: "division by zero",
+        explanation: "An integer or float was divided by zero, or `%` was used with a zero divisor. Check the divisor before dividing.",
+        example: Some("let d: int = 0;\nprint(10 / d);"),
+        fix: Some("let d: int = 0;\nif d != 0 { print(10 / d); }"),
+    },
+    ErrorCode {
+        code: "AX0402",
+        title: "index out of bounds",
+        explanation: "An array, string or buffer was indexed outside its length, or `pop` was called on an empty array. Valid indexes run from 0 to `len(x) - 1`.",
+        example: Some("let xs: array<int> = [1, 2];\nprint(xs[2]);"),
+        fix: Some("let xs: array<int> = [1, 2];\nprint(xs[len(xs) - 1]);"),
+    },
+    ErrorCode {
+        code: "AX0403",
+        title: "uncaught exception",
+        explanation: "A value was thrown with `throw` and no enclosing `try`/`catch` handled it. The backtrace shows where it was thrown.",
+        example: Some("throw \"failed\";"),
+        fix: Some("try {\n  throw \"failed\";\n} catch e {\n  print(e);\n}"),
+    },
+    ErrorCode {
+        code: "AX0404",
+        title: "undefined name at runtime",
+        explanation: "A function, method, class, field or variable could not be found while running. The type checker normally catches these; at runtime they usually come from values typed `any`.",
+        example: Some("class P {\n    let x: int;\n}\nlet p: any = new P();\nprint(p.y);"),
+        fix: Some("class P {\n    let x: int;\n}\nlet p: any = new P();\nprint(p.x);"),
+    },
+    ErrorCode {
+        code: "AX0405",
+        title: "runtime type error",
+        explanation: "A value had the wrong type for an operation while running, typically because it came through `any`, `obj` or a native function. The message names the operation and the expected type.",
+        example: Some("let v: any = \"5\";\nprint(v + 1);"),
+        fix: Some("let v: any = 5;\nprint(v + 1);"),
+    },
+    ErrorCode {
+        code: "AX0406",
+        title: "wrong number of arguments at runtime",
+        explanation: "A function, lambda or builtin was called with the wrong number of arguments while running, typically through a value typed `any` or from host code via `CompiledProgram::call`.",
+        example: Some("let f: any = fn(a: int) -> int { return a; };\nprint(f(1, 2));"),
+        fix: Some("let f: any = fn(a: int) -> int { return a; };\nprint(f(1));"),
+    },
+    ErrorCode {
+        code: "AX0407",
+        title: "I/O error",
+        explanation: "Reading, writing or creating a file or directory failed. The message includes the operating system's reason.",
+        example: Some("print(read_file(\"missing.txt\"));"),
+        fix: Some("if exists(\"missing.txt\") { print(read_file(\"missing.txt\")); }"),
+    },
+    ErrorCode {
+        code: "AX0408",
+        title: "invalid data format",
+        explanation: "JSON or TOML content could not be parsed or produced, or a value could not be converted to or from a host type.",
+        example: Some("print(json_get(\"{oops\", \"a\"));"),
+        fix: Some("print(json_get(\"{\\\"a\\\": 1}\", \"a\"));"),
+    },
+    ErrorCode {
+        code: "AX0409",
+        title: "matrix shape mismatch",
+        explanation: "`matrix_mul` needs rectangular matrices where the column count of the first equals the row count of the second.",
+        example: Some("print(matrix_mul([[1, 2]], [[1, 2]]));"),
+        fix: Some("print(matrix_mul([[1, 2]], [[1], [2]]));"),
+    },
+    ErrorCode {
+        code: "AX0410",
+        title: "invalid try option",
+        explanation: "`try (attempts: n, backoff: ms)` needs at least one attempt and a backoff that is not negative. Both options are evaluated each time the `try` statement starts.",
+        example: Some("try (attempts: 0) {\n  print(read_file(\"data.txt\"));\n} catch e {\n  retry;\n}"),
+        fix: Some("try (attempts: 3) {\n  print(read_file(\"data.txt\"));\n} catch e {\n  retry;\n}"),
+    },
+    ErrorCode {
+        code: "AX0501",
+        title: "permission denied",
+        explanation: "The script used a capability the runtime does not grant: filesystem reads or writes, paths outside the allowed prefixes, or stdin.",
+        example: Some("print(read_file(\"/etc/passwd\"));"),
+        fix: Some("print(read_file(\"data/input.txt\"));"),
+    },
+    ErrorCode {
+        code: "AX0502",
+        title: "step limit exceeded",
+        explanation: "The script evaluated more statements and expressions than `Limits::max_steps` allows, usually because of an unbounded loop.",
+        example: Some("while true { }"),
+        fix: Some("let i: int = 0;\nwhile i < 10 { i++; }"),
+    },
+    ErrorCode {
+        code: "AX0503",
+        title: "timeout",
+        explanation: "The script ran longer than `Limits::timeout`.",
+        example: Some("while true { }"),
+        fix: Some("let i: int = 0;\nwhile i < 10 { i++; }"),
+    },
+    ErrorCode {
+        code: "AX0504",
+        title: "call depth exceeded",
+        explanation: "More nested function, method or lambda calls were active than `Limits::max_call_depth` allows, usually because of recursion without a base case.",
+        example: Some("fn f(n: int) -> int { return f(n + 1); }\nprint(f(0));"),
+        fix: Some("fn f(n: int) -> int {\n  if n > 10 { return n; }\n  return f(n + 1);\n}\nprint(f(0));"),
+    },
+    ErrorCode {
+        code: "AX0505",
+        title: "memory limit exceeded",
+        explanation: "The script kept more arrays, maps, objects and buffers alive than `Limits::max_allocations` allows.",
+        example: Some("let xs: array<array<int> > = [[0]];\nwhile true { push(xs, [1]); }"),
+        fix: Some("let xs: array<array<int> > = [[0]];\nlet i: int = 0;\nwhile i < 10 { push(xs, [1]); i++; }"),
+    },
+    ErrorCode {
+        code: "AX0506",
+        title: "interrupted",
+        explanation: "The host cancelled the script through `InterruptHandle::interrupt`. The script stops at the next loop iteration or call.",
+        example: Some("while true { }"),
+        fix: Some("let i: int = 0;\nwhile i < 10 { i++; }"),
+    },
+];
@@ -0,0 +1,167 @@
+use serde_json::{json, Value};
+use crate::error::{AxityError, SourceFile, SourceMap, Span, Warning};
+
+// A secondary location shown under the main snippet, e.g. where a function was declared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+const RED: &str = "1;31";
+const BLUE: &str = "1;34";
+const YELLOW: &str = "1;33";
+const BOLD: &str = "1";
+
+struct Painter { color: bool }
+
+impl Painter {
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color && !text.is_empty() { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text.to_string() }
+    }
+}
+
+impl AxityError {
+    // Multi-line diagnostic with the offending source line underlined, secondary labels and notes.
+    // Falls back to the one-line header when the sources are not available.
+    pub fn render(&self, color: bool) -> String {
+        let p = Painter { color };
+        let header = format!("{}: {}", p.paint(RED, &format!("{}[{}]", self.kind.title(), self.code)), p.paint(BOLD, self.kind.message()));
+        self.parts().render(&p, header, RED)
+    }
+
+    // One JSON object for this error (not `others`): kind, severity, code, message, file, range,
+    // labels, notes and backtrace. Ranges are 1-based lines and columns; `end` is exclusive.
+    pub fn to_json(&self) -> Value {
+        let mut obj = self.parts().to_json(self.kind.name(), "error", self.code, self.kind.message());
+        obj["backtrace"] = self.details.trace.iter().map(|fr| json!({
+            "function": fr.function,
+            "file": fr.path.as_deref(),
+            "line": fr.span.as_ref().map(|s| s.line),
+            "column": fr.span.as_ref().map(|s| s.col),
+        })).collect::<Vec<_>>().into();
+        obj
+    }
+
+    // This error and every one in `others`, one JSON object per line.
+    pub fn render_json(&self) -> String {
+        self.all().map(|e| e.to_json().to_string()).collect::<Vec<_>>().join("\n")
+    }
+
+    fn parts(&self) -> Parts<'_> {
+        Parts { span: self.span.as_ref(), labels: &self.details.labels, notes: &self.details.notes, sources: self.details.sources.as_ref(), path: self.path.as_deref() }
+    }
+}
+
+impl Warning {
+    // Same layout as `AxityError::render`, headed `warning[lint]` (or `error[lint]` when denied).
+    pub fn render(&self, color: bool) -> String {
+        let p = Painter { color };
+        let (title, code) = if self.denied { ("error", RED) } else { ("warning", YELLOW) };
+        let header = format!("{}: {}", p.paint(code, &format!("{}[{}]", title, self.lint)), p.paint(BOLD, &self.message));
+        self.parts().render(&p, header, code)
+    }
+
+    pub fn to_json(&self) -> Value {
+        self.parts().to_json("lint", if self.denied { "error" } else { "warning" }, self.lint, &self.message)
+    }
+
+    fn parts(&self) -> Parts<'_> {
+        Parts { span: Some(&self.span), labels: &self.labels, notes: &self.notes, sources: self.sources.as_ref(), path: self.path.as_deref() }
+    }
+}
+
+// The pieces of a diagnostic shared by errors and warnings.
+struct Parts<'a> {
+    span: Option<&'a Span>,
+    labels: &'a [Label],
+    notes: &'a [String],
+    sources: Option<&'a SourceMap>,
+    path: Option<&'a str>,
+}
+
+impl Parts<'_> {
+    fn render(&self, p: &Painter, header: String, primary: &str) -> String {
+        let mut out = header;
+        let lines: Vec<usize> = self.span.into_iter().chain(self.labels.iter().map(|l| &l.span)).map(|s| s.line).collect();
+        let width = lines.iter().max().map(|n| n.to_string().len()).unwrap_or(1);
+        let gutter = " ".repeat(width);
+        let mut shown_file = None;
+        if let Some(sp) = self.span {
+            out.push_str(&format!("\n{}{} {}", gutter, p.paint(BLUE, "-->"), self.location(sp)));
+            if let Some(file) = self.file_of(sp) {
+                out.push_str(&format!("\n{} {}", gutter, p.paint(BLUE, "|")));
+                out.push_str(&snippet(p, file, sp, '^', primary, "", width));
+                shown_file = Some(sp.file);
+            }
+        }
+        for label in self.labels {
+            let Some(file) = self.file_of(&label.span) else {
+                out.push_str(&format!("\n{} {} {}: {}", gutter, p.paint(BLUE, "="), p.paint(BOLD, "note"), label.message));
+                continue;
+            };
+            if shown_file == Some(label.span.file) {
+                out.push_str(&format!("\n{} {}", gutter, p.paint(BLUE, "|")));
+            } else {
+                out.push_str(&format!("\n{}{} {}", gutter, p.paint(BLUE, ":::"), self.location(&label.span)));
+                shown_file = Some(label.span.file);
+            }
+            out.push_str(&snippet(p, file, &label.span, '-', BLUE, &label.message, width));
+        }
+        for note in self.notes {
+            out.push_str(&format!("\n{} {} {}: {}", gutter, p.paint(BLUE, "="), p.paint(BOLD, "note"), note));
+        }
+        out
+    }
+
+    fn to_json(&self, kind: &str, severity: &str, code: &str, message: &str) -> Value {
+        let file = |sp: &Span| self.sources.and_then(|m| m.name(sp.file)).or(self.path).map(str::to_string);
+        json!({
+            "kind": kind,
+            "severity": severity,
+            "code": code,
+            "message": message,
+            "file": self.span.and_then(file),
+            "range": self.span.map(|sp| self.json_range(sp)),
+            "labels": self.labels.iter().map(|l| json!({ "message": l.message, "file": file(&l.span), "range": self.json_range(&l.span) })).collect::<Vec<_>>(),
+            "notes": self.notes,
+        })
+    }
+
+    fn json_range(&self, span: &Span) -> Value {
+        let end = match self.sources.and_then(|m| m.get(span.file)) {
+            Some(f) if span.end > span.start => f.position(span.end),
+            _ => (span.line, span.col + 1),
+        };
+        json!({
+            "start": { "line": span.line, "column": span.col, "offset": span.start },
+            "end": { "line": end.0, "column": end.1, "offset": span.end.max(span.start) },
+        })
+    }
+
+    fn file_of(&self, span: &Span) -> Option<&SourceFile> {
+        self.sources?.get(span.file).filter(|f| f.line(span.line).is_some())
+    }
+
+    fn location(&self, span: &Span) -> String {
+        match self.sources.and_then(|m| m.name(span.file)).or(self.path) {
+            Some(name) => format!("{}:{}:{}", name, span.line, span.col),
+            None => format!("{}:{}", span.line, span.col),
+        }
+    }
+}
+
+// One numbered source line followed by a marker line underlining `span`.
+fn snippet(p: &Painter, file: &SourceFile, span: &Span, mark: char, code: &str, message: &str, width: usize) -> String {
+    let text = file.line(span.line).unwrap_or("");
+    let before: String = text.chars().take(span.col.saturating_sub(1)).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    let rest = text.chars().count().saturating_sub(span.col.saturating_sub(1));
+    let covered = file.src.get(span.start..span.end).map(|s| s.chars().take_while(|c| *c != '\n').count()).unwrap_or(0);
+    let len = covered.min(rest).max(1);
+    let marker = mark.to_string().repeat(len);
+    let num = format!("{:>w$}", span.line, w = width);
+    let mut out = format!("\n{} {} {}", p.paint(BLUE, &num), p.paint(BLUE, "|"), text);
+    out.push_str(&format!("\n{} {} {}{}", " ".repeat(width), p.paint(BLUE, "|"), before, p.paint(code, &marker)));
+    if !message.is_empty() { out.push_str(&format!(" {}", p.paint(code, message))); }
+    out
+}
@@ -1,9 +1,43 @@
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone)]
+pub mod codes;
+mod diagnostic;
+mod source_map;
+mod warning;
+pub use diagnostic::Label;
+pub use source_map::{FileId, SourceFile, SourceMap};
+pub use warning::Warning;
+
+// `start..end` is a byte range into the file's source.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Span {
+    pub file: FileId,
     pub line: usize,
     pub col: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(file: FileId, line: usize, col: usize, start: usize, end: usize) -> Self { Self { file, line, col, start, end } }
+}
+
+// One line of a backtrace: the function and the location reached inside it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFrame {
+    pub function: String,
+    pub span: Option<Span>,
+    pub path: Option<Box<str>>,
+}
+
+impl Display for TraceFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.span, &self.path) {
+            (Some(s), Some(p)) => write!(f, "at {} ({}:{}:{})", self.function, p, s.line, s.col),
+            (Some(s), None) => write!(f, "at {} ({}:{})", self.function, s.line, s.col),
+            _ => write!(f, "at {}", self.function),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -12,30 +46,165 @@ pub enum AxityErrorKind {
     Parse(String),
     Type(String),
     Runtime(String),
+    Permission(String),
+    StepLimit(String),
+    Timeout(String),
+    CallDepth(String),
+    MemoryLimit(String),
+    Interrupted(String),
+}
+
+impl AxityErrorKind {
+    // Heading used by rendered diagnostics, e.g. "type error".
+    pub fn title(&self) -> &'static str {
+        match self {
+            AxityErrorKind::Lex(_) => "lex error",
+            AxityErrorKind::Parse(_) => "parse error",
+            AxityErrorKind::Type(_) => "type error",
+            AxityErrorKind::Runtime(_) => "runtime error",
+            AxityErrorKind::Permission(_) => "permission denied",
+            AxityErrorKind::StepLimit(_) => "step limit exceeded",
+            AxityErrorKind::Timeout(_) => "timeout",
+            AxityErrorKind::CallDepth(_) => "call depth exceeded",
+            AxityErrorKind::MemoryLimit(_) => "memory limit exceeded",
+            AxityErrorKind::Interrupted(_) => "interrupted",
+        }
+    }
+    // Code used when the construction site does not pick a more specific one.
+    pub fn default_code(&self) -> &'static str {
+        match self {
+            AxityErrorKind::Lex(_) => "AX0100",
+            AxityErrorKind::Parse(_) => "AX0200",
+            AxityErrorKind::Type(_) => "AX0300",
+            AxityErrorKind::Runtime(_) => "AX0400",
+            AxityErrorKind::Permission(_) => "AX0501",
+            AxityErrorKind::StepLimit(_) => "AX0502",
+            AxityErrorKind::Timeout(_) => "AX0503",
+            AxityErrorKind::CallDepth(_) => "AX0504",
+            AxityErrorKind::MemoryLimit(_) => "AX0505",
+            AxityErrorKind::Interrupted(_) => "AX0506",
+        }
+    }
+    // Stable identifier used in machine-readable output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AxityErrorKind::Lex(_) => "lex",
+            AxityErrorKind::Parse(_) => "parse",
+            AxityErrorKind::Type(_) => "type",
+            AxityErrorKind::Runtime(_) => "runtime",
+            AxityErrorKind::Permission(_) => "permission",
+            AxityErrorKind::StepLimit(_) => "step_limit",
+            AxityErrorKind::Timeout(_) => "timeout",
+            AxityErrorKind::CallDepth(_) => "call_depth",
+            AxityErrorKind::MemoryLimit(_) => "memory_limit",
+            AxityErrorKind::Interrupted(_) => "interrupted",
+        }
+    }
+    pub fn message(&self) -> &str {
+        match self {
+            AxityErrorKind::Lex(m) | AxityErrorKind::Parse(m) | AxityErrorKind::Type(m) | AxityErrorKind::Runtime(m)
+            | AxityErrorKind::Permission(m) | AxityErrorKind::StepLimit(m) | AxityErrorKind::Timeout(m)
+            | AxityErrorKind::CallDepth(m) | AxityErrorKind::MemoryLimit(m) | AxityErrorKind::Interrupted(m) => m,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AxityError {
     pub kind: AxityErrorKind,
+    // Stable code such as `AX0302`, documented by `codes::explain`.
+    pub code: &'static str,
     pub span: Option<Span>,
+    // File the span points into, filled in from the `SourceMap` once the error leaves the compiler.
+    pub path: Option<Box<str>>,
+    // Boxed so that `Result<_, AxityError>` stays small on the success path.
+    pub details: Box<ErrorDetails>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ErrorDetails {
+    // Active calls when the error was raised, innermost first; empty for errors outside any call.
+    pub trace: Vec<TraceFrame>,
+    // Secondary spans (e.g. where a name was declared) and notes shown under the snippet.
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    // Sources of the program the error came from, attached alongside `path`.
+    pub sources: Option<SourceMap>,
+    // Further errors from the same pass (e.g. every type error in a script), reported after this one.
+    pub others: Vec<AxityError>,
 }
 
 impl AxityError {
-    pub fn lex(msg: &str, span: Span) -> Self { Self { kind: AxityErrorKind::Lex(msg.to_string()), span: Some(span) } }
-    pub fn parse(msg: &str, span: Span) -> Self { Self { kind: AxityErrorKind::Parse(msg.to_string()), span: Some(span) } }
-    pub fn ty(msg: &str, span: Span) -> Self { Self { kind: AxityErrorKind::Type(msg.to_string()), span: Some(span) } }
-    pub fn rt(msg: &str) -> Self { Self { kind: AxityErrorKind::Runtime(msg.to_string()), span: None } }
+    fn with_kind(kind: AxityErrorKind, span: Option<Span>) -> Self {
+        Self { code: kind.default_code(), kind, span, path: None, details: Box::default() }
+    }
+    pub fn lex(msg: &str, span: Span) -> Self { Self::with_kind(AxityErrorKind::Lex(msg.to_string()), Some(span)) }
+    pub fn parse(msg: &str, span: Span) -> Self { Self::with_kind(AxityErrorKind::Parse(msg.to_string()), Some(span)) }
+    pub fn ty(msg: &str, span: Span) -> Self { Self::with_kind(AxityErrorKind::Type(msg.to_string()), Some(span)) }
+    pub fn rt(msg: &str) -> Self { Self::with_kind(AxityErrorKind::Runtime(msg.to_string()), None) }
+    pub fn new(kind: AxityErrorKind) -> Self { Self::with_kind(kind, None) }
+    pub fn perm(msg: &str) -> Self { Self::with_kind(AxityErrorKind::Permission(msg.to_string()), None) }
+    pub fn with_code(mut self, code: &'static str) -> Self { self.code = code; self }
+    pub fn with_label(mut self, span: &Span, message: &str) -> Self { self.details.labels.push(Label { span: span.clone(), message: message.to_string() }); self }
+    pub fn with_note(mut self, note: &str) -> Self { self.details.notes.push(note.to_string()); self }
+    pub fn with_others(mut self, others: Vec<AxityError>) -> Self { self.details.others.extend(others); self }
+    // This error followed by `others`.
+    pub fn all(&self) -> impl Iterator<Item = &AxityError> { std::iter::once(self).chain(self.details.others.iter()) }
+    // Very deep traces (runaway recursion) keep only the innermost and outermost frames.
+    pub fn backtrace(&self) -> String {
+        const KEEP: usize = 10;
+        let mut out = String::from("stack backtrace:");
+        let n = self.details.trace.len();
+        for (i, fr) in self.details.trace.iter().enumerate() {
+            if n > 2 * KEEP && i >= KEEP && i < n - KEEP {
+                if i == KEEP { out.push_str(&format!("\n  ... {} frames omitted", n - 2 * KEEP)); }
+                continue;
+            }
+            out.push_str(&format!("\n  {}", fr));
+        }
+        out
+    }
+    // The `kind` a script sees when this error is raised inside `try`. Only runtime faults can be caught;
+    // limits, interrupts, permission denials and uncaught throws stay fatal (`None`).
+    pub fn exception_kind(&self) -> Option<&'static str> {
+        if !matches!(self.kind, AxityErrorKind::Runtime(_)) { return None; }
+        match self.code {
+            "AX0401" => Some("arithmetic"),
+            "AX0402" => Some("index"),
+            "AX0403" => None,
+            "AX0404" => Some("name"),
+            "AX0405" | "AX0406" => Some("type"),
+            "AX0407" => Some("io"),
+            "AX0408" => Some("format"),
+            "AX0409" | "AX0410" => Some("value"),
+            _ => Some("runtime"),
+        }
+    }
+    // Keeps an existing (more precise) span and only fills in a missing one.
+    pub fn or_span(mut self, span: &Span) -> Self { if self.span.is_none() { self.span = Some(span.clone()); } self }
 }
 
 
 impl Display for AxityError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match (&self.kind, &self.span) {
-            (AxityErrorKind::Lex(m), Some(s)) => write!(f, "lex error at {}:{}: {}", s.line, s.col, m),
-            (AxityErrorKind::Parse(m), Some(s)) => write!(f, "parse error at {}:{}: {}", s.line, s.col, m),
-            (AxityErrorKind::Type(m), Some(s)) => write!(f, "type error at {}:{}: {}", s.line, s.col, m),
-            (AxityErrorKind::Runtime(m), Some(s)) => write!(f, "runtime error at {}:{}: {}", s.line, s.col, m),
+        let at = match (&self.span, &self.path) {
+            (Some(s), Some(p)) => Some(format!("{}:{}:{}", p, s.line, s.col)),
+            (Some(s), None) => Some(format!("{}:{}", s.line, s.col)),
+            _ => None,
+        };
+        match (&self.kind, at) {
+            (AxityErrorKind::Lex(m), Some(a)) => write!(f, "lex error at {}: {}", a, m),
+            (AxityErrorKind::Parse(m), Some(a)) => write!(f, "parse error at {}: {}", a, m),
+            (AxityErrorKind::Type(m), Some(a)) => write!(f, "type error at {}: {}", a, m),
+            (AxityErrorKind::Runtime(m), Some(a)) => write!(f, "runtime error at {}: {}", a, m),
             (AxityErrorKind::Runtime(m), None) => write!(f, "runtime error: {}", m),
+            (AxityErrorKind::Permission(m), Some(a)) => write!(f, "permission denied at {}: {}", a, m),
+            (AxityErrorKind::Permission(m), None) => write!(f, "permission denied: {}", m),
+            (AxityErrorKind::StepLimit(m), _) => write!(f, "step limit exceeded: {}", m),
+            (AxityErrorKind::Timeout(m), _) => write!(f, "timeout: {}", m),
+            (AxityErrorKind::CallDepth(m), _) => write!(f, "call depth exceeded: {}", m),
+            (AxityErrorKind::MemoryLimit(m), _) => write!(f, "memory limit exceeded: {}", m),
+            (AxityErrorKind::Interrupted(m), _) => write!(f, "interrupted: {}", m),
             (k, None) => write!(f, "{:?}", k),
         }
     }
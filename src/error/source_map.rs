@@ -0,0 +1,83 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use crate::error::{AxityError, Span, Warning};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FileId(pub u32);
+
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub src: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(name: String, src: String) -> Self {
+        let line_starts = std::iter::once(0).chain(src.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        Self { name, src, line_starts }
+    }
+    // 1-based line text without its newline.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self.line_starts.get(line).map(|e| e - 1).unwrap_or(self.src.len());
+        Some(self.src[start..end].trim_end_matches('\r'))
+    }
+    pub fn line_count(&self) -> usize { self.line_starts.len() }
+    // 1-based line and character column of a byte offset.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.src.len());
+        let idx = self.line_starts.partition_point(|&s| s <= offset) - 1;
+        let start = self.line_starts[idx];
+        let col = self.src.get(start..offset).map(|t| t.chars().count()).unwrap_or(offset - start);
+        (idx + 1, col + 1)
+    }
+}
+
+// Every file that went into a program, indexed by the `FileId` stored in spans.
+// Clones share the files, so errors can keep the map around to render snippets.
+#[derive(Clone, Default)]
+pub struct SourceMap {
+    files: Arc<Vec<SourceFile>>,
+}
+
+impl Debug for SourceMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.files.iter().map(|sf| &sf.name)).finish()
+    }
+}
+
+impl SourceMap {
+    pub fn new() -> Self { Self::default() }
+    pub fn add(&mut self, name: &str, src: &str) -> FileId {
+        Arc::make_mut(&mut self.files).push(SourceFile::new(name.to_string(), src.to_string()));
+        FileId((self.files.len() - 1) as u32)
+    }
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> { self.files.get(id.0 as usize) }
+    pub fn name(&self, id: FileId) -> Option<&str> { self.get(id).map(|f| f.name.as_str()) }
+    pub fn files(&self) -> &[SourceFile] { &self.files }
+    pub fn location(&self, span: &Span) -> String {
+        match self.name(span.file) {
+            Some(n) => format!("{}:{}:{}", n, span.line, span.col),
+            None => format!("{}:{}", span.line, span.col),
+        }
+    }
+    // Records which file the error's span points into so it displays as `path:line:col`,
+    // and keeps the sources so `AxityError::render` can show the offending lines.
+    pub fn attach(&self, mut err: AxityError) -> AxityError {
+        if err.path.is_none() {
+            if let Some(sp) = &err.span { err.path = self.name(sp.file).map(|n| n.into()); }
+        }
+        for fr in err.details.trace.iter_mut().filter(|fr| fr.path.is_none()) {
+            if let Some(sp) = &fr.span { fr.path = self.name(sp.file).map(|n| n.into()); }
+        }
+        if err.details.sources.is_none() { err.details.sources = Some(self.clone()); }
+        err.details.others = std::mem::take(&mut err.details.others).into_iter().map(|o| self.attach(o)).collect();
+        err
+    }
+    pub fn attach_warning(&self, mut w: Warning) -> Warning {
+        if w.path.is_none() { w.path = self.name(w.span.file).map(|n| n.into()); }
+        if w.sources.is_none() { w.sources = Some(self.clone()); }
+        w
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt::{Display, Formatter};
+use crate::error::{Label, SourceMap, Span};
+
+// A lint finding. Unlike `AxityError` it does not stop compilation; `denied` is set when the
+// lint's configured level turns it into an error.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    // Name of the lint that fired, e.g. `unused_variable`.
+    pub lint: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub denied: bool,
+    pub path: Option<Box<str>>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub sources: Option<SourceMap>,
+}
+
+impl Warning {
+    pub fn new(lint: &'static str, message: &str, span: &Span) -> Self {
+        Self { lint, message: message.to_string(), span: span.clone(), denied: false, path: None, labels: Vec::new(), notes: Vec::new(), sources: None }
+    }
+    pub fn with_label(mut self, span: &Span, message: &str) -> Self { self.labels.push(Label { span: span.clone(), message: message.to_string() }); self }
+    pub fn with_note(mut self, note: &str) -> Self { self.notes.push(note.to_string()); self }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let title = if self.denied { "error" } else { "warning" };
+        match &self.path {
+            Some(p) => write!(f, "{} at {}:{}:{}: {} [{}]", title, p, self.span.line, self.span.col, self.message, self.lint),
+            None => write!(f, "{} at {}:{}: {} [{}]", title, self.span.line, self.span.col, self.message, self.lint),
+        }
+    }
+}
@@ -1,13 +1,19 @@
 use crate::ast::*;
-use crate::error::AxityError;
-use crate::runtime::{Runtime, Value, Object};
+use crate::error::{AxityError, Span};
+use crate::runtime::{Runtime, Value, Object, Lambda};
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::Path;
 
 const SCALE: i64 = 1_000_000;
 pub fn execute(p: &Program, rt: &mut Runtime, out: &mut String) -> Result<(), AxityError> {
-    // build indexes
+    build_index(p, rt);
+    rt.program_id = None;
+    run(p, rt, out)
+}
+
+pub fn build_index(p: &Program, rt: &mut Runtime) {
     rt.func_index.clear();
     rt.class_index.clear();
     for (i, it) in p.items.iter().enumerate() {
@@ -17,18 +23,29 @@ pub fn execute(p: &Program, rt: &mut Runtime, out: &mut String) -> Result<(), Ax
             _ => {}
         }
     }
+}
+
+// Runs top-level statements and `main`; expects `build_index` to have been called for `p`.
+pub fn run(p: &Program, rt: &mut Runtime, out: &mut String) -> Result<(), AxityError> {
+    rt.begin_run();
+    let r = run_items(p, rt, out);
+    rt.end_run();
+    r
+}
+
+fn run_items(p: &Program, rt: &mut Runtime, out: &mut String) -> Result<(), AxityError> {
     for it in &p.items {
         if let Item::Stmt(s) = it {
             match exec_stmt(p, s, rt, out)? {
                 Control::Next => {}
                 Control::Return(_) => {}
-                Control::Retry => {}
-                Control::Throw(e) => { return Err(AxityError::rt(&format!("uncaught exception: {}", fmt_value(&e, 2)))); }
+                Control::Retry | Control::Break(_) | Control::Continue(_) => {}
+                Control::Throw(e) => { return Err(rt.uncaught(&fmt_value(&e, 2))); }
             }
         }
     }
     if rt.func_index.contains_key("main") {
-        let rv = call_func("main", &[], p, rt, out)?;
+        let rv = call_func("main", &[], None, p, rt, out)?;
         rt.emit(out, &fmt_value(&rv, 2));
         rt.emit(out, "\n");
     }
@@ -137,7 +154,13 @@ fn forc_match(initst: &Stmt, cond: &Expr, postst: &Stmt, rt: &Runtime) -> Option
     }
     None
 }
+// Errors raised while running `s` get its span unless a nested statement or expression already set one.
 fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Result<Control, AxityError> {
+    exec_stmt_at(p, s, rt, out).map_err(|e| e.or_span(s.span()))
+}
+
+fn exec_stmt_at(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Result<Control, AxityError> {
+    rt.tick()?;
     match s {
         Stmt::Let{ name, init, .. } => { let v = eval_expr(p, init, rt, out)?; rt.set(name.clone(), v); Ok(Control::Next) }
         Stmt::Assign{ name, expr, .. } => {
@@ -152,7 +175,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                                 Value::Bool(b) => if b {1} else {0},
                                 _ => {
                                     let v = eval_expr(p, expr, rt, out)?;
-                                    if !rt.assign(name, v) { return Err(AxityError::rt("assign to undefined variable")); }
+                                    if !rt.assign(name, v) { return Err(AxityError::rt("assign to undefined variable").with_code("AX0404")); }
                                     return Ok(Control::Next);
                                 }
                             };
@@ -160,8 +183,8 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                                 BinOp::Add => ci + ri,
                                 BinOp::Sub => ci - ri,
                                 BinOp::Mul => ci * ri,
-                                BinOp::Div => ci / ri,
-                                BinOp::Mod => if ri == 0 { ci } else { ci % ri },
+                                BinOp::Div => int_div(ci, ri)?,
+                                BinOp::Mod => int_rem(ci, ri)?,
                                 BinOp::BitAnd => ci & ri,
                                 BinOp::BitOr => ci | ri,
                                 BinOp::BitXor => ci ^ ri,
@@ -177,7 +200,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                 }
             }
             let v = eval_expr(p, expr, rt, out)?;
-            if !rt.assign(name, v) { return Err(AxityError::rt("assign to undefined variable")); }
+            if !rt.assign(name, v) { return Err(AxityError::rt("assign to undefined variable").with_code("AX0404")); }
             Ok(Control::Next)
         }
         Stmt::Print{ expr, .. } => {
@@ -192,37 +215,15 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
         }
         Stmt::Expr(e) => { let _ = eval_expr(p, e, rt, out)?; Ok(Control::Next) }
         Stmt::Retry(_) => Ok(Control::Retry),
-        Stmt::Throw{ expr, .. } => {
+        Stmt::Break{ label, .. } => Ok(Control::Break(label.clone())),
+        Stmt::Continue{ label, .. } => Ok(Control::Continue(label.clone())),
+        Stmt::Throw{ expr, span } => {
             let v = eval_expr(p, expr, rt, out)?;
+            rt.note_throw(span);
             Ok(Control::Throw(v))
         }
-        Stmt::Try{ body, catch_name, catch_body, .. } => {
-            rt.push_scope();
-            for st in body {
-                match exec_stmt(p, st, rt, out)? {
-                    Control::Next => {}
-                    Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
-                    Control::Retry => {}
-                    Control::Throw(err) => {
-                        rt.pop_scope();
-                        rt.push_scope();
-                        rt.set(catch_name.clone(), err);
-                        for stc in catch_body {
-                            match exec_stmt(p, stc, rt, out)? {
-                                Control::Next => {}
-                                Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
-                                Control::Retry => {}
-                                Control::Throw(e2) => { rt.pop_scope(); return Ok(Control::Throw(e2)); }
-                            }
-                        }
-                        rt.pop_scope();
-                        return Ok(Control::Next);
-                    }
-                }
-            }
-            rt.pop_scope();
-            Ok(Control::Next)
-        }
+        Stmt::Rethrow(span) => rethrow(rt, span),
+        Stmt::Try{ body, catches, finally, retry, .. } => exec_try(p, body, catches, finally.as_deref(), retry, rt, out),
         Stmt::MemberAssign{ object, field, expr, .. } => {
             let ov = eval_expr(p, object, rt, out)?;
             match ov {
@@ -241,8 +242,8 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                                 BinOp::Add => ci + ri,
                                 BinOp::Sub => ci - ri,
                                 BinOp::Mul => ci * ri,
-                                BinOp::Div => ci / ri,
-                                BinOp::Mod => if ri == 0 { ci } else { ci % ri },
+                                BinOp::Div => int_div(ci, ri)?,
+                                BinOp::Mod => int_rem(ci, ri)?,
                                 BinOp::BitAnd => ci & ri,
                                 BinOp::BitOr => ci | ri,
                                 BinOp::BitXor => ci ^ ri,
@@ -264,16 +265,16 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                     rc.borrow_mut().insert(field.clone(), v);
                     Ok(Control::Next)
                 }
-                _ => Err(AxityError::rt("member assignment on non-object"))
+                _ => Err(AxityError::rt("member assignment on non-object").with_code("AX0405"))
             }
         }
-        Stmt::While{ cond, body, .. } => {
+        Stmt::While{ label, cond, body, .. } => {
             // triple-nested while optimization: i<Ni { let j=J0; while j<Nj { let k=K0; while k<Nk { total += i+j+k; iterations += 1; k++; } j++; } i++; }
             if let Expr::Binary{ op: BinOp::Lt, left: i_left, right: i_right, .. } = cond {
                 // outer loop variable name
                 if let Expr::Var(i_name, _) = &**i_left {
                     // expected tail increment of i
-                    if body.len() >= 3 {
+                    if body.len() == 3 {
                         // expect: let j, while j<..., assign i++
                         if let (Stmt::Let{ name: j_name, init: j_init, .. }, Stmt::While{ cond: j_cond, body: j_body, .. }, Stmt::Assign{ name: i_assign, expr: i_expr, .. }) = (&body[0], &body[1], body.last().unwrap()) {
                             if i_assign == i_name {
@@ -282,14 +283,14 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                                         // match j while
                                         if let Expr::Binary{ op: BinOp::Lt, left: j_left, right: j_right, .. } = j_cond {
                                             if let Expr::Var(jv, _) = &**j_left {
-                                                if jv == j_name && j_body.len() >= 3 {
+                                                if jv == j_name && j_body.len() == 3 {
                                                     // expect: let k, while k<..., assign j++
                                                     if let (Stmt::Let{ name: k_name, init: k_init, .. }, Stmt::While{ cond: k_cond, body: k_body, .. }, Stmt::Assign{ name: j_assign, expr: j_expr, .. }) = (&j_body[0], &j_body[1], j_body.last().unwrap()) {
                                                         if j_assign == j_name {
                                                             if let Expr::Binary{ op: BinOp::Add, left: jl, right: jr, .. } = j_expr {
                                                                 if matches!(&**jl, Expr::Var(v, _) if v==j_name) && matches!(&**jr, Expr::Int(1, _)) {
                                                                     // match k while body: expect total += (i+j+k); iterations += 1; k++
-                                                                    if k_body.len() >= 3 {
+                                                                    if k_body.len() == 3 {
                                                                         // first two stmts assignments
                                                                         if let (Stmt::Assign{ name: tot_name, expr: tot_expr, .. }, Stmt::Assign{ name: it_name, expr: it_expr, .. }, Stmt::Assign{ name: k_assign, expr: k_expr, .. }) = (&k_body[0], &k_body[1], &k_body[2]) {
                                                                             // k++
@@ -422,6 +423,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                                             };
                                             let mut total = match rt.get(tname) { Some(Value::Int(v)) => v, _ => 0 };
                                             while i < bound {
+                                                rt.back_edge()?;
                                                 total += i;
                                                 i += 1;
                                             }
@@ -437,15 +439,19 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                 }
             }
             rt.push_scope();
-            loop {
+            'iter: loop {
+                rt.back_edge()?;
                 let ci = eval_cond_ci(p, cond, rt, out)?;
                 if ci == 0 { break; }
                 let mut did_retry = false;
                 for st in body {
                     match exec_stmt(p, st, rt, out)? {
                         Control::Next => {}
-                        Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
-                        Control::Retry => { did_retry = true; break; }
+                        Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
+                        Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                        Control::Break(t) if targets(&t, label) => break 'iter,
+                        Control::Continue(t) if targets(&t, label) => { did_retry = true; break; }
+                        ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                         Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                     }
                 }
@@ -462,8 +468,9 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                 for st in then_body {
                     match exec_stmt(p, st, rt, out)? {
                         Control::Next => {}
-                        Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
+                        Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
                         Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                        ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                         Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                     }
                 }
@@ -471,8 +478,9 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                 for st in else_body {
                     match exec_stmt(p, st, rt, out)? {
                         Control::Next => {}
-                        Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
+                        Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
                         Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                        ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                         Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                     }
                 }
@@ -480,15 +488,19 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
             rt.pop_scope();
             Ok(Control::Next)
         }
-        Stmt::DoWhile{ body, cond, .. } => {
+        Stmt::DoWhile{ label, body, cond, .. } => {
             rt.push_scope();
-            loop {
+            'iter: loop {
+                rt.back_edge()?;
                 let mut did_retry = false;
                 for st in body {
                     match exec_stmt(p, st, rt, out)? {
                         Control::Next => {}
-                        Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
-                        Control::Retry => { did_retry = true; break; }
+                        Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
+                        Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                        Control::Break(t) if targets(&t, label) => break 'iter,
+                        Control::Continue(t) if targets(&t, label) => { did_retry = true; break; }
+                        ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                         Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                     }
                 }
@@ -499,7 +511,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
             rt.pop_scope();
             Ok(Control::Next)
         }
-        Stmt::ForC{ init, cond, post, body, .. } => {
+        Stmt::ForC{ label, init, cond, post, body, .. } => {
             if let (Some(initst), Some(c), Some(pst)) = (init.as_ref(), cond.as_ref(), post.as_ref()) {
                 if let (Stmt::Let{ name: iname, init: iinit, .. }, Stmt::Assign{ name: pname, expr: pexpr, .. }) = (&**initst, &**pst) {
                     let post_ok = pname == iname && matches!(pexpr, Expr::Binary{ op: BinOp::Add, left, right, .. } if matches!(&**left, Expr::Var(v, _) if v==iname) && matches!(&**right, Expr::Int(1, _)));
@@ -525,6 +537,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                                                         let mut total = match rt.get(tname) { Some(Value::Int(iv)) => iv, _ => 0 };
                                                         let mut i = start_i;
                                                         while i < bound {
+                                                            rt.back_edge()?;
                                                             total += i;
                                                             i += 1;
                                                         }
@@ -604,15 +617,19 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
             }
             rt.push_scope();
             if let Some(initst) = init { let _ = exec_stmt(p, &*initst, rt, out)?; }
-            loop {
+            'iter: loop {
+                rt.back_edge()?;
                 let ci = if let Some(c) = cond { eval_cond_ci(p, c, rt, out)? } else { 1 };
                 if ci == 0 { break; }
                 let mut did_retry = false;
                 for st in body {
                     match exec_stmt(p, st, rt, out)? {
                         Control::Next => {}
-                        Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
-                        Control::Retry => { did_retry = true; break; }
+                        Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
+                        Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                        Control::Break(t) if targets(&t, label) => break 'iter,
+                        Control::Continue(t) if targets(&t, label) => { did_retry = true; break; }
+                        ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                         Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                     }
                 }
@@ -620,7 +637,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                     match exec_stmt(p, &*pst, rt, out)? {
                         Control::Next => {},
                         Control::Return(_) => {},
-                        Control::Retry => {},
+                        Control::Retry | Control::Break(_) | Control::Continue(_) => {},
                         Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                     }
                 }
@@ -629,21 +646,25 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
             rt.pop_scope();
             Ok(Control::Next)
         }
-        Stmt::ForEach{ var, collection, body, .. } => {
+        Stmt::ForEach{ label, var, collection, body, .. } => {
             let collv = eval_expr(p, collection, rt, out)?;
             match collv {
                 Value::Array(vs) => {
                     let len = vs.borrow().len();
                     rt.push_scope();
-                    for i in 0..len {
+                    'iter: for i in 0..len {
                         let el = { let vb = vs.borrow(); vb[i].clone() };
                         rt.set(var.clone(), el);
+                        rt.back_edge()?;
                         let mut did_retry = false;
                         for st in body {
                             match exec_stmt(p, st, rt, out)? {
                                 Control::Next => {}
-                                Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
-                                Control::Retry => { did_retry = true; break; }
+                                Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
+                                Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                                Control::Break(t) if targets(&t, label) => break 'iter,
+                                Control::Continue(t) if targets(&t, label) => { did_retry = true; break; }
+                                ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                                 Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                             }
                         }
@@ -655,14 +676,18 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                 Value::Map(mm) => {
                     let keys: Vec<String> = mm.borrow().keys().cloned().collect();
                     rt.push_scope();
-                    for k in keys {
+                    'iter: for k in keys {
                         rt.set(var.clone(), Value::Str(k.clone()));
+                        rt.back_edge()?;
                         let mut did_retry = false;
                         for st in body {
                             match exec_stmt(p, st, rt, out)? {
                                 Control::Next => {}
-                                Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
-                                Control::Retry => { did_retry = true; break; }
+                                Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
+                                Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                                Control::Break(t) if targets(&t, label) => break 'iter,
+                                Control::Continue(t) if targets(&t, label) => { did_retry = true; break; }
+                                ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                                 Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                             }
                         }
@@ -671,7 +696,7 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                     rt.pop_scope();
                     Ok(Control::Next)
                 }
-                _ => Err(AxityError::rt("foreach expects array or map"))
+                _ => Err(AxityError::rt("foreach expects array or map").with_code("AX0405"))
             }
         }
         Stmt::Return{ expr, .. } => { let v = eval_expr(p, expr, rt, out)?; Ok(Control::Return(v)) }
@@ -691,8 +716,9 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                     for st in &arm.body {
                         match exec_stmt(p, st, rt, out)? {
                             Control::Next => {}
-                            Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
+                            Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
                             Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                            ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                             Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                         }
                     }
@@ -706,8 +732,9 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
                     for st in body {
                         match exec_stmt(p, st, rt, out)? {
                             Control::Next => {}
-                            Control::Return(_) => { rt.pop_scope(); return Ok(Control::Next); }
+                            Control::Return(v) => { rt.pop_scope(); return Ok(Control::Return(v)); }
                             Control::Retry => { rt.pop_scope(); return Ok(Control::Retry); }
+                            ctl @ (Control::Break(_) | Control::Continue(_)) => { rt.pop_scope(); return Ok(ctl); }
                             Control::Throw(e) => { rt.pop_scope(); return Ok(Control::Throw(e)); }
                         }
                     }
@@ -720,10 +747,15 @@ fn exec_stmt(p: &Program, s: &Stmt, rt: &mut Runtime, out: &mut String) -> Resul
 }
 
 fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    eval_expr_at(p, e, rt, out).map_err(|err| err.or_span(e.span()))
+}
+
+fn eval_expr_at(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    rt.tick()?;
     match e {
         Expr::Int(i, _) => Ok(Value::Int(*i)),
         Expr::Flt(f, _) => Ok(Value::Flt(*f)),
-        Expr::Var(name, _) => rt.get(name).ok_or_else(|| AxityError::rt("read of undefined variable")),
+        Expr::Var(name, _) => rt.get(name).ok_or_else(|| AxityError::rt("read of undefined variable").with_code("AX0404")),
         Expr::Str(s, _) => Ok(Value::Str(s.clone())),
         Expr::Lambda{ params, ret, body, .. } => {
             Ok(Value::Lambda(Rc::new(crate::runtime::Lambda{ params: params.clone(), ret: ret.clone(), body: body.clone() })))
@@ -752,20 +784,20 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                             let r = eval_expr(p, right, rt, out)?;
                             match r {
                                 Value::Bool(rb) => return Ok(Value::Bool(lb && rb)),
-                                _ => return Err(AxityError::rt("unsupported bool op")),
+                                _ => return Err(AxityError::rt("unsupported bool op").with_code("AX0405")),
                             }
                         } else {
                             if lb { return Ok(Value::Bool(true)); }
                             let r = eval_expr(p, right, rt, out)?;
                             match r {
                                 Value::Bool(rb) => return Ok(Value::Bool(lb || rb)),
-                                _ => return Err(AxityError::rt("unsupported bool op")),
+                                _ => return Err(AxityError::rt("unsupported bool op").with_code("AX0405")),
                             }
                         }
                     }
-                    Value::Int(_) => return Err(AxityError::rt("logical on ints")),
-                    Value::Flt(_) => return Err(AxityError::rt("logical on flt")),
-                    _ => return Err(AxityError::rt("unsupported bool op")),
+                    Value::Int(_) => return Err(AxityError::rt("logical on ints").with_code("AX0405")),
+                    Value::Flt(_) => return Err(AxityError::rt("logical on flt").with_code("AX0405")),
+                    _ => return Err(AxityError::rt("unsupported bool op").with_code("AX0405")),
                 }
             }
             let r = eval_expr(p, right, rt, out)?;
@@ -775,8 +807,8 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Add => li + ri,
                         BinOp::Sub => li - ri,
                         BinOp::Mul => li * ri,
-                        BinOp::Div => li / ri,
-                        BinOp::Mod => li % ri,
+                        BinOp::Div => int_div(li, ri)?,
+                        BinOp::Mod => int_rem(li, ri)?,
                         BinOp::BitAnd => li & ri,
                         BinOp::BitOr => li | ri,
                         BinOp::BitXor => li ^ ri,
@@ -788,7 +820,7 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Ge => if li >= ri {1} else {0},
                         BinOp::Eq => if li == ri {1} else {0},
                         BinOp::Ne => if li != ri {1} else {0},
-                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on ints")),
+                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on ints").with_code("AX0405")),
                     };
                     Ok(Value::Int(v))
                 }
@@ -797,16 +829,16 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Add => lf + rf,
                         BinOp::Sub => lf - rf,
                         BinOp::Mul => ((lf as i128) * (rf as i128) / (SCALE as i128)) as i64,
-                        BinOp::Div => ((lf as i128) * (SCALE as i128) / (rf as i128)) as i64,
-                        BinOp::Mod => (lf % rf),
-                        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => return Err(AxityError::rt("bitwise requires int")),
+                        BinOp::Div => flt_div(lf, rf)?,
+                        BinOp::Mod => int_rem(lf, rf)?,
+                        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => return Err(AxityError::rt("bitwise requires int").with_code("AX0405")),
                         BinOp::Lt => if lf < rf {1} else {0},
                         BinOp::Le => if lf <= rf {1} else {0},
                         BinOp::Gt => if lf > rf {1} else {0},
                         BinOp::Ge => if lf >= rf {1} else {0},
                         BinOp::Eq => if lf == rf {1} else {0},
                         BinOp::Ne => if lf != rf {1} else {0},
-                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on flt")),
+                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on flt").with_code("AX0405")),
                     };
                     Ok(match op { BinOp::Add|BinOp::Sub|BinOp::Mul|BinOp::Div => Value::Flt(v), _ => Value::Int(v) })
                 }
@@ -816,16 +848,16 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Add => lf + rf,
                         BinOp::Sub => lf - rf,
                         BinOp::Mul => ((lf as i128) * (rf as i128) / (SCALE as i128)) as i64,
-                        BinOp::Div => ((lf as i128) * (SCALE as i128) / (rf as i128)) as i64,
-                        BinOp::Mod => (lf % rf),
-                        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => return Err(AxityError::rt("bitwise requires int")),
+                        BinOp::Div => flt_div(lf, rf)?,
+                        BinOp::Mod => int_rem(lf, rf)?,
+                        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => return Err(AxityError::rt("bitwise requires int").with_code("AX0405")),
                         BinOp::Lt => if lf < rf {1} else {0},
                         BinOp::Le => if lf <= rf {1} else {0},
                         BinOp::Gt => if lf > rf {1} else {0},
                         BinOp::Ge => if lf >= rf {1} else {0},
                         BinOp::Eq => if lf == rf {1} else {0},
                         BinOp::Ne => if lf != rf {1} else {0},
-                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on flt")),
+                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on flt").with_code("AX0405")),
                     };
                     Ok(match op { BinOp::Add|BinOp::Sub|BinOp::Mul|BinOp::Div => Value::Flt(v), _ => Value::Int(v) })
                 }
@@ -835,16 +867,16 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Add => lf + rf,
                         BinOp::Sub => lf - rf,
                         BinOp::Mul => ((lf as i128) * (rf as i128) / (SCALE as i128)) as i64,
-                        BinOp::Div => ((lf as i128) * (SCALE as i128) / (rf as i128)) as i64,
-                        BinOp::Mod => (lf % rf),
-                        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => return Err(AxityError::rt("bitwise requires int")),
+                        BinOp::Div => flt_div(lf, rf)?,
+                        BinOp::Mod => int_rem(lf, rf)?,
+                        BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => return Err(AxityError::rt("bitwise requires int").with_code("AX0405")),
                         BinOp::Lt => if lf < rf {1} else {0},
                         BinOp::Le => if lf <= rf {1} else {0},
                         BinOp::Gt => if lf > rf {1} else {0},
                         BinOp::Ge => if lf >= rf {1} else {0},
                         BinOp::Eq => if lf == rf {1} else {0},
                         BinOp::Ne => if lf != rf {1} else {0},
-                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on flt")),
+                        BinOp::And | BinOp::Or => return Err(AxityError::rt("logical on flt").with_code("AX0405")),
                     };
                     Ok(match op { BinOp::Add|BinOp::Sub|BinOp::Mul|BinOp::Div => Value::Flt(v), _ => Value::Int(v) })
                 }
@@ -854,8 +886,8 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Add => li + ri,
                         BinOp::Sub => li - ri,
                         BinOp::Mul => li * ri,
-                        BinOp::Div => li / ri,
-                        BinOp::Mod => if ri == 0 { li } else { li % ri },
+                        BinOp::Div => int_div(li, ri)?,
+                        BinOp::Mod => int_rem(li, ri)?,
                         BinOp::BitAnd => li & ri,
                         BinOp::BitOr => li | ri,
                         BinOp::BitXor => li ^ ri,
@@ -867,7 +899,7 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Ge => if li >= ri {1} else {0},
                         BinOp::Eq => if li == ri {1} else {0},
                         BinOp::Ne => if li != ri {1} else {0},
-                        BinOp::And | BinOp::Or => return Err(AxityError::rt("unsupported bool op")),
+                        BinOp::And | BinOp::Or => return Err(AxityError::rt("unsupported bool op").with_code("AX0405")),
                     };
                     Ok(Value::Int(v))
                 }
@@ -876,7 +908,7 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Add => Ok(Value::Str(format!("{}{}", ls, rs))),
                         BinOp::Eq => Ok(Value::Int(if ls == rs {1} else {0})),
                         BinOp::Ne => Ok(Value::Int(if ls != rs {1} else {0})),
-                        _ => Err(AxityError::rt("unsupported string binary op"))
+                        _ => Err(AxityError::rt("unsupported string binary op").with_code("AX0405"))
                     }
                 }
                 (Value::Int(li), Value::Object(_)) | (Value::Object(_), Value::Int(li)) => {
@@ -886,7 +918,7 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Sub => li - ri,
                         BinOp::Mul => li * ri,
                         BinOp::Div => if ri==0 { li } else { li / ri },
-                        BinOp::Mod => if ri==0 { li } else { li % ri },
+                        BinOp::Mod => int_rem(li, ri)?,
                         BinOp::BitAnd => li & ri,
                         BinOp::BitOr => li | ri,
                         BinOp::BitXor => li ^ ri,
@@ -898,7 +930,7 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Ge => if li >= ri {1} else {0},
                         BinOp::Eq => if li == ri {1} else {0},
                         BinOp::Ne => if li != ri {1} else {0},
-                        BinOp::And | BinOp::Or => return Err(AxityError::rt("unsupported bool op")),
+                        BinOp::And | BinOp::Or => return Err(AxityError::rt("unsupported bool op").with_code("AX0405")),
                     };
                     Ok(Value::Int(v))
                 }
@@ -908,18 +940,23 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                         BinOp::Or => lb || rb,
                         BinOp::Eq => lb == rb,
                         BinOp::Ne => lb != rb,
-                        _ => return Err(AxityError::rt("unsupported bool op")),
+                        _ => return Err(AxityError::rt("unsupported bool op").with_code("AX0405")),
                     };
                     Ok(match op { BinOp::And | BinOp::Or => Value::Bool(v), _ => Value::Int(if v {1} else {0}) })
                 }
-                _ => Err(AxityError::rt("type mismatch in binary"))
+                _ => Err(AxityError::rt("type mismatch in binary").with_code("AX0405"))
             }
         }
+        Expr::Cond{ cond, then_expr, else_expr, .. } => {
+            let c = eval_expr(p, cond, rt, out)?;
+            let ci = match c { Value::Int(i) => i, Value::Bool(b) => if b {1} else {0}, _ => 0 };
+            eval_expr(p, if ci != 0 { then_expr } else { else_expr }, rt, out)
+        }
         Expr::UnaryNot{ expr, .. } => {
             let v = eval_expr(p, expr, rt, out)?;
             match v {
                 Value::Bool(b) => Ok(Value::Bool(!b)),
-                _ => Err(AxityError::rt("! requires bool"))
+                _ => Err(AxityError::rt("! requires bool").with_code("AX0405"))
             }
         }
         Expr::UnaryNeg{ expr, .. } => {
@@ -927,17 +964,17 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
             match v {
                 Value::Int(i) => Ok(Value::Int(-i)),
                 Value::Flt(f) => Ok(Value::Flt(-f)),
-                _ => Err(AxityError::rt("unary - requires int or flt"))
+                _ => Err(AxityError::rt("unary - requires int or flt").with_code("AX0405"))
             }
         }
         Expr::UnaryBitNot{ expr, .. } => {
             let v = eval_expr(p, expr, rt, out)?;
             match v {
                 Value::Int(i) => Ok(Value::Int(!i)),
-                _ => Err(AxityError::rt("~ requires int"))
+                _ => Err(AxityError::rt("~ requires int").with_code("AX0405"))
             }
         }
-        Expr::New(name, args, _) => {
+        Expr::New(name, args, span) => {
             let mut fields = std::collections::HashMap::new();
             for it in &p.items {
                 if let Item::Class(c) = it {
@@ -954,7 +991,7 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                                 crate::types::Type::Obj => rt.new_obj_map(HashMap::new()),
                                 crate::types::Type::Fn(_, _) => Value::Int(0),
                                 crate::types::Type::Buffer => rt.new_buffer(Vec::new()),
-                                crate::types::Type::Any => Value::Int(0),
+                                crate::types::Type::Any | crate::types::Type::Error => Value::Int(0),
                             };
                             fields.insert(f.name.clone(), dv);
                         }
@@ -964,12 +1001,18 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
                             let mut ev_args = Vec::new();
                             ev_args.push(obj_val.clone());
                             for a in args { ev_args.push(eval_expr(p, a, rt, out)?); }
-                            let _ = call_method("init", &ev_args, p, rt, out)?;
+                            let _ = call_method("init", &ev_args, Some(span), p, rt, out)?;
                         }
                         return Ok(obj_val);
                     }
                 }
             }
+            if crate::types::is_error_class(name) {
+                // `new IoError("disk full")` takes an optional message.
+                let message = match args.first() { Some(a) => eval_expr(p, a, rt, out)?, None => Value::Str(String::new()) };
+                let message = match message { Value::Str(s) => s, other => fmt_value(&other, 2) };
+                return Ok(error_object(rt, name, &message, ""));
+            }
             Ok(rt.new_object(name.clone(), fields))
         }
         Expr::Member{ object, field, .. } => {
@@ -977,585 +1020,829 @@ fn eval_expr(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Resul
             match ov {
                 Value::Object(rc) => {
                     let b = rc.borrow();
-                    b.fields.get(field).cloned().ok_or_else(|| AxityError::rt("unknown field"))
+                    b.fields.get(field).cloned().ok_or_else(|| AxityError::rt("unknown field").with_code("AX0404"))
                 }
                 Value::Obj(rc) => {
                     Ok(rc.borrow().get(field).cloned().unwrap_or(Value::Int(0)))
                 }
-                _ => Err(AxityError::rt("member access on non-object"))
+                _ => Err(AxityError::rt("member access on non-object").with_code("AX0405"))
             }
         }
         Expr::Index{ array, index, .. } => {
             let av = eval_expr(p, array, rt, out)?;
             let iv = eval_expr(p, index, rt, out)?;
-            let idx = match iv { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("index non-int")) };
+            let idx = match iv { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("index non-int").with_code("AX0405")) };
             match av {
                 Value::Array(vs) => {
                     let vsb = vs.borrow();
-                    if idx >= vsb.len() { return Err(AxityError::rt("index out of bounds")); }
+                    if idx >= vsb.len() { return Err(AxityError::rt("index out of bounds").with_code("AX0402")); }
                     Ok(vsb[idx].clone())
                 }
-                _ => Err(AxityError::rt("index on non-array"))
+                _ => Err(AxityError::rt("index on non-array").with_code("AX0405"))
             }
         }
-        Expr::CallCallee{ callee, args, .. } => {
+        Expr::CallCallee{ callee, args, span } => {
             let fval = eval_expr(p, callee, rt, out)?;
             match fval {
-                Value::Lambda(l) => {
-                    rt.push_scope();
-                    for (i, par) in l.params.iter().enumerate() {
-                        let av = if let Some(arg) = args.get(i) { eval_expr(p, arg, rt, out)? } else { Value::Int(0) };
-                        rt.set(par.name.clone(), av);
-                    }
-                    for st in &l.body {
-                        match exec_stmt(p, st, rt, out)? {
-                            Control::Next => {}
-                            Control::Return(v) => { rt.pop_scope(); return Ok(v); }
-                            Control::Retry => {}
-                            Control::Throw(e) => { rt.pop_scope(); return Err(AxityError::rt(&format!("uncaught exception: {}", fmt_value(&e, 2)))); }
-                        }
-                    }
-                    rt.pop_scope();
-                    Ok(Value::Int(0))
-                }
-                _ => Err(AxityError::rt("callee is not function"))
+                Value::Lambda(l) => call_lambda("<lambda>", &l, args, Some(span), p, rt, out),
+                _ => Err(AxityError::rt("callee is not function").with_code("AX0405"))
             }
         }
-        Expr::MethodCall{ object, name, args, .. } => {
+        Expr::MethodCall{ object, name, args, span } => {
             let ov = eval_expr(p, object, rt, out)?;
             let mut ev_args = Vec::new();
             ev_args.push(ov.clone());
             for a in args { ev_args.push(eval_expr(p, a, rt, out)?); }
-            call_method(name, &ev_args, p, rt, out)
-        }
-        Expr::Call{ name, args, .. } => {
-            if name == "len" {
-                if args.len() != 1 { return Err(AxityError::rt("len expects one argument")); }
-                let av = eval_expr(p, &args[0], rt, out)?;
-                match av {
-                    Value::Array(v) => Ok(Value::Int(v.borrow().len() as i64)),
-                    Value::Str(s) => Ok(Value::Int(s.len() as i64)),
-                    _ => Err(AxityError::rt("len expects array or string"))
-                }
-            } else if name == "slice" {
-                if args.len() != 3 { return Err(AxityError::rt("slice expects (array, start, len)")); }
-                let arr = eval_expr(p, &args[0], rt, out)?;
-                let st = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("start must be int")) };
-                let ln = match eval_expr(p, &args[2], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("len must be int")) };
-                match arr {
-                    Value::Array(v) => {
-                        let vb = v.borrow();
-                        let end = st.saturating_add(ln).min(vb.len());
-                        let mut outv = Vec::with_capacity(end.saturating_sub(st));
-                        for i in st..end { outv.push(vb[i].clone()); }
-                        Ok(rt.new_array(outv))
-                    }
-                    _ => Err(AxityError::rt("slice expects array"))
-                }
-            } else if name == "range" {
-                if args.len() != 2 { return Err(AxityError::rt("range expects (start, end)")); }
-                let st = match eval_expr(p, &args[0], rt, out)? { Value::Int(i) => i, _ => return Err(AxityError::rt("start must be int")) };
-                let en = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i, _ => return Err(AxityError::rt("end must be int")) };
-                let step = if en >= st { 1 } else { -1 };
-                let mut v = Vec::with_capacity((en - st).abs() as usize);
-                let mut i = st;
-                while (step > 0 && i < en) || (step < 0 && i > en) { v.push(Value::Int(i)); i += step; }
-                Ok(rt.new_array(v))
-            } else if name == "buffer_new" {
-                if args.len() != 1 { return Err(AxityError::rt("buffer_new expects size")); }
-                let sz = match eval_expr(p, &args[0], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("size must be int")) };
-                Ok(rt.new_buffer(vec![0u8; sz]))
-            } else if name == "buffer_len" {
-                if args.len() != 1 { return Err(AxityError::rt("buffer_len expects buffer")); }
-                match eval_expr(p, &args[0], rt, out)? {
-                    Value::Buffer(b) => Ok(Value::Int(b.borrow().len() as i64)),
-                    _ => Err(AxityError::rt("arg must be buffer"))
-                }
-            } else if name == "buffer_get" {
-                if args.len() != 2 { return Err(AxityError::rt("buffer_get expects (buffer, index)")); }
-                let b = eval_expr(p, &args[0], rt, out)?;
-                let idx = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("index must be int")) };
-                match b {
-                    Value::Buffer(buf) => {
-                        let bb = buf.borrow();
-                        if idx >= bb.len() { return Err(AxityError::rt("index out of bounds")); }
-                        Ok(Value::Int(bb[idx] as i64))
-                    }
-                    _ => Err(AxityError::rt("first arg must be buffer"))
-                }
-            } else if name == "buffer_set" {
-                if args.len() != 3 { return Err(AxityError::rt("buffer_set expects (buffer, index, byte)")); }
-                let b = eval_expr(p, &args[0], rt, out)?;
-                let idx = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("index must be int")) };
-                let byte = match eval_expr(p, &args[2], rt, out)? { Value::Int(i) => i as u8, _ => return Err(AxityError::rt("byte must be int")) };
-                match b {
-                    Value::Buffer(buf) => {
-                        let mut bb = buf.borrow_mut();
-                        if idx >= bb.len() { return Err(AxityError::rt("index out of bounds")); }
-                        bb[idx] = byte;
-                        Ok(Value::Int(idx as i64))
-                    }
-                    _ => Err(AxityError::rt("first arg must be buffer"))
-                }
-            } else if name == "buffer_push" {
-                if args.len() != 2 { return Err(AxityError::rt("buffer_push expects (buffer, byte)")); }
-                let b = eval_expr(p, &args[0], rt, out)?;
-                let byte = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as u8, _ => return Err(AxityError::rt("byte must be int")) };
-                match b {
-                    Value::Buffer(buf) => { buf.borrow_mut().push(byte); Ok(Value::Int(buf.borrow().len() as i64)) }
-                    _ => Err(AxityError::rt("first arg must be buffer"))
-                }
-            } else if name == "buffer_from_string" {
-                if args.len() != 1 { return Err(AxityError::rt("buffer_from_string expects string")); }
-                match eval_expr(p, &args[0], rt, out)? {
-                    Value::Str(s) => Ok(rt.new_buffer(s.into_bytes())),
-                    _ => Err(AxityError::rt("arg must be string"))
-                }
-            } else if name == "buffer_to_string" {
-                if args.len() != 1 { return Err(AxityError::rt("buffer_to_string expects buffer")); }
-                match eval_expr(p, &args[0], rt, out)? {
-                    Value::Buffer(b) => {
-                        let bb = b.borrow();
-                        Ok(Value::Str(String::from_utf8_lossy(&bb).to_string()))
-                    }
-                    _ => Err(AxityError::rt("arg must be buffer"))
-                }
-            } else if name == "map_remove" {
-                if args.len() != 2 { return Err(AxityError::rt("map_remove expects (map, key)")); }
-                let m = eval_expr(p, &args[0], rt, out)?;
-                let k = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                match m {
-                    Value::Map(mm) => Ok(Value::Int(if mm.borrow_mut().remove(&k).is_some() {1} else {0})),
-                    _ => Err(AxityError::rt("first arg must be map"))
-                }
-            } else if name == "map_clear" {
-                if args.len() != 1 { return Err(AxityError::rt("map_clear expects (map)")); }
-                let m = eval_expr(p, &args[0], rt, out)?;
-                match m {
-                    Value::Map(mm) => { let sz = mm.borrow().len() as i64; mm.borrow_mut().clear(); Ok(Value::Int(sz)) }
-                    _ => Err(AxityError::rt("first arg must be map"))
-                }
-            } else if name == "map_size" {
-                if args.len() != 1 { return Err(AxityError::rt("map_size expects (map)")); }
-                let m = eval_expr(p, &args[0], rt, out)?;
-                match m {
-                    Value::Map(mm) => Ok(Value::Int(mm.borrow().len() as i64)),
-                    _ => Err(AxityError::rt("first arg must be map"))
+            call_method(name, &ev_args, Some(span), p, rt, out)
+        }
+        Expr::Call{ name, args, span } => {
+            if let Some(v) = call_builtin(p, name, args, rt, out)? { return Ok(v); }
+            // try lambda in variables first, else named function
+            if let Some(val) = rt.get(&name) {
+                if let Value::Lambda(l) = val {
+                    call_lambda(name, &l, args, Some(span), p, rt, out)
+                } else {
+                    let mut ev_args = Vec::new();
+                    for a in args { ev_args.push(eval_expr(p, a, rt, out)?); }
+                    call_func(name, &ev_args, Some(span), p, rt, out)
                 }
-            } else if name == "string_replace" {
-                if args.len() != 3 { return Err(AxityError::rt("string_replace expects (s, from, to)")); }
-                let s = match eval_expr(p, &args[0], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("s must be string")) };
-                let from = match eval_expr(p, &args[1], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("from must be string")) };
-                let to = match eval_expr(p, &args[2], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("to must be string")) };
-                Ok(Value::Str(s.replace(&from, &to)))
-            } else if name == "string_split" {
-                if args.len() != 2 { return Err(AxityError::rt("string_split expects (s, sep)")); }
-                let s = match eval_expr(p, &args[0], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("s must be string")) };
-                let sep = match eval_expr(p, &args[1], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("sep must be string")) };
+            } else {
+                let mut ev_args = Vec::new();
+                for a in args { ev_args.push(eval_expr(p, a, rt, out)?); }
+                call_func(name, &ev_args, Some(span), p, rt, out)
+            }
+        }
+    }
+}
+
+// Names `call_builtin` (or the parser, for `print`) handles before any host native is looked up.
+pub const BUILTINS: &[&str] = &[
+    "attempt", "buffer_from_string", "buffer_get", "buffer_len", "buffer_new", "buffer_push", "buffer_set", "buffer_to_string",
+    "cos", "env_get", "env_set", "exists", "index_of", "input", "input_all", "input_eof", "json_get", "json_set", "len",
+    "map_clear", "map_get", "map_has", "map_keys", "map_new_int", "map_new_string", "map_remove", "map_set", "map_size",
+    "matrix_mul", "mkdir", "pop", "print", "push", "range", "read_env", "read_file", "read_json", "read_toml", "set", "sin",
+    "slice", "string_replace", "string_split", "strlen", "substr", "tan", "to_int", "to_string", "toml_get", "toml_set",
+    "write_env", "write_file", "write_json", "write_toml",
+];
+
+// Builtins by name, or `None` for anything else. Kept out of `eval_expr_at` so that calls into
+// script functions do not carry this (large) frame down the recursion.
+fn call_builtin(p: &Program, name: &str, args: &[Expr], rt: &mut Runtime, out: &mut String) -> Result<Option<Value>, AxityError> {
+    let v = if name == "len" {
+        if args.len() != 1 { return Err(AxityError::rt("len expects one argument").with_code("AX0406")); }
+        let av = eval_expr(p, &args[0], rt, out)?;
+        match av {
+            Value::Array(v) => Ok(Value::Int(v.borrow().len() as i64)),
+            Value::Str(s) => Ok(Value::Int(s.len() as i64)),
+            _ => Err(AxityError::rt("len expects array or string").with_code("AX0405"))
+        }
+    } else if name == "slice" {
+        if args.len() != 3 { return Err(AxityError::rt("slice expects (array, start, len)").with_code("AX0406")); }
+        let arr = eval_expr(p, &args[0], rt, out)?;
+        let st = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("start must be int").with_code("AX0405")) };
+        let ln = match eval_expr(p, &args[2], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("len must be int").with_code("AX0405")) };
+        match arr {
+            Value::Array(v) => {
+                let vb = v.borrow();
+                let end = st.saturating_add(ln).min(vb.len());
+                let mut outv = Vec::with_capacity(end.saturating_sub(st));
+                for i in st..end { outv.push(vb[i].clone()); }
+                Ok(rt.new_array(outv))
+            }
+            _ => Err(AxityError::rt("slice expects array").with_code("AX0405"))
+        }
+    } else if name == "range" {
+        if args.len() != 2 { return Err(AxityError::rt("range expects (start, end)").with_code("AX0406")); }
+        let st = match eval_expr(p, &args[0], rt, out)? { Value::Int(i) => i, _ => return Err(AxityError::rt("start must be int").with_code("AX0405")) };
+        let en = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i, _ => return Err(AxityError::rt("end must be int").with_code("AX0405")) };
+        let step = if en >= st { 1 } else { -1 };
+        let mut v = Vec::with_capacity((en - st).abs() as usize);
+        let mut i = st;
+        while (step > 0 && i < en) || (step < 0 && i > en) { v.push(Value::Int(i)); i += step; }
+        Ok(rt.new_array(v))
+    } else if name == "buffer_new" {
+        if args.len() != 1 { return Err(AxityError::rt("buffer_new expects size").with_code("AX0406")); }
+        let sz = match eval_expr(p, &args[0], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("size must be int").with_code("AX0405")) };
+        Ok(rt.new_buffer(vec![0u8; sz]))
+    } else if name == "buffer_len" {
+        if args.len() != 1 { return Err(AxityError::rt("buffer_len expects buffer").with_code("AX0406")); }
+        match eval_expr(p, &args[0], rt, out)? {
+            Value::Buffer(b) => Ok(Value::Int(b.borrow().len() as i64)),
+            _ => Err(AxityError::rt("arg must be buffer").with_code("AX0405"))
+        }
+    } else if name == "buffer_get" {
+        if args.len() != 2 { return Err(AxityError::rt("buffer_get expects (buffer, index)").with_code("AX0406")); }
+        let b = eval_expr(p, &args[0], rt, out)?;
+        let idx = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("index must be int").with_code("AX0405")) };
+        match b {
+            Value::Buffer(buf) => {
+                let bb = buf.borrow();
+                if idx >= bb.len() { return Err(AxityError::rt("index out of bounds").with_code("AX0402")); }
+                Ok(Value::Int(bb[idx] as i64))
+            }
+            _ => Err(AxityError::rt("first arg must be buffer").with_code("AX0405"))
+        }
+    } else if name == "buffer_set" {
+        if args.len() != 3 { return Err(AxityError::rt("buffer_set expects (buffer, index, byte)").with_code("AX0406")); }
+        let b = eval_expr(p, &args[0], rt, out)?;
+        let idx = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("index must be int").with_code("AX0405")) };
+        let byte = match eval_expr(p, &args[2], rt, out)? { Value::Int(i) => i as u8, _ => return Err(AxityError::rt("byte must be int").with_code("AX0405")) };
+        match b {
+            Value::Buffer(buf) => {
+                let mut bb = buf.borrow_mut();
+                if idx >= bb.len() { return Err(AxityError::rt("index out of bounds").with_code("AX0402")); }
+                bb[idx] = byte;
+                Ok(Value::Int(idx as i64))
+            }
+            _ => Err(AxityError::rt("first arg must be buffer").with_code("AX0405"))
+        }
+    } else if name == "buffer_push" {
+        if args.len() != 2 { return Err(AxityError::rt("buffer_push expects (buffer, byte)").with_code("AX0406")); }
+        let b = eval_expr(p, &args[0], rt, out)?;
+        let byte = match eval_expr(p, &args[1], rt, out)? { Value::Int(i) => i as u8, _ => return Err(AxityError::rt("byte must be int").with_code("AX0405")) };
+        match b {
+            Value::Buffer(buf) => { buf.borrow_mut().push(byte); Ok(Value::Int(buf.borrow().len() as i64)) }
+            _ => Err(AxityError::rt("first arg must be buffer").with_code("AX0405"))
+        }
+    } else if name == "buffer_from_string" {
+        if args.len() != 1 { return Err(AxityError::rt("buffer_from_string expects string").with_code("AX0406")); }
+        match eval_expr(p, &args[0], rt, out)? {
+            Value::Str(s) => Ok(rt.new_buffer(s.into_bytes())),
+            _ => Err(AxityError::rt("arg must be string").with_code("AX0405"))
+        }
+    } else if name == "buffer_to_string" {
+        if args.len() != 1 { return Err(AxityError::rt("buffer_to_string expects buffer").with_code("AX0406")); }
+        match eval_expr(p, &args[0], rt, out)? {
+            Value::Buffer(b) => {
+                let bb = b.borrow();
+                Ok(Value::Str(String::from_utf8_lossy(&bb).to_string()))
+            }
+            _ => Err(AxityError::rt("arg must be buffer").with_code("AX0405"))
+        }
+    } else if name == "map_remove" {
+        if args.len() != 2 { return Err(AxityError::rt("map_remove expects (map, key)").with_code("AX0406")); }
+        let m = eval_expr(p, &args[0], rt, out)?;
+        let k = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        match m {
+            Value::Map(mm) => Ok(Value::Int(if mm.borrow_mut().remove(&k).is_some() {1} else {0})),
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "map_clear" {
+        if args.len() != 1 { return Err(AxityError::rt("map_clear expects (map)").with_code("AX0406")); }
+        let m = eval_expr(p, &args[0], rt, out)?;
+        match m {
+            Value::Map(mm) => { let sz = mm.borrow().len() as i64; mm.borrow_mut().clear(); Ok(Value::Int(sz)) }
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "map_size" {
+        if args.len() != 1 { return Err(AxityError::rt("map_size expects (map)").with_code("AX0406")); }
+        let m = eval_expr(p, &args[0], rt, out)?;
+        match m {
+            Value::Map(mm) => Ok(Value::Int(mm.borrow().len() as i64)),
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "string_replace" {
+        if args.len() != 3 { return Err(AxityError::rt("string_replace expects (s, from, to)").with_code("AX0406")); }
+        let s = match eval_expr(p, &args[0], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("s must be string").with_code("AX0405")) };
+        let from = match eval_expr(p, &args[1], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("from must be string").with_code("AX0405")) };
+        let to = match eval_expr(p, &args[2], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("to must be string").with_code("AX0405")) };
+        Ok(Value::Str(s.replace(&from, &to)))
+    } else if name == "string_split" {
+        if args.len() != 2 { return Err(AxityError::rt("string_split expects (s, sep)").with_code("AX0406")); }
+        let s = match eval_expr(p, &args[0], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("s must be string").with_code("AX0405")) };
+        let sep = match eval_expr(p, &args[1], rt, out)? { Value::Str(v) => v, _ => return Err(AxityError::rt("sep must be string").with_code("AX0405")) };
+        let mut v = Vec::new();
+        for part in s.split(&sep) { v.push(Value::Str(part.to_string())); }
+        Ok(rt.new_array(v))
+    } else if name == "push" {
+        if args.len() != 2 { return Err(AxityError::rt("push expects array and value").with_code("AX0406")); }
+        let arr = eval_expr(p, &args[0], rt, out)?;
+        let val = eval_expr(p, &args[1], rt, out)?;
+        match arr {
+            Value::Array(v) => { v.borrow_mut().push(val); Ok(Value::Int(v.borrow().len() as i64)) }
+            _ => Err(AxityError::rt("push expects array").with_code("AX0405"))
+        }
+    } else if name == "pop" {
+        if args.len() != 1 { return Err(AxityError::rt("pop expects array").with_code("AX0406")); }
+        let arr = eval_expr(p, &args[0], rt, out)?;
+        match arr {
+            Value::Array(v) => { v.borrow_mut().pop().ok_or_else(|| AxityError::rt("pop from empty array").with_code("AX0402")) }
+            _ => Err(AxityError::rt("pop expects array").with_code("AX0405"))
+        }
+    } else if name == "set" {
+        if args.len() != 3 { return Err(AxityError::rt("set expects array, index, value").with_code("AX0406")); }
+        let arr = eval_expr(p, &args[0], rt, out)?;
+        let idxv = eval_expr(p, &args[1], rt, out)?;
+        let val = eval_expr(p, &args[2], rt, out)?;
+        let idx = match idxv { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("set index must be int").with_code("AX0405")) };
+        match arr {
+            Value::Array(v) => { let mut vb = v.borrow_mut(); if idx>=vb.len() { return Err(AxityError::rt("index out of bounds").with_code("AX0402")); } vb[idx] = val; Ok(Value::Int(idx as i64)) }
+            _ => Err(AxityError::rt("set expects array").with_code("AX0405"))
+        }
+    } else if name == "map_new_int" {
+        if args.len() != 0 { return Err(AxityError::rt("map_new_int expects no args").with_code("AX0406")); }
+        Ok(rt.new_map())
+    } else if name == "map_new_string" {
+        if args.len() != 0 { return Err(AxityError::rt("map_new_string expects no args").with_code("AX0406")); }
+        Ok(rt.new_map())
+    } else if name == "map_set" {
+        let m = eval_expr(p, &args[0], rt, out)?;
+        let k = eval_expr(p, &args[1], rt, out)?;
+        let v = eval_expr(p, &args[2], rt, out)?;
+        let key = match k { Value::Str(s) => s, _ => return Err(AxityError::rt("map key must be string").with_code("AX0405")) };
+        match m {
+            Value::Map(mm) => { mm.borrow_mut().insert(key, v); Ok(Value::Int(1)) }
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "map_get" {
+        let m = eval_expr(p, &args[0], rt, out)?;
+        let k = eval_expr(p, &args[1], rt, out)?;
+        let key = match k { Value::Str(s) => s, _ => return Err(AxityError::rt("map key must be string").with_code("AX0405")) };
+        match m {
+            Value::Map(mm) => Ok(mm.borrow().get(&key).cloned().unwrap_or(Value::Int(0))),
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "map_has" {
+        let m = eval_expr(p, &args[0], rt, out)?;
+        let k = eval_expr(p, &args[1], rt, out)?;
+        let key = match k { Value::Str(s) => s, _ => return Err(AxityError::rt("map key must be string").with_code("AX0405")) };
+        match m {
+            Value::Map(mm) => Ok(Value::Bool(mm.borrow().contains_key(&key))),
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "map_keys" {
+        let m = eval_expr(p, &args[0], rt, out)?;
+        match m {
+            Value::Map(mm) => {
                 let mut v = Vec::new();
-                for part in s.split(&sep) { v.push(Value::Str(part.to_string())); }
+                for k in mm.borrow().keys() { v.push(Value::Str(k.clone())); }
                 Ok(rt.new_array(v))
-            } else if name == "push" {
-                if args.len() != 2 { return Err(AxityError::rt("push expects array and value")); }
-                let arr = eval_expr(p, &args[0], rt, out)?;
-                let val = eval_expr(p, &args[1], rt, out)?;
-                match arr {
-                    Value::Array(v) => { v.borrow_mut().push(val); Ok(Value::Int(v.borrow().len() as i64)) }
-                    _ => Err(AxityError::rt("push expects array"))
-                }
-            } else if name == "pop" {
-                if args.len() != 1 { return Err(AxityError::rt("pop expects array")); }
-                let arr = eval_expr(p, &args[0], rt, out)?;
-                match arr {
-                    Value::Array(v) => { v.borrow_mut().pop().ok_or_else(|| AxityError::rt("pop from empty array")) }
-                    _ => Err(AxityError::rt("pop expects array"))
-                }
-            } else if name == "set" {
-                if args.len() != 3 { return Err(AxityError::rt("set expects array, index, value")); }
-                let arr = eval_expr(p, &args[0], rt, out)?;
-                let idxv = eval_expr(p, &args[1], rt, out)?;
-                let val = eval_expr(p, &args[2], rt, out)?;
-                let idx = match idxv { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("set index must be int")) };
-                match arr {
-                    Value::Array(v) => { let mut vb = v.borrow_mut(); if idx>=vb.len() { return Err(AxityError::rt("index out of bounds")); } vb[idx] = val; Ok(Value::Int(idx as i64)) }
-                    _ => Err(AxityError::rt("set expects array"))
-                }
-            } else if name == "map_new_int" {
-                if args.len() != 0 { return Err(AxityError::rt("map_new_int expects no args")); }
-                Ok(rt.new_map())
-            } else if name == "map_new_string" {
-                if args.len() != 0 { return Err(AxityError::rt("map_new_string expects no args")); }
-                Ok(rt.new_map())
-            } else if name == "map_set" {
-                let m = eval_expr(p, &args[0], rt, out)?;
-                let k = eval_expr(p, &args[1], rt, out)?;
-                let v = eval_expr(p, &args[2], rt, out)?;
-                let key = match k { Value::Str(s) => s, _ => return Err(AxityError::rt("map key must be string")) };
-                match m {
-                    Value::Map(mm) => { mm.borrow_mut().insert(key, v); Ok(Value::Int(1)) }
-                    _ => Err(AxityError::rt("first arg must be map"))
-                }
-            } else if name == "map_get" {
-                let m = eval_expr(p, &args[0], rt, out)?;
-                let k = eval_expr(p, &args[1], rt, out)?;
-                let key = match k { Value::Str(s) => s, _ => return Err(AxityError::rt("map key must be string")) };
-                match m {
-                    Value::Map(mm) => Ok(mm.borrow().get(&key).cloned().unwrap_or(Value::Int(0))),
-                    _ => Err(AxityError::rt("first arg must be map"))
-                }
-            } else if name == "map_has" {
-                let m = eval_expr(p, &args[0], rt, out)?;
-                let k = eval_expr(p, &args[1], rt, out)?;
-                let key = match k { Value::Str(s) => s, _ => return Err(AxityError::rt("map key must be string")) };
-                match m {
-                    Value::Map(mm) => Ok(Value::Bool(mm.borrow().contains_key(&key))),
-                    _ => Err(AxityError::rt("first arg must be map"))
-                }
-            } else if name == "map_keys" {
-                let m = eval_expr(p, &args[0], rt, out)?;
-                match m {
-                    Value::Map(mm) => {
-                        let mut v = Vec::new();
-                        for k in mm.borrow().keys() { v.push(Value::Str(k.clone())); }
-                        Ok(rt.new_array(v))
-                    }
-                    _ => Err(AxityError::rt("first arg must be map"))
-                }
-            } else if name == "read_file" {
-                if args.len() != 1 { return Err(AxityError::rt("read_file expects path")); }
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                match std::fs::read_to_string(&pth) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e))) }
-            } else if name == "write_file" {
-                if args.len() != 2 { return Err(AxityError::rt("write_file expects (path, content)")); }
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string")) };
-                match std::fs::write(&pth, content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e))) }
-            } else if name == "mkdir" {
-                if args.len() != 1 { return Err(AxityError::rt("mkdir expects path")); }
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                match std::fs::create_dir_all(&pth) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("mkdir error: {}", e))) }
-            } else if name == "exists" {
-                if args.len() != 1 { return Err(AxityError::rt("exists expects path")); }
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                Ok(Value::Bool(std::path::Path::new(&pth).exists()))
-            } else if name == "read_json" {
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                match std::fs::read_to_string(&pth) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e))) }
-            } else if name == "write_json" {
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string")) };
-                if serde_json::from_str::<serde_json::Value>(&content).is_err() { return Err(AxityError::rt("invalid json")) }
-                match std::fs::write(&pth, content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e))) }
-            } else if name == "json_get" {
-                if args.len() != 2 { return Err(AxityError::rt("json_get expects (json, key)")); }
-                let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("json must be string")) };
-                let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| AxityError::rt(&format!("json parse: {}", e)))?;
-                let res = v.get(&key).cloned().unwrap_or(serde_json::Value::Null);
-                Ok(Value::Str(res.to_string()))
-            } else if name == "json_set" {
-                if args.len() != 3 { return Err(AxityError::rt("json_set expects (json, key, value)")); }
-                let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("json must be string")) };
-                let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                let value = match eval_expr(p, &args[2], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("value must be string")) };
-                let mut v: serde_json::Value = serde_json::from_str(&content).map_err(|e| AxityError::rt(&format!("json parse: {}", e)))?;
-                if let serde_json::Value::Object(ref mut m) = v {
-                    m.insert(key, serde_json::Value::String(value));
-                    Ok(Value::Str(v.to_string()))
-                } else { Err(AxityError::rt("json_set requires object at root")) }
-            } else if name == "read_toml" {
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                match std::fs::read_to_string(&pth) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e))) }
-            } else if name == "write_toml" {
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string")) };
-                match std::fs::write(&pth, content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e))) }
-            } else if name == "toml_get" {
-                if args.len() != 2 { return Err(AxityError::rt("toml_get expects (toml, key.path)")); }
-                let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("toml must be string")) };
-                let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                let parts: Vec<&str> = key.split('.').collect();
-                let mut section: Option<&str> = None;
-                let mut field: &str = parts[0];
-                if parts.len() == 2 { section = Some(parts[0]); field = parts[1]; }
-                let mut in_section = section.is_none();
-                for line in content.lines() {
-                    let l = line.trim();
-                    if l.starts_with('[') && l.ends_with(']') {
-                        in_section = match section { Some(sec) => &l[1..l.len()-1] == sec, None => false };
-                        continue;
-                    }
-                    if !in_section { continue; }
-                    if let Some((k,v)) = l.split_once('=') {
-                        if k.trim() == field { return Ok(Value::Str(v.trim().trim_matches('"').to_string())); }
-                    }
-                }
-                Ok(Value::Str(String::new()))
-            } else if name == "toml_set" {
-                if args.len() != 3 { return Err(AxityError::rt("toml_set expects (toml, key.path, value)")); }
-                let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("toml must be string")) };
-                let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                let value = match eval_expr(p, &args[2], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("value must be string")) };
-                let parts: Vec<&str> = key.split('.').collect();
-                let mut lines: Vec<String> = Vec::new();
-                let mut wrote = false;
-                let mut in_section = parts.len()==1;
-                let mut _section_written = false;
-                let (section, field) = if parts.len()==2 { (Some(parts[0]), parts[1]) } else { (None, parts[0]) };
-                for line in content.lines() {
-                    let l = line.trim();
-                    if l.starts_with('[') && l.ends_with(']') {
-                        in_section = match section { Some(sec) => &l[1..l.len()-1] == sec, None => false };
-                        lines.push(line.to_string());
-                        continue;
-                    }
-                    if in_section {
-                        if let Some((k,_)) = l.split_once('=') {
-                            if k.trim() == field {
-                                lines.push(format!("{} = \"{}\"", field, value));
-                                wrote = true;
-                                continue;
-                            }
-                        }
-                    }
-                    lines.push(line.to_string());
-                }
-                if !wrote {
-                    if let Some(sec) = section {
-                        if !content.contains(&format!("[{}]", sec)) && !_section_written {
-                            lines.push(format!("[{}]", sec));
-                            _section_written = true;
-                        }
-                        lines.push(format!("{} = \"{}\"", field, value));
-                    } else {
+            }
+            _ => Err(AxityError::rt("first arg must be map").with_code("AX0405"))
+        }
+    } else if name == "read_file" {
+        if args.len() != 1 { return Err(AxityError::rt("read_file expects path").with_code("AX0406")); }
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, false)?;
+        match rt.fs.read_to_string(Path::new(&pth)) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e)).with_code("AX0407")) }
+    } else if name == "write_file" {
+        if args.len() != 2 { return Err(AxityError::rt("write_file expects (path, content)").with_code("AX0406")); }
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, true)?;
+        match rt.fs.write(Path::new(&pth), &content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e)).with_code("AX0407")) }
+    } else if name == "mkdir" {
+        if args.len() != 1 { return Err(AxityError::rt("mkdir expects path").with_code("AX0406")); }
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, true)?;
+        match rt.fs.create_dir_all(Path::new(&pth)) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("mkdir error: {}", e)).with_code("AX0407")) }
+    } else if name == "exists" {
+        if args.len() != 1 { return Err(AxityError::rt("exists expects path").with_code("AX0406")); }
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, false)?;
+        Ok(Value::Bool(rt.fs.exists(Path::new(&pth))))
+    } else if name == "read_json" {
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, false)?;
+        match rt.fs.read_to_string(Path::new(&pth)) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e)).with_code("AX0407")) }
+    } else if name == "write_json" {
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string").with_code("AX0405")) };
+        if serde_json::from_str::<serde_json::Value>(&content).is_err() { return Err(AxityError::rt("invalid json").with_code("AX0408")) }
+        rt.check_path(name, &pth, true)?;
+        match rt.fs.write(Path::new(&pth), &content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e)).with_code("AX0407")) }
+    } else if name == "json_get" {
+        if args.len() != 2 { return Err(AxityError::rt("json_get expects (json, key)").with_code("AX0406")); }
+        let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("json must be string").with_code("AX0405")) };
+        let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        let v: serde_json::Value = serde_json::from_str(&content).map_err(|e| AxityError::rt(&format!("json parse: {}", e)).with_code("AX0408"))?;
+        let res = v.get(&key).cloned().unwrap_or(serde_json::Value::Null);
+        Ok(Value::Str(res.to_string()))
+    } else if name == "json_set" {
+        if args.len() != 3 { return Err(AxityError::rt("json_set expects (json, key, value)").with_code("AX0406")); }
+        let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("json must be string").with_code("AX0405")) };
+        let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        let value = match eval_expr(p, &args[2], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("value must be string").with_code("AX0405")) };
+        let mut v: serde_json::Value = serde_json::from_str(&content).map_err(|e| AxityError::rt(&format!("json parse: {}", e)).with_code("AX0408"))?;
+        if let serde_json::Value::Object(ref mut m) = v {
+            m.insert(key, serde_json::Value::String(value));
+            Ok(Value::Str(v.to_string()))
+        } else { Err(AxityError::rt("json_set requires object at root").with_code("AX0408")) }
+    } else if name == "read_toml" {
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, false)?;
+        match rt.fs.read_to_string(Path::new(&pth)) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e)).with_code("AX0407")) }
+    } else if name == "write_toml" {
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, true)?;
+        match rt.fs.write(Path::new(&pth), &content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e)).with_code("AX0407")) }
+    } else if name == "toml_get" {
+        if args.len() != 2 { return Err(AxityError::rt("toml_get expects (toml, key.path)").with_code("AX0406")); }
+        let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("toml must be string").with_code("AX0405")) };
+        let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        let parts: Vec<&str> = key.split('.').collect();
+        let mut section: Option<&str> = None;
+        let mut field: &str = parts[0];
+        if parts.len() == 2 { section = Some(parts[0]); field = parts[1]; }
+        let mut in_section = section.is_none();
+        for line in content.lines() {
+            let l = line.trim();
+            if l.starts_with('[') && l.ends_with(']') {
+                in_section = match section { Some(sec) => &l[1..l.len()-1] == sec, None => false };
+                continue;
+            }
+            if !in_section { continue; }
+            if let Some((k,v)) = l.split_once('=') {
+                if k.trim() == field { return Ok(Some(Value::Str(v.trim().trim_matches('"').to_string()))); }
+            }
+        }
+        Ok(Value::Str(String::new()))
+    } else if name == "toml_set" {
+        if args.len() != 3 { return Err(AxityError::rt("toml_set expects (toml, key.path, value)").with_code("AX0406")); }
+        let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("toml must be string").with_code("AX0405")) };
+        let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        let value = match eval_expr(p, &args[2], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("value must be string").with_code("AX0405")) };
+        let parts: Vec<&str> = key.split('.').collect();
+        let mut lines: Vec<String> = Vec::new();
+        let mut wrote = false;
+        let mut in_section = parts.len()==1;
+        let mut _section_written = false;
+        let (section, field) = if parts.len()==2 { (Some(parts[0]), parts[1]) } else { (None, parts[0]) };
+        for line in content.lines() {
+            let l = line.trim();
+            if l.starts_with('[') && l.ends_with(']') {
+                in_section = match section { Some(sec) => &l[1..l.len()-1] == sec, None => false };
+                lines.push(line.to_string());
+                continue;
+            }
+            if in_section {
+                if let Some((k,_)) = l.split_once('=') {
+                    if k.trim() == field {
                         lines.push(format!("{} = \"{}\"", field, value));
+                        wrote = true;
+                        continue;
                     }
                 }
-                Ok(Value::Str(lines.join("\n")))
-            } else if name == "read_env" {
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                match std::fs::read_to_string(&pth) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e))) }
-            } else if name == "write_env" {
-                let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string")) };
-                let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string")) };
-                match std::fs::write(&pth, content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e))) }
-            } else if name == "input" {
-                if args.len() > 1 { return Err(AxityError::rt("input expects zero or one argument")); }
-                if let Some(a0) = args.get(0) {
-                    let prompt = match eval_expr(p, a0, rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("prompt must be string")) };
-                    rt.emit(out, &prompt);
+            }
+            lines.push(line.to_string());
+        }
+        if !wrote {
+            if let Some(sec) = section {
+                if !content.contains(&format!("[{}]", sec)) && !_section_written {
+                    lines.push(format!("[{}]", sec));
+                    _section_written = true;
                 }
-                let mut s = String::new();
-                let _ = std::io::stdin().read_line(&mut s);
-                if s.ends_with('\n') { s.pop(); if s.ends_with('\r') { s.pop(); } }
-                Ok(Value::Str(s))
-            } else if name == "env_get" {
-                if args.len() != 2 { return Err(AxityError::rt("env_get expects (file_content, key)")); }
-                let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string")) };
-                let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                for line in content.lines() {
-                    if let Some((k,v)) = line.split_once('=') { if k.trim()==key { return Ok(Value::Str(v.trim().to_string())); } }
+                lines.push(format!("{} = \"{}\"", field, value));
+            } else {
+                lines.push(format!("{} = \"{}\"", field, value));
+            }
+        }
+        Ok(Value::Str(lines.join("\n")))
+    } else if name == "read_env" {
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, false)?;
+        match rt.fs.read_to_string(Path::new(&pth)) { Ok(s) => Ok(Value::Str(s)), Err(e) => Err(AxityError::rt(&format!("read error: {}", e)).with_code("AX0407")) }
+    } else if name == "write_env" {
+        let pth = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("path must be string").with_code("AX0405")) };
+        let content = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string").with_code("AX0405")) };
+        rt.check_path(name, &pth, true)?;
+        match rt.fs.write(Path::new(&pth), &content) { Ok(_) => Ok(Value::Int(1)), Err(e) => Err(AxityError::rt(&format!("write error: {}", e)).with_code("AX0407")) }
+    } else if name == "input" {
+        if args.len() > 1 { return Err(AxityError::rt("input expects zero or one argument").with_code("AX0406")); }
+        rt.check_stdin(name)?;
+        if let Some(a0) = args.get(0) {
+            let prompt = match eval_expr(p, a0, rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("prompt must be string").with_code("AX0405")) };
+            rt.emit(out, &prompt);
+        }
+        Ok(Value::Str(rt.input.read_line().unwrap_or_default()))
+    } else if name == "input_all" {
+        if !args.is_empty() { return Err(AxityError::rt("input_all expects no arguments").with_code("AX0406")); }
+        rt.check_stdin(name)?;
+        Ok(Value::Str(rt.input.read_all()))
+    // Scripts written before the builtin existed may define their own `attempt`, which keeps precedence.
+    } else if name == "attempt" && !rt.func_index.contains_key(name) && rt.get(name).is_none() {
+        if !args.is_empty() { return Err(AxityError::rt("attempt expects no arguments").with_code("AX0406")); }
+        Ok(Value::Int(rt.attempt()))
+    } else if name == "input_eof" {
+        if !args.is_empty() { return Err(AxityError::rt("input_eof expects no arguments").with_code("AX0406")); }
+        rt.check_stdin(name)?;
+        Ok(Value::Bool(rt.input.at_eof()))
+    } else if name == "env_get" {
+        if args.len() != 2 { return Err(AxityError::rt("env_get expects (file_content, key)").with_code("AX0406")); }
+        let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string").with_code("AX0405")) };
+        let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        for line in content.lines() {
+            if let Some((k,v)) = line.split_once('=') { if k.trim()==key { return Ok(Some(Value::Str(v.trim().to_string()))); } }
+        }
+        Ok(Value::Str(String::new()))
+    } else if name == "env_set" {
+        if args.len() != 3 { return Err(AxityError::rt("env_set expects (file_content, key, value)").with_code("AX0406")); }
+        let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string").with_code("AX0405")) };
+        let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string").with_code("AX0405")) };
+        let value = match eval_expr(p, &args[2], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("value must be string").with_code("AX0405")) };
+        let mut lines: Vec<String> = Vec::new(); let mut found=false;
+        for line in content.lines() {
+            if let Some((k,_)) = line.split_once('=') {
+                if k.trim()==key { lines.push(format!("{}={}", key, value)); found=true; } else { lines.push(line.to_string()); }
+            } else { lines.push(line.to_string()); }
+        }
+        if !found { lines.push(format!("{}={}", key, value)); }
+        Ok(Value::Str(lines.join("\n")))
+    } else if name == "strlen" {
+        if args.len() != 1 { return Err(AxityError::rt("strlen expects one argument").with_code("AX0406")); }
+        let s = eval_expr(p, &args[0], rt, out)?;
+        match s { Value::Str(ss) => Ok(Value::Int(ss.len() as i64)), _ => Err(AxityError::rt("strlen expects string").with_code("AX0405")) }
+    } else if name == "substr" {
+        if args.len() != 3 { return Err(AxityError::rt("substr expects (string, start, len)").with_code("AX0406")); }
+        let s = eval_expr(p, &args[0], rt, out)?; let st = eval_expr(p, &args[1], rt, out)?; let ln = eval_expr(p, &args[2], rt, out)?;
+        let start = match st { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("substr start must be int").with_code("AX0405")) };
+        let len = match ln { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("substr len must be int").with_code("AX0405")) };
+        match s { Value::Str(ss) => {
+            let end = start.saturating_add(len).min(ss.len());
+            match ss.get(start.min(ss.len())..end) {
+                Some(sub) => Ok(Value::Str(sub.to_string())),
+                None => Err(AxityError::rt("substr range splits a character").with_code("AX0402")),
+            }
+        }, _ => Err(AxityError::rt("substr expects string").with_code("AX0405")) }
+    } else if name == "index_of" {
+        if args.len() != 2 { return Err(AxityError::rt("index_of expects (string, string)").with_code("AX0406")); }
+        let s = eval_expr(p, &args[0], rt, out)?; let sub = eval_expr(p, &args[1], rt, out)?;
+        match (s, sub) { (Value::Str(ss), Value::Str(subs)) => Ok(Value::Int(ss.find(&subs).map(|i| i as i64).unwrap_or(-1))), _ => Err(AxityError::rt("index_of expects strings").with_code("AX0405")) }
+    } else if name == "to_int" {
+        if args.len() != 1 { return Err(AxityError::rt("to_int expects one argument").with_code("AX0406")); }
+        let s = eval_expr(p, &args[0], rt, out)?;
+        match s { Value::Str(ss) => Ok(Value::Int(ss.parse::<i64>().unwrap_or(0))), _ => Err(AxityError::rt("to_int expects string").with_code("AX0405")) }
+    } else if name == "to_string" {
+        if args.len() != 1 { return Err(AxityError::rt("to_string expects one argument").with_code("AX0406")); }
+        let i = eval_expr(p, &args[0], rt, out)?;
+        match i { Value::Int(ii) => Ok(Value::Str(ii.to_string())), _ => Err(AxityError::rt("to_string expects int").with_code("AX0405")) }
+    } else if name == "matrix_mul" {
+        if args.len() != 2 { return Err(AxityError::rt("matrix_mul expects (A, B)").with_code("AX0406")); }
+        let a = eval_expr(p, &args[0], rt, out)?;
+        let b = eval_expr(p, &args[1], rt, out)?;
+        let (arows, brows) = match (a, b) {
+            (Value::Array(ar), Value::Array(br)) => (ar, br),
+            _ => return Err(AxityError::rt("matrix_mul expects arrays of arrays").with_code("AX0405")),
+        };
+        let ab = arows.borrow();
+        let bb = brows.borrow();
+        let m = ab.len();
+        if m == 0 { return Ok(Some(rt.new_array(Vec::new()))); }
+        let n = match &ab[0] {
+            Value::Array(r) => r.borrow().len(),
+            _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")),
+        };
+        for r in &*ab {
+            let rl = match r { Value::Array(rc) => rc.borrow().len(), _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")) };
+            if rl != n { return Err(AxityError::rt("matrix A rows have inconsistent lengths").with_code("AX0409")); }
+        }
+        let bn = bb.len();
+        if bn != n { return Err(AxityError::rt("matrix dimension mismatch: cols(A) != rows(B)").with_code("AX0409")); }
+        let pcols = match &bb[0] {
+            Value::Array(r) => r.borrow().len(),
+            _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")),
+        };
+        for r in &*bb {
+            let rl = match r { Value::Array(rc) => rc.borrow().len(), _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")) };
+            if rl != pcols { return Err(AxityError::rt("matrix B rows have inconsistent lengths").with_code("AX0409")); }
+        }
+        let mut use_flt = false;
+        for r in &*ab {
+            if let Value::Array(rc) = r {
+                for v in rc.borrow().iter() {
+                    if matches!(v, Value::Flt(_)) { use_flt = true; break; }
                 }
-                Ok(Value::Str(String::new()))
-            } else if name == "env_set" {
-                if args.len() != 3 { return Err(AxityError::rt("env_set expects (file_content, key, value)")); }
-                let content = match eval_expr(p, &args[0], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("content must be string")) };
-                let key = match eval_expr(p, &args[1], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("key must be string")) };
-                let value = match eval_expr(p, &args[2], rt, out)? { Value::Str(s) => s, _ => return Err(AxityError::rt("value must be string")) };
-                let mut lines: Vec<String> = Vec::new(); let mut found=false;
-                for line in content.lines() {
-                    if let Some((k,_)) = line.split_once('=') {
-                        if k.trim()==key { lines.push(format!("{}={}", key, value)); found=true; } else { lines.push(line.to_string()); }
-                    } else { lines.push(line.to_string()); }
+            }
+            if use_flt { break; }
+        }
+        if !use_flt {
+            for r in &*bb {
+                if let Value::Array(rc) = r {
+                    for v in rc.borrow().iter() {
+                        if matches!(v, Value::Flt(_)) { use_flt = true; break; }
+                    }
                 }
-                if !found { lines.push(format!("{}={}", key, value)); }
-                Ok(Value::Str(lines.join("\n")))
-            } else if name == "strlen" {
-                if args.len() != 1 { return Err(AxityError::rt("strlen expects one argument")); }
-                let s = eval_expr(p, &args[0], rt, out)?;
-                match s { Value::Str(ss) => Ok(Value::Int(ss.len() as i64)), _ => Err(AxityError::rt("strlen expects string")) }
-            } else if name == "substr" {
-                if args.len() != 3 { return Err(AxityError::rt("substr expects (string, start, len)")); }
-                let s = eval_expr(p, &args[0], rt, out)?; let st = eval_expr(p, &args[1], rt, out)?; let ln = eval_expr(p, &args[2], rt, out)?;
-                let start = match st { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("substr start must be int")) };
-                let len = match ln { Value::Int(i) => i as usize, _ => return Err(AxityError::rt("substr len must be int")) };
-                match s { Value::Str(ss) => {
-                    let end = start.saturating_add(len).min(ss.len());
-                    Ok(Value::Str(ss[start.min(ss.len())..end].to_string()))
-                }, _ => Err(AxityError::rt("substr expects string")) }
-            } else if name == "index_of" {
-                if args.len() != 2 { return Err(AxityError::rt("index_of expects (string, string)")); }
-                let s = eval_expr(p, &args[0], rt, out)?; let sub = eval_expr(p, &args[1], rt, out)?;
-                match (s, sub) { (Value::Str(ss), Value::Str(subs)) => Ok(Value::Int(ss.find(&subs).map(|i| i as i64).unwrap_or(-1))), _ => Err(AxityError::rt("index_of expects strings")) }
-            } else if name == "to_int" {
-                if args.len() != 1 { return Err(AxityError::rt("to_int expects one argument")); }
-                let s = eval_expr(p, &args[0], rt, out)?;
-                match s { Value::Str(ss) => Ok(Value::Int(ss.parse::<i64>().unwrap_or(0))), _ => Err(AxityError::rt("to_int expects string")) }
-            } else if name == "to_string" {
-                if args.len() != 1 { return Err(AxityError::rt("to_string expects one argument")); }
-                let i = eval_expr(p, &args[0], rt, out)?;
-                match i { Value::Int(ii) => Ok(Value::Str(ii.to_string())), _ => Err(AxityError::rt("to_string expects int")) }
-            } else if name == "matrix_mul" {
-                if args.len() != 2 { return Err(AxityError::rt("matrix_mul expects (A, B)")); }
-                let a = eval_expr(p, &args[0], rt, out)?;
-                let b = eval_expr(p, &args[1], rt, out)?;
-                let (arows, brows) = match (a, b) {
-                    (Value::Array(ar), Value::Array(br)) => (ar, br),
-                    _ => return Err(AxityError::rt("matrix_mul expects arrays of arrays")),
-                };
-                let ab = arows.borrow();
-                let bb = brows.borrow();
-                let m = ab.len();
-                if m == 0 { return Ok(rt.new_array(Vec::new())); }
-                let n = match &ab[0] {
-                    Value::Array(r) => r.borrow().len(),
-                    _ => return Err(AxityError::rt("matrix rows must be arrays")),
-                };
-                for r in &*ab {
-                    let rl = match r { Value::Array(rc) => rc.borrow().len(), _ => return Err(AxityError::rt("matrix rows must be arrays")) };
-                    if rl != n { return Err(AxityError::rt("matrix A rows have inconsistent lengths")); }
-                }
-                let bn = bb.len();
-                if bn != n { return Err(AxityError::rt("matrix dimension mismatch: cols(A) != rows(B)")); }
-                let pcols = match &bb[0] {
-                    Value::Array(r) => r.borrow().len(),
-                    _ => return Err(AxityError::rt("matrix rows must be arrays")),
-                };
-                for r in &*bb {
-                    let rl = match r { Value::Array(rc) => rc.borrow().len(), _ => return Err(AxityError::rt("matrix rows must be arrays")) };
-                    if rl != pcols { return Err(AxityError::rt("matrix B rows have inconsistent lengths")); }
-                }
-                let mut use_flt = false;
-                for r in &*ab {
-                    if let Value::Array(rc) = r {
-                        for v in rc.borrow().iter() {
-                            if matches!(v, Value::Flt(_)) { use_flt = true; break; }
-                        }
-                    }
-                    if use_flt { break; }
-                }
-                if !use_flt {
-                    for r in &*bb {
-                        if let Value::Array(rc) = r {
-                            for v in rc.borrow().iter() {
-                                if matches!(v, Value::Flt(_)) { use_flt = true; break; }
-                            }
-                        }
-                        if use_flt { break; }
-                    }
-                }
-                let mut out_rows: Vec<Value> = Vec::with_capacity(m);
-                for i in 0..m {
-                    let ai = match &ab[i] { Value::Array(rc) => rc.borrow(), _ => return Err(AxityError::rt("matrix rows must be arrays")) };
-                    let mut row_vals: Vec<Value> = Vec::with_capacity(pcols);
-                    for j in 0..pcols {
-                        if use_flt {
-                            let mut acc: i128 = 0;
-                            for k in 0..n {
-                                let lv = ai[k].clone();
-                                let rv = match &bb[k] { Value::Array(rc) => rc.borrow()[j].clone(), _ => return Err(AxityError::rt("matrix rows must be arrays")) };
-                                let lf: i64 = match lv { Value::Flt(f) => f, Value::Int(ii) => ii * SCALE, _ => return Err(AxityError::rt("matrix elements must be int or flt")) };
-                                let rf: i64 = match rv { Value::Flt(f) => f, Value::Int(ii) => ii * SCALE, _ => return Err(AxityError::rt("matrix elements must be int or flt")) };
-                                acc += ((lf as i128) * (rf as i128)) / (SCALE as i128);
-                            }
-                            row_vals.push(Value::Flt(acc as i64));
-                        } else {
-                            let mut acc: i128 = 0;
-                            for k in 0..n {
-                                let lv = ai[k].clone();
-                                let rv = match &bb[k] { Value::Array(rc) => rc.borrow()[j].clone(), _ => return Err(AxityError::rt("matrix rows must be arrays")) };
-                                let li: i64 = match lv { Value::Int(ii) => ii, _ => return Err(AxityError::rt("matrix elements must be int")) };
-                                let ri: i64 = match rv { Value::Int(ii) => ii, _ => return Err(AxityError::rt("matrix elements must be int")) };
-                                acc += (li as i128) * (ri as i128);
-                            }
-                            row_vals.push(Value::Int(acc as i64));
-                        }
-                    }
-                    out_rows.push(rt.new_array(row_vals));
-                }
-                Ok(rt.new_array(out_rows))
-            } else if name == "sin" || name == "cos" || name == "tan" {
-                if args.len() != 1 { return Err(AxityError::rt("trig expects one argument (radians)")); }
-                let x = eval_expr(p, &args[0], rt, out)?;
-                let xr = match x {
-                    Value::Flt(f) => (f as f64) / (SCALE as f64),
-                    Value::Int(i) => (i as f64),
-                    _ => return Err(AxityError::rt("trig arg must be flt or int"))
-                };
-                let val = if name=="sin" { xr.sin() } else if name=="cos" { xr.cos() } else { xr.tan() };
-                Ok(Value::Flt((val * SCALE as f64).round() as i64))
-            } else {
-                // try lambda in variables first, else named function
-                if let Some(val) = rt.get(&name) {
-                    if let Value::Lambda(l) = val {
-                        rt.push_scope();
-                        for (i, par) in l.params.iter().enumerate() {
-                            let av = if let Some(arg) = args.get(i) { eval_expr(p, arg, rt, out)? } else { Value::Int(0) };
-                            rt.set(par.name.clone(), av);
-                        }
-                        for st in &l.body {
-                            match exec_stmt(p, st, rt, out)? {
-                                Control::Next => {}
-                                Control::Return(v) => { rt.pop_scope(); return Ok(v); }
-                                Control::Retry => {}
-                                Control::Throw(e) => { rt.pop_scope(); return Err(AxityError::rt(&format!("uncaught exception: {}", fmt_value(&e, 2)))); }
-                            }
-                        }
-                        rt.pop_scope();
-                        Ok(Value::Int(0))
-                    } else {
-                        let mut ev_args = Vec::new();
-                        for a in args { ev_args.push(eval_expr(p, a, rt, out)?); }
-                        call_func(name, &ev_args, p, rt, out)
+                if use_flt { break; }
+            }
+        }
+        let mut out_rows: Vec<Value> = Vec::with_capacity(m);
+        for i in 0..m {
+            let ai = match &ab[i] { Value::Array(rc) => rc.borrow(), _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")) };
+            let mut row_vals: Vec<Value> = Vec::with_capacity(pcols);
+            for j in 0..pcols {
+                if use_flt {
+                    let mut acc: i128 = 0;
+                    for k in 0..n {
+                        let lv = ai[k].clone();
+                        let rv = match &bb[k] { Value::Array(rc) => rc.borrow()[j].clone(), _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")) };
+                        let lf: i64 = match lv { Value::Flt(f) => f, Value::Int(ii) => ii * SCALE, _ => return Err(AxityError::rt("matrix elements must be int or flt").with_code("AX0405")) };
+                        let rf: i64 = match rv { Value::Flt(f) => f, Value::Int(ii) => ii * SCALE, _ => return Err(AxityError::rt("matrix elements must be int or flt").with_code("AX0405")) };
+                        acc += ((lf as i128) * (rf as i128)) / (SCALE as i128);
                     }
+                    row_vals.push(Value::Flt(acc as i64));
                 } else {
-                    let mut ev_args = Vec::new();
-                    for a in args { ev_args.push(eval_expr(p, a, rt, out)?); }
-                    call_func(name, &ev_args, p, rt, out)
+                    let mut acc: i128 = 0;
+                    for k in 0..n {
+                        let lv = ai[k].clone();
+                        let rv = match &bb[k] { Value::Array(rc) => rc.borrow()[j].clone(), _ => return Err(AxityError::rt("matrix rows must be arrays").with_code("AX0405")) };
+                        let li: i64 = match lv { Value::Int(ii) => ii, _ => return Err(AxityError::rt("matrix elements must be int").with_code("AX0405")) };
+                        let ri: i64 = match rv { Value::Int(ii) => ii, _ => return Err(AxityError::rt("matrix elements must be int").with_code("AX0405")) };
+                        acc += (li as i128) * (ri as i128);
+                    }
+                    row_vals.push(Value::Int(acc as i64));
                 }
             }
+            out_rows.push(rt.new_array(row_vals));
+        }
+        Ok(rt.new_array(out_rows))
+    } else if name == "sin" || name == "cos" || name == "tan" {
+        if args.len() != 1 { return Err(AxityError::rt("trig expects one argument (radians)").with_code("AX0406")); }
+        let x = eval_expr(p, &args[0], rt, out)?;
+        let xr = match x {
+            Value::Flt(f) => (f as f64) / (SCALE as f64),
+            Value::Int(i) => (i as f64),
+            _ => return Err(AxityError::rt("trig arg must be flt or int").with_code("AX0405"))
+        };
+        let val = if name=="sin" { xr.sin() } else if name=="cos" { xr.cos() } else { xr.tan() };
+        Ok(Value::Flt((val * SCALE as f64).round() as i64))
+    } else {
+        return Ok(None);
+    };
+    v.map(Some)
+}
+
+// Calls a script function by name with already-evaluated arguments; expects `build_index` to have been called for `p`.
+pub fn call_function(p: &Program, name: &str, args: &[Value], rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    let fidx = rt.func_index.get(name).cloned().ok_or_else(|| AxityError::rt(&format!("undefined function '{}'", name)).with_code("AX0404"))?;
+    if let Item::Func(f) = &p.items[fidx] {
+        if f.params.len() != args.len() { return Err(AxityError::rt(&format!("function '{}' expects {} arguments, got {}", name, f.params.len(), args.len())).with_code("AX0406")); }
+        if let Some((i, par)) = f.params.iter().enumerate().find(|(i, par)| !value_matches(&args[*i], &par.ty)) {
+            return Err(AxityError::rt(&format!("argument {} of '{}' must be {}", i + 1, name, par.ty)).with_code("AX0405"));
         }
     }
+    rt.begin_run();
+    let r = call_func(name, args, None, p, rt, out);
+    rt.end_run();
+    r
+}
+
+// Runs `body` inside a call frame; an error leaving the frame gets its backtrace captured while the frame is still active.
+fn in_frame<T>(rt: &mut Runtime, name: &str, site: Option<&Span>, body: impl FnOnce(&mut Runtime) -> Result<T, AxityError>) -> Result<T, AxityError> {
+    rt.enter_call(name, site)?;
+    let r = body(rt).map_err(|mut e| {
+        if e.details.trace.is_empty() { e.details.trace = rt.capture_trace(e.span.as_ref()); }
+        e
+    });
+    rt.exit_call();
+    r
+}
+
+fn call_func(name: &str, args: &[Value], site: Option<&Span>, p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    in_frame(rt, name, site, |rt| call_func_body(name, args, p, rt, out))
 }
 
-fn call_func(name: &str, args: &[Value], p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
-    let fidx = rt.func_index.get(name).cloned().ok_or_else(|| AxityError::rt("undefined function"))?;
-    let f = match &p.items[fidx] { Item::Func(f) => f, _ => return Err(AxityError::rt("function index mismatch")) };
+fn call_func_body(name: &str, args: &[Value], p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    if !rt.func_index.contains_key(name) {
+        if let Some(nf) = rt.natives.get(name).cloned() { return nf.call(rt, args); }
+    }
+    let fidx = rt.func_index.get(name).cloned().ok_or_else(|| AxityError::rt("undefined function").with_code("AX0404"))?;
+    let f = match &p.items[fidx] { Item::Func(f) => f, _ => return Err(AxityError::rt("function index mismatch").with_code("AX0404")) };
     rt.push_scope();
     for (i,par) in f.params.iter().enumerate() { rt.set(par.name.clone(), args.get(i).cloned().unwrap_or(Value::Int(0))); }
     for st in &f.body {
         match exec_stmt(p, st, rt, out)? {
             Control::Next => {},
             Control::Return(v) => { rt.pop_scope(); return Ok(v); },
-            Control::Retry => {},
-            Control::Throw(e) => { rt.pop_scope(); return Err(AxityError::rt(&format!("uncaught exception: {}", fmt_value(&e, 2)))); }
+            Control::Retry | Control::Break(_) | Control::Continue(_) => {},
+            Control::Throw(e) => { rt.pop_scope(); return Err(unwind(rt, e)); }
         }
     }
     rt.pop_scope();
     Ok(Value::Int(0))
 }
 
-fn call_method(name: &str, args: &[Value], p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
-    let (obj, rest) = args.split_first().ok_or_else(|| AxityError::rt("missing receiver"))?;
-    let class_name = match obj { Value::Object(rc) => rc.borrow().class.clone(), _ => return Err(AxityError::rt("receiver is not object")) };
-    let cidx = rt.class_index.get(&class_name).cloned().ok_or_else(|| AxityError::rt("undefined class"))?;
-    let c = match &p.items[cidx] { Item::Class(c) => c, _ => return Err(AxityError::rt("class index mismatch")) };
-    let f = c.methods.iter().find(|m| m.name == name).ok_or_else(|| AxityError::rt("undefined method"))?;
+fn call_method(name: &str, args: &[Value], site: Option<&Span>, p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    let frame = match args.first() { Some(Value::Object(rc)) => format!("{}.{}", rc.borrow().class, name), _ => name.to_string() };
+    in_frame(rt, &frame, site, |rt| call_method_body(name, args, p, rt, out))
+}
+
+fn call_method_body(name: &str, args: &[Value], p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    let (obj, rest) = args.split_first().ok_or_else(|| AxityError::rt("missing receiver").with_code("AX0404"))?;
+    let class_name = match obj { Value::Object(rc) => rc.borrow().class.clone(), _ => return Err(AxityError::rt("receiver is not object").with_code("AX0405")) };
+    let cidx = rt.class_index.get(&class_name).cloned().ok_or_else(|| AxityError::rt("undefined class").with_code("AX0404"))?;
+    let c = match &p.items[cidx] { Item::Class(c) => c, _ => return Err(AxityError::rt("class index mismatch").with_code("AX0404")) };
+    let f = c.methods.iter().find(|m| m.name == name).ok_or_else(|| AxityError::rt("undefined method").with_code("AX0404"))?;
     rt.push_scope();
     for (i,par) in f.params.iter().enumerate() { rt.set(par.name.clone(), if i==0 { args.get(0).cloned().unwrap() } else { rest.get(i-1).cloned().unwrap_or(Value::Int(0)) }); }
     for st in &f.body {
         match exec_stmt(p, st, rt, out)? {
             Control::Next => {},
             Control::Return(v) => { rt.pop_scope(); return Ok(v); },
-            Control::Retry => {},
-            Control::Throw(e) => { rt.pop_scope(); return Err(AxityError::rt(&format!("uncaught exception: {}", fmt_value(&e, 2)))); }
+            Control::Retry | Control::Break(_) | Control::Continue(_) => {},
+            Control::Throw(e) => { rt.pop_scope(); return Err(unwind(rt, e)); }
+        }
+    }
+    rt.pop_scope();
+    Ok(Value::Int(0))
+}
+
+// Arguments are evaluated inside the lambda's scope, as they always have been.
+fn call_lambda(name: &str, l: &Lambda, args: &[Expr], site: Option<&Span>, p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    in_frame(rt, name, site, |rt| call_lambda_body(l, args, p, rt, out))
+}
+
+fn call_lambda_body(l: &Lambda, args: &[Expr], p: &Program, rt: &mut Runtime, out: &mut String) -> Result<Value, AxityError> {
+    if l.params.len() != args.len() { return Err(AxityError::rt(&format!("lambda expects {} arguments, got {}", l.params.len(), args.len())).with_code("AX0406")); }
+    rt.push_scope();
+    for (i, par) in l.params.iter().enumerate() {
+        let av = if let Some(arg) = args.get(i) { eval_expr(p, arg, rt, out)? } else { Value::Int(0) };
+        rt.set(par.name.clone(), av);
+    }
+    for st in &l.body {
+        match exec_stmt(p, st, rt, out)? {
+            Control::Next => {}
+            Control::Return(v) => { rt.pop_scope(); return Ok(v); }
+            Control::Retry | Control::Break(_) | Control::Continue(_) => {}
+            Control::Throw(e) => { rt.pop_scope(); return Err(unwind(rt, e)); }
         }
     }
     rt.pop_scope();
     Ok(Value::Int(0))
 }
 
-enum Control { Next, Return(Value), Retry, Throw(Value) }
+fn unwind(rt: &mut Runtime, v: Value) -> AxityError {
+    let msg = fmt_value(&v, 2);
+    rt.unwind(v, &msg)
+}
+
+// The value a `catch` receives for a recoverable runtime fault: an `obj` with `kind`, `message` and `code`.
+fn fault_value(rt: &mut Runtime, e: &AxityError) -> Option<Value> {
+    let kind = e.exception_kind()?;
+    Some(error_object(rt, crate::types::error_class_for(kind), e.kind.message(), e.code))
+}
+
+fn error_object(rt: &mut Runtime, class: &str, message: &str, code: &str) -> Value {
+    let kind = crate::types::error_class_kind(class).unwrap_or("error");
+    let mut fields = HashMap::new();
+    fields.insert("message".to_string(), Value::Str(message.to_string()));
+    fields.insert("kind".to_string(), Value::Str(kind.to_string()));
+    fields.insert("code".to_string(), Value::Str(code.to_string()));
+    rt.new_object(class.to_string(), fields)
+}
+
+// Whether `v` fits `ty`: a `catch (e: T)` clause taking a thrown value, or a host call argument.
+fn value_matches(v: &Value, ty: &crate::types::Type) -> bool {
+    use crate::types::Type;
+    match (ty, v) {
+        (Type::Any | Type::Error, _) => true,
+        (Type::Class(c), Value::Object(rc)) => {
+            let class = &rc.borrow().class;
+            class == c || (c == "Error" && crate::types::is_error_class(class))
+        }
+        (Type::Int, Value::Int(_)) | (Type::Flt, Value::Flt(_)) | (Type::String, Value::Str(_)) | (Type::Bool, Value::Bool(_))
+        | (Type::Array(_), Value::Array(_)) | (Type::Map(_), Value::Map(_)) | (Type::Obj, Value::Obj(_))
+        | (Type::Buffer, Value::Buffer(_)) | (Type::Fn(..), Value::Lambda(_)) => true,
+        _ => false,
+    }
+}
+
+// Runs `body` in a fresh scope; on failure the scopes it left open are discarded.
+fn exec_block(p: &Program, body: &[Stmt], rt: &mut Runtime, out: &mut String) -> Result<Control, AxityError> {
+    let depth = rt.scopes.len();
+    rt.push_scope();
+    for st in body {
+        match exec_stmt(p, st, rt, out) {
+            Ok(Control::Next) => {}
+            Ok(ctl) => { rt.scopes.truncate(depth + 1); rt.pop_scope(); return Ok(ctl); }
+            Err(e) => { rt.scopes.truncate(depth); return Err(e); }
+        }
+    }
+    rt.pop_scope();
+    Ok(Control::Next)
+}
+
+// A caught runtime fault or cross-frame throw leaves the same way it arrived.
+fn rethrow(rt: &mut Runtime, span: &Span) -> Result<Control, AxityError> {
+    match rt.current_catch() {
+        Some((v, Some(e))) => Err(rt.restore_thrown(v, e)),
+        Some((v, None)) => { rt.note_throw(span); Ok(Control::Throw(v)) }
+        None => Err(AxityError::rt("`throw;` outside a catch block").with_code("AX0313")),
+    }
+}
+
+fn exec_try(p: &Program, body: &[Stmt], catches: &[CatchClause], finally: Option<&[Stmt]>, retry: &RetryPolicy, rt: &mut Runtime, out: &mut String) -> Result<Control, AxityError> {
+    // The type checker only allows `retry` under `attempts`, so a `try` without it runs once.
+    let attempts = match &retry.attempts { Some(e) => try_option(p, e, rt, out)?, None => 1 };
+    let backoff = match &retry.backoff { Some(e) => try_option(p, e, rt, out)?, None => 0 };
+    if attempts < 1 { return Err(AxityError::rt("try attempts must be at least 1").with_code("AX0410")); }
+    if backoff < 0 { return Err(AxityError::rt("try backoff must not be negative").with_code("AX0410")); }
+    rt.enter_try();
+    let res = loop {
+        let mut again = false;
+        let mut outcome = exec_block(p, body, rt, out);
+        let raised = match outcome {
+            Ok(Control::Throw(ref v)) => Some((v.clone(), None)),
+            Err(ref e) => match rt.take_thrown(e).or_else(|| fault_value(rt, e)) {
+                Some(v) => Some((v, Some(e.clone()))),
+                // Fatal errors (limits, interrupts) skip every handler but still run `finally`.
+                None => {
+                    if let Some(fin) = finally { outcome = exec_finally(p, fin, outcome, rt, out); }
+                    break outcome;
+                }
+            },
+            _ => None,
+        };
+        if let Some((v, err)) = raised {
+            outcome = match catches.iter().find(|c| value_matches(&v, &c.ty)) {
+                Some(c) => {
+                    let depth = rt.scopes.len();
+                    rt.push_scope();
+                    rt.set(c.name.clone(), v.clone());
+                    rt.begin_catch(v.clone(), err.clone());
+                    let res = exec_block(p, &c.body, rt, out);
+                    rt.end_catch();
+                    rt.scopes.truncate(depth + 1);
+                    rt.pop_scope();
+                    match res {
+                        // Once the attempts are used up, `retry` lets the exception go on unwinding.
+                        Ok(Control::Retry) if rt.attempt() < attempts => { again = true; Ok(Control::Next) }
+                        Ok(Control::Retry) => propagate(rt, v, err),
+                        res => res,
+                    }
+                }
+                None => propagate(rt, v, err),
+            };
+        }
+        if let Some(fin) = finally { outcome = exec_finally(p, fin, outcome, rt, out); }
+        if !again || !matches!(outcome, Ok(Control::Next)) { break outcome; }
+        if let Err(e) = rt.pause(backoff as u64) { break Err(e); }
+        rt.next_attempt();
+    };
+    rt.exit_try();
+    res
+}
+
+fn try_option(p: &Program, e: &Expr, rt: &mut Runtime, out: &mut String) -> Result<i64, AxityError> {
+    match eval_expr(p, e, rt, out)? {
+        Value::Int(n) => Ok(n),
+        _ => Err(AxityError::rt("try options must be int").with_code("AX0405")),
+    }
+}
+
+// Sends a caught value on unwinding the way it arrived: as a thrown value, or as the error it came from.
+fn propagate(rt: &mut Runtime, v: Value, err: Option<AxityError>) -> Result<Control, AxityError> {
+    match err {
+        Some(e) => Err(rt.restore_thrown(v, e)),
+        None => Ok(Control::Throw(v)),
+    }
+}
+
+fn exec_finally(p: &Program, fin: &[Stmt], outcome: Result<Control, AxityError>, rt: &mut Runtime, out: &mut String) -> Result<Control, AxityError> {
+    if let Err(ref e) = outcome {
+        // A fatal error runs `finally` on a small grace budget and stays the outcome, however `finally` ends.
+        if e.exception_kind().is_none() && e.code != "AX0403" {
+            rt.begin_grace();
+            let _ = exec_block(p, fin, rt, out);
+            return outcome;
+        }
+    }
+    // Whatever the block is leaving with stays alive, and pending, while `finally` runs.
+    let pending = rt.pending_throw();
+    let kept = match outcome { Ok(Control::Return(ref v)) | Ok(Control::Throw(ref v)) => Some(v.clone()), _ => None };
+    let mark = rt.hold(pending.iter().cloned().chain(kept));
+    let fin_res = exec_block(p, fin, rt, out);
+    rt.release(mark);
+    match fin_res {
+        Ok(Control::Next) => {}
+        // Leaving `finally` early replaces the original outcome.
+        other => return other,
+    }
+    match (pending, outcome) {
+        (Some(v), Err(e)) => Err(rt.restore_thrown(v, e)),
+        (_, outcome) => outcome,
+    }
+}
+
+// An unlabelled `break` or `continue` stops at the innermost loop; a labelled one at the loop with that label.
+fn targets(target: &Option<String>, label: &Option<String>) -> bool {
+    target.is_none() || target == label
+}
+
+fn int_div(a: i64, b: i64) -> Result<i64, AxityError> {
+    if b == 0 { Err(AxityError::rt("division by zero").with_code("AX0401")) } else { Ok(a.wrapping_div(b)) }
+}
+
+fn int_rem(a: i64, b: i64) -> Result<i64, AxityError> {
+    if b == 0 { Err(AxityError::rt("modulo by zero").with_code("AX0401")) } else { Ok(a.wrapping_rem(b)) }
+}
+
+// Fixed-point division; both operands are scaled by `SCALE`.
+fn flt_div(a: i64, b: i64) -> Result<i64, AxityError> {
+    if b == 0 { Err(AxityError::rt("division by zero").with_code("AX0401")) } else { Ok(((a as i128) * (SCALE as i128) / (b as i128)) as i64) }
+}
+
+enum Control { Next, Return(Value), Retry, Break(Option<String>), Continue(Option<String>), Throw(Value) }
 
 pub fn fmt_value(v: &Value, depth: usize) -> String {
     if depth == 0 { return String::from("..."); }
@@ -1,21 +1,39 @@
-use crate::error::{AxityError, Span};
+use std::iter::Peekable;
+use std::str::CharIndices;
+use crate::error::{AxityError, FileId, Span};
 use crate::token::{Token, TokenKind};
 
-pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> {
+// `chars().peekable()` that also reports the byte offset of the next character.
+#[derive(Clone)]
+struct Cursor<'a> {
+    it: Peekable<CharIndices<'a>>,
+    len: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(s: &'a str) -> Self { Self { it: s.char_indices().peekable(), len: s.len() } }
+    fn peek(&mut self) -> Option<&char> { self.it.peek().map(|(_, c)| c) }
+    fn next(&mut self) -> Option<char> { self.it.next().map(|(_, c)| c) }
+    fn pos(&mut self) -> usize { self.it.peek().map(|(i, _)| *i).unwrap_or(self.len) }
+}
+
+pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> { lex_file(input, FileId::default()) }
+
+pub fn lex_file(input: &str, file: FileId) -> Result<Vec<Token>, AxityError> {
     let mut out = Vec::new();
-    let mut iter = input.chars().peekable();
+    let mut iter = Cursor::new(input);
     let mut line = 1usize;
     let mut col = 1usize;
     while let Some(&c) = iter.peek() {
         if c == '\n' { iter.next(); line += 1; col = 1; continue; }
         if c.is_whitespace() { iter.next(); col += 1; continue; }
         if c == '\"' {
-            let start_col = col;
+            let start_col = col; let start = iter.pos();
             iter.next(); col += 1;
             let mut s = String::new();
             while let Some(&ch) = iter.peek() {
                 if ch == '\"' { break; }
-                if ch == '\n' { return Err(AxityError::lex("unterminated string", Span{ line, col: start_col })); }
+                if ch == '\n' { return Err(AxityError::lex("unterminated string", Span::new(file, line, start_col, start, iter.pos())).with_code("AX0102")); }
                 if ch == '\\' {
                     iter.next(); col += 1;
                     match iter.peek().copied() {
@@ -30,12 +48,12 @@ pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> {
                     s.push(ch); iter.next(); col += 1;
                 }
             }
-            if iter.peek() == Some(&'\"') { iter.next(); col += 1; } else { return Err(AxityError::lex("unterminated string", Span{ line, col: start_col })); }
-            out.push(Token{ kind: TokenKind::StringLit(s), span: Span{ line, col: start_col } });
+            if iter.peek() == Some(&'\"') { iter.next(); col += 1; } else { return Err(AxityError::lex("unterminated string", Span::new(file, line, start_col, start, iter.pos())).with_code("AX0102")); }
+            out.push(Token{ kind: TokenKind::StringLit(s), span: Span::new(file, line, start_col, start, iter.pos()) });
             continue;
         }
         if c.is_ascii_alphabetic() || c == '_' {
-            let start_col = col;
+            let start_col = col; let start = iter.pos();
             let mut s = String::new();
             while let Some(&ch) = iter.peek() {
                 if ch.is_ascii_alphanumeric() || ch == '_' { s.push(ch); iter.next(); col += 1; } else { break; }
@@ -54,8 +72,11 @@ pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> {
                 "else" => TokenKind::Else,
                 "try" => TokenKind::Try,
                 "catch" => TokenKind::Catch,
+                "finally" => TokenKind::Finally,
                 "throw" => TokenKind::Throw,
                 "retry" => TokenKind::Retry,
+                "break" => TokenKind::Break,
+                "continue" => TokenKind::Continue,
                 "do" => TokenKind::Do,
                 "for" => TokenKind::For,
                 "in" => TokenKind::In,
@@ -77,11 +98,11 @@ pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> {
                 "map" => TokenKind::MapKw,
                 _ => TokenKind::Ident(s),
             };
-            out.push(Token{ kind, span: Span{ line, col: start_col } });
+            out.push(Token{ kind, span: Span::new(file, line, start_col, start, iter.pos()) });
             continue;
         }
         if c.is_ascii_digit() {
-            let start_col = col;
+            let start_col = col; let start = iter.pos();
             let mut s = String::new();
             let mut has_dot = false;
             let mut has_exp = false;
@@ -96,46 +117,47 @@ pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> {
             if has_dot || has_exp {
                 let f = s.parse::<f64>().unwrap_or(0.0);
                 let scaled = (f * 1_000_000.0).round() as i64;
-                out.push(Token{ kind: TokenKind::FltLit(scaled), span: Span{ line, col: start_col } });
+                out.push(Token{ kind: TokenKind::FltLit(scaled), span: Span::new(file, line, start_col, start, iter.pos()) });
             } else {
                 let v = s.parse::<i64>().unwrap_or(0);
-                out.push(Token{ kind: TokenKind::IntLit(v), span: Span{ line, col: start_col } });
+                out.push(Token{ kind: TokenKind::IntLit(v), span: Span::new(file, line, start_col, start, iter.pos()) });
             }
             continue;
         }
         match c {
-            '(' => { out.push(Token{ kind: TokenKind::LParen, span: Span{ line, col } }); iter.next(); col += 1; }
-            ')' => { out.push(Token{ kind: TokenKind::RParen, span: Span{ line, col } }); iter.next(); col += 1; }
-            '{' => { out.push(Token{ kind: TokenKind::LBrace, span: Span{ line, col } }); iter.next(); col += 1; }
-            '}' => { out.push(Token{ kind: TokenKind::RBrace, span: Span{ line, col } }); iter.next(); col += 1; }
-            '[' => { out.push(Token{ kind: TokenKind::LBracket, span: Span{ line, col } }); iter.next(); col += 1; }
-            ']' => { out.push(Token{ kind: TokenKind::RBracket, span: Span{ line, col } }); iter.next(); col += 1; }
-            ':' => { out.push(Token{ kind: TokenKind::Colon, span: Span{ line, col } }); iter.next(); col += 1; }
-            ';' => { out.push(Token{ kind: TokenKind::Semicolon, span: Span{ line, col } }); iter.next(); col += 1; }
-            ',' => { out.push(Token{ kind: TokenKind::Comma, span: Span{ line, col } }); iter.next(); col += 1; }
-            '.' => { out.push(Token{ kind: TokenKind::Dot, span: Span{ line, col } }); iter.next(); col += 1; }
+            '(' => { out.push(Token{ kind: TokenKind::LParen, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            ')' => { out.push(Token{ kind: TokenKind::RParen, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            '{' => { out.push(Token{ kind: TokenKind::LBrace, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            '}' => { out.push(Token{ kind: TokenKind::RBrace, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            '[' => { out.push(Token{ kind: TokenKind::LBracket, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            ']' => { out.push(Token{ kind: TokenKind::RBracket, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            ':' => { out.push(Token{ kind: TokenKind::Colon, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            '?' => { out.push(Token{ kind: TokenKind::Question, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            ';' => { out.push(Token{ kind: TokenKind::Semicolon, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            ',' => { out.push(Token{ kind: TokenKind::Comma, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            '.' => { out.push(Token{ kind: TokenKind::Dot, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
             '-' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('>') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::Arrow, span: Span{ line, col: start_col } }); }
-                else if let Some('-') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::MinusMinus, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::Minus, span: Span{ line, col: start_col } }); }
+                if let Some('>') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::Arrow, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else if let Some('-') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::MinusMinus, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::Minus, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
             '=' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::EqEq, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::Assign, span: Span{ line, col: start_col } }); }
+                if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::EqEq, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::Assign, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
             '+' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('+') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::PlusPlus, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::Plus, span: Span{ line, col: start_col } }); }
+                if let Some('+') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::PlusPlus, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::Plus, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
-            '*' => { out.push(Token{ kind: TokenKind::Star, span: Span{ line, col } }); iter.next(); col += 1; }
+            '*' => { out.push(Token{ kind: TokenKind::Star, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
             '/' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
                 if let Some('/') = iter.peek().copied() {
                     // Could be '//' single-line or '///' block comment
@@ -184,50 +206,51 @@ pub fn lex(input: &str) -> Result<Vec<Token>, AxityError> {
                         continue;
                     }
                 } else {
-                    out.push(Token{ kind: TokenKind::Slash, span: Span{ line, col: start_col } });
+                    out.push(Token{ kind: TokenKind::Slash, span: Span::new(file, line, start_col, start, iter.pos()) });
                 }
             }
-            '%' => { out.push(Token{ kind: TokenKind::Percent, span: Span{ line, col } }); iter.next(); col += 1; }
+            '%' => { out.push(Token{ kind: TokenKind::Percent, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
             '&' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('&') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::AndAnd, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::BitAnd, span: Span{ line, col: start_col } }); }
+                if let Some('&') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::AndAnd, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::BitAnd, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
             '|' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('|') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::OrOr, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::BitOr, span: Span{ line, col: start_col } }); }
+                if let Some('|') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::OrOr, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::BitOr, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
-            '^' => { out.push(Token{ kind: TokenKind::BitXor, span: Span{ line, col } }); iter.next(); col += 1; }
-            '~' => { out.push(Token{ kind: TokenKind::Tilde, span: Span{ line, col } }); iter.next(); col += 1; }
+            '^' => { out.push(Token{ kind: TokenKind::BitXor, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
+            '~' => { out.push(Token{ kind: TokenKind::Tilde, span: Span::new(file, line, col, iter.pos(), iter.pos() + 1) }); iter.next(); col += 1; }
             '<' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('<') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::Shl, span: Span{ line, col: start_col } }); }
-                else if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::LessEq, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::Less, span: Span{ line, col: start_col } }); }
+                if let Some('<') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::Shl, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::LessEq, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::Less, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
             '>' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('>') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::Shr, span: Span{ line, col: start_col } }); }
-                else if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::GreaterEq, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::Greater, span: Span{ line, col: start_col } }); }
+                if let Some('>') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::Shr, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::GreaterEq, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::Greater, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
             '!' => {
-                let start_col = col;
+                let start_col = col; let start = iter.pos();
                 iter.next(); col += 1;
-                if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::NotEq, span: Span{ line, col: start_col } }); }
-                else { out.push(Token{ kind: TokenKind::Bang, span: Span{ line, col: start_col } }); }
+                if let Some('=') = iter.peek().copied() { iter.next(); col += 1; out.push(Token{ kind: TokenKind::NotEq, span: Span::new(file, line, start_col, start, iter.pos()) }); }
+                else { out.push(Token{ kind: TokenKind::Bang, span: Span::new(file, line, start_col, start, iter.pos()) }); }
             }
             _ => {
-                return Err(AxityError::lex("unexpected character", Span{ line, col }));
+                return Err(AxityError::lex("unexpected character", Span::new(file, line, col, iter.pos(), iter.pos() + c.len_utf8())).with_code("AX0101"));
             }
         }
     }
-    out.push(Token{ kind: TokenKind::Eof, span: Span{ line, col } });
+    let end = iter.pos();
+    out.push(Token{ kind: TokenKind::Eof, span: Span::new(file, line, col, end, end) });
     Ok(out)
 }
 
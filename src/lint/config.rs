@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariable,
+    UnusedFunction,
+    UnreachableCode,
+    ShadowedBinding,
+    ConstantCondition,
+    AnyComparison,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 6] = [Lint::UnusedVariable, Lint::UnusedFunction, Lint::UnreachableCode, Lint::ShadowedBinding, Lint::ConstantCondition, Lint::AnyComparison];
+    // Name used in warnings, `--allow=`/`--deny=` flags and `[lints]` tables.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused_variable",
+            Lint::UnusedFunction => "unused_function",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::ShadowedBinding => "shadowed_binding",
+            Lint::ConstantCondition => "constant_condition",
+            Lint::AnyComparison => "any_comparison",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Lint> { Lint::ALL.into_iter().find(|l| l.name() == name) }
+    pub fn description(self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "a variable or parameter that is never read",
+            Lint::UnusedFunction => "a function that is never called",
+            Lint::UnreachableCode => "statements after `return`, `throw` or `retry`",
+            Lint::ShadowedBinding => "a `let` that reuses the name of a variable in an enclosing scope",
+            Lint::ConstantCondition => "an `if` whose condition is always true or always false",
+            Lint::AnyComparison => "a comparison where one side is typed `any`",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    pub fn from_name(name: &str) -> Option<LintLevel> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+}
+
+// Level per lint; lints that were not configured warn.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintConfig {
+    pub fn new() -> Self { Self::default() }
+    pub fn allow(mut self, lint: Lint) -> Self { self.set(lint, LintLevel::Allow); self }
+    pub fn warn(mut self, lint: Lint) -> Self { self.set(lint, LintLevel::Warn); self }
+    pub fn deny(mut self, lint: Lint) -> Self { self.set(lint, LintLevel::Deny); self }
+    pub fn set(&mut self, lint: Lint, level: LintLevel) { self.levels.insert(lint, level); }
+    pub fn level(&self, lint: Lint) -> LintLevel { self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn) }
+    // `warnings` names every lint at once, so `--deny=warnings` fails on any finding.
+    pub fn set_by_name(&mut self, name: &str, level: LintLevel) -> Result<(), String> {
+        if name == "warnings" {
+            for lint in Lint::ALL { self.set(lint, level); }
+            return Ok(());
+        }
+        let lint = Lint::from_name(name).ok_or_else(|| format!("unknown lint '{}'", name))?;
+        self.set(lint, level);
+        Ok(())
+    }
+    // Reads the `[lints]` table of an `axity.toml`, e.g. `unused_variable = "allow"`.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let doc: toml::Value = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+        let mut config = Self::new();
+        let Some(table) = doc.get("lints") else { return Ok(config) };
+        let table = table.as_table().ok_or("`lints` must be a table")?;
+        // The `warnings` group applies first so individual lints can override it.
+        let mut entries: Vec<_> = table.iter().collect();
+        entries.sort_by_key(|(name, _)| *name != "warnings");
+        for (name, level) in entries {
+            let level = level.as_str().and_then(LintLevel::from_name)
+                .ok_or_else(|| format!("lint '{}' must be \"allow\", \"warn\" or \"deny\"", name))?;
+            config.set_by_name(name, level)?;
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet};
+use crate::ast::{BinOp, Expr, Function, Item, Program, Stmt};
+use crate::error::{FileId, Span, Warning};
+use crate::types::Type;
+
+mod config;
+pub use config::{Lint, LintConfig, LintLevel};
+
+// Runs every lint over a type-checked program. Allowed lints are dropped and denied ones are
+// marked `denied`; the result is ordered by position.
+pub fn lint(p: &Program, config: &LintConfig) -> Vec<Warning> {
+    let mut found: Vec<(Lint, Warning)> = Vec::new();
+    let mut names = Names::new(p);
+    names.program(p);
+    found.extend(names.out);
+    visit_blocks(p, &mut |body| {
+        unreachable(body, &mut found);
+        constant_conditions(body, &mut found);
+    });
+    let mut out: Vec<Warning> = found.into_iter().filter_map(|(lint, mut w)| match config.level(lint) {
+        LintLevel::Allow => None,
+        LintLevel::Warn => Some(w),
+        LintLevel::Deny => { w.denied = true; Some(w) }
+    }).collect();
+    out.sort_by_key(|w| (w.span.file.0, w.span.start, w.span.line, w.span.col));
+    out
+}
+
+struct Binding<'a> {
+    name: &'a str,
+    ty: &'a Type,
+    span: &'a Span,
+    used: bool,
+    // Catch variables are bound implicitly and never reported as unused.
+    check_unused: bool,
+}
+
+// Scope-aware pass: unused variables and functions, shadowed `let`s and comparisons of `any` values.
+struct Names<'a> {
+    scopes: Vec<Vec<Binding<'a>>>,
+    // First scope of the function being walked; shadowing is only reported within one function.
+    fn_base: usize,
+    current_fn: Option<&'a str>,
+    funcs: HashMap<&'a str, &'a Function>,
+    called: HashSet<&'a str>,
+    out: Vec<(Lint, Warning)>,
+}
+
+impl<'a> Names<'a> {
+    fn new(p: &'a Program) -> Self {
+        let funcs = p.items.iter().filter_map(|it| match it { Item::Func(f) => Some((f.name.as_str(), f)), _ => None }).collect();
+        Self { scopes: Vec::new(), fn_base: 0, current_fn: None, funcs, called: HashSet::new(), out: Vec::new() }
+    }
+
+    fn program(&mut self, p: &'a Program) {
+        self.scopes.push(Vec::new());
+        for it in &p.items {
+            if let Item::Stmt(s) = it { self.stmt(s); }
+        }
+        for it in &p.items {
+            match it {
+                Item::Func(f) => self.function(f),
+                Item::Class(c) => for m in &c.methods { self.function(m) },
+                _ => {}
+            }
+        }
+        self.pop();
+        // Imported files are libraries; only the entry file's functions are expected to be used.
+        for it in &p.items {
+            let Item::Func(f) = it else { continue };
+            if f.name == "main" || f.name.starts_with('_') || f.span.file != FileId::default() || self.called.contains(f.name.as_str()) { continue; }
+            self.out.push((Lint::UnusedFunction, Warning::new(Lint::UnusedFunction.name(), &format!("function `{}` is never called", f.name), &f.span)));
+        }
+    }
+
+    fn function(&mut self, f: &'a Function) {
+        self.fn_base = self.scopes.len();
+        self.current_fn = Some(&f.name);
+        self.scopes.push(Vec::new());
+        for par in &f.params { self.declare(&par.name, &par.ty, &par.span, false, true); }
+        for s in &f.body { self.stmt(s); }
+        self.pop();
+        self.fn_base = 0;
+        self.current_fn = None;
+    }
+
+    fn declare(&mut self, name: &'a str, ty: &'a Type, span: &'a Span, is_let: bool, check_unused: bool) {
+        if is_let && !name.starts_with('_') {
+            if let Some(prev) = self.scopes[self.fn_base..].iter().rev().flat_map(|sc| sc.iter().rev()).find(|b| b.name == name) {
+                let w = Warning::new(Lint::ShadowedBinding.name(), &format!("`{}` shadows an earlier binding", name), span)
+                    .with_label(prev.span, &format!("previous binding of `{}`", name))
+                    .with_note(&format!("to update the existing variable, assign with `{} = ...`", name));
+                self.out.push((Lint::ShadowedBinding, w));
+            }
+        }
+        self.scopes.last_mut().unwrap().push(Binding { name, ty, span, used: false, check_unused });
+    }
+
+    fn pop(&mut self) {
+        for b in self.scopes.pop().unwrap_or_default() {
+            if b.used || !b.check_unused || b.name.starts_with('_') || b.name == "self" { continue; }
+            let w = Warning::new(Lint::UnusedVariable.name(), &format!("unused variable `{}`", b.name), b.span)
+                .with_note(&format!("if this is intentional, prefix it with an underscore: `_{}`", b.name));
+            self.out.push((Lint::UnusedVariable, w));
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding<'a>> {
+        self.scopes.iter().rev().flat_map(|sc| sc.iter().rev()).find(|b| b.name == name)
+    }
+
+    fn use_name(&mut self, name: &str) {
+        if let Some(b) = self.scopes.iter_mut().rev().flat_map(|sc| sc.iter_mut().rev()).find(|b| b.name == name) {
+            b.used = true;
+        } else if let Some((&f, _)) = self.funcs.get_key_value(name) {
+            if self.current_fn != Some(f) { self.called.insert(f); }
+        }
+    }
+
+    fn block(&mut self, body: &'a [Stmt]) {
+        self.scopes.push(Vec::new());
+        for s in body { self.stmt(s); }
+        self.pop();
+    }
+
+    fn stmt(&mut self, s: &'a Stmt) {
+        match s {
+            Stmt::Let{ name, ty, init, span } => { self.expr(init); self.declare(name, ty, span, true, true); }
+            // Assigning to a variable does not count as reading it.
+            Stmt::Assign{ expr, .. } => self.expr(expr),
+            Stmt::MemberAssign{ object, expr, .. } => { self.expr(object); self.expr(expr); }
+            Stmt::Print{ expr, .. } | Stmt::Throw{ expr, .. } | Stmt::Return{ expr, .. } | Stmt::Expr(expr) => self.expr(expr),
+            Stmt::Retry(_) | Stmt::Rethrow(_) | Stmt::Break{ .. } | Stmt::Continue{ .. } => {}
+            Stmt::Try{ body, catches, finally, retry, .. } => {
+                for e in retry.attempts.iter().chain(&retry.backoff) { self.expr(e); }
+                self.block(body);
+                for c in catches {
+                    self.scopes.push(Vec::new());
+                    self.declare(&c.name, &c.ty, &c.span, false, false);
+                    for s in &c.body { self.stmt(s); }
+                    self.pop();
+                }
+                if let Some(fin) = finally { self.block(fin); }
+            }
+            Stmt::While{ cond, body, .. } => { self.expr(cond); self.block(body); }
+            Stmt::DoWhile{ body, cond, .. } => { self.block(body); self.expr(cond); }
+            Stmt::ForC{ init, cond, post, body, .. } => {
+                self.scopes.push(Vec::new());
+                if let Some(init) = init { self.stmt(init); }
+                if let Some(cond) = cond { self.expr(cond); }
+                if let Some(post) = post { self.stmt(post); }
+                self.block(body);
+                self.pop();
+            }
+            Stmt::ForEach{ var, collection, body, span, .. } => {
+                self.expr(collection);
+                self.scopes.push(Vec::new());
+                // Element types are not tracked; `Error` keeps the variable out of `any_comparison`.
+                self.declare(var, &Type::Error, span, false, true);
+                for s in body { self.stmt(s); }
+                self.pop();
+            }
+            Stmt::If{ cond, then_body, else_body, .. } => { self.expr(cond); self.block(then_body); self.block(else_body); }
+            Stmt::Match{ expr, arms, default, .. } => {
+                self.expr(expr);
+                for arm in arms { self.block(&arm.body); }
+                if let Some(d) = default { self.block(d); }
+            }
+        }
+    }
+
+    fn expr(&mut self, e: &'a Expr) {
+        match e {
+            Expr::Int(..) | Expr::Flt(..) | Expr::Bool(..) => {}
+            Expr::Str(s, _) => for name in interpolated(s) { self.use_name(name) },
+            Expr::Var(name, _) => self.use_name(name),
+            Expr::ArrayLit(items, _) | Expr::New(_, items, _) => for x in items { self.expr(x) },
+            Expr::ObjLit(fields, _) => for (_, x) in fields { self.expr(x) },
+            Expr::Lambda{ params, body, .. } => {
+                self.scopes.push(Vec::new());
+                for par in params { self.declare(&par.name, &par.ty, &par.span, false, true); }
+                for s in body { self.stmt(s); }
+                self.pop();
+            }
+            Expr::Member{ object, .. } => self.expr(object),
+            Expr::Index{ array, index, .. } => { self.expr(array); self.expr(index); }
+            Expr::MethodCall{ object, args, .. } => { self.expr(object); for a in args { self.expr(a); } }
+            Expr::UnaryNot{ expr, .. } | Expr::UnaryNeg{ expr, .. } | Expr::UnaryBitNot{ expr, .. } => self.expr(expr),
+            Expr::Cond{ cond, then_expr, else_expr, .. } => { self.expr(cond); self.expr(then_expr); self.expr(else_expr); }
+            Expr::Binary{ op, left, right, span } => {
+                if matches!(op, BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge) && (self.is_any(left) || self.is_any(right)) {
+                    let w = Warning::new(Lint::AnyComparison.name(), "comparison involving an `any` value", span)
+                        .with_note("the result depends on the runtime type; convert the value first, e.g. with `to_int` or `to_string`");
+                    self.out.push((Lint::AnyComparison, w));
+                }
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Call{ name, args, .. } => { self.use_name(name); for a in args { self.expr(a); } }
+            Expr::CallCallee{ callee, args, .. } => { self.expr(callee); for a in args { self.expr(a); } }
+        }
+    }
+
+    // Whether the expression is statically typed `any`, as far as declarations tell.
+    fn is_any(&self, e: &Expr) -> bool {
+        match e {
+            Expr::Var(name, _) => self.lookup(name).is_some_and(|b| *b.ty == Type::Any),
+            Expr::Index{ array, .. } => match array.as_ref() {
+                Expr::Var(name, _) => self.lookup(name).is_some_and(|b| matches!(b.ty, Type::Array(t) | Type::Map(t) if **t == Type::Any)),
+                _ => false,
+            },
+            Expr::Member{ object, .. } => matches!(object.as_ref(), Expr::Var(name, _) if self.lookup(name).is_some_and(|b| *b.ty == Type::Obj)),
+            Expr::Call{ name, .. } => name == "input" || self.funcs.get(name.as_str()).is_some_and(|f| f.ret == Type::Any),
+            _ => false,
+        }
+    }
+}
+
+// Names referenced as `!{name}` inside a string literal.
+fn interpolated(s: &str) -> impl Iterator<Item = &str> {
+    s.split("!{").skip(1).filter_map(|rest| rest.find('}').map(|end| &rest[..end]))
+}
+
+// Calls `f` on every statement list: function, method and lambda bodies, nested blocks, and each
+// top-level statement on its own.
+fn visit_blocks<'a>(p: &'a Program, f: &mut dyn FnMut(&'a [Stmt])) {
+    for it in &p.items {
+        match it {
+            Item::Stmt(s) => { f(std::slice::from_ref(s)); visit_stmt(s, f); }
+            Item::Func(func) => visit_body(&func.body, f),
+            Item::Class(c) => for m in &c.methods { visit_body(&m.body, f) },
+            Item::Import(..) => {}
+        }
+    }
+}
+
+fn visit_body<'a>(body: &'a [Stmt], f: &mut dyn FnMut(&'a [Stmt])) {
+    f(body);
+    for s in body { visit_stmt(s, f); }
+}
+
+fn visit_stmt<'a>(s: &'a Stmt, f: &mut dyn FnMut(&'a [Stmt])) {
+    match s {
+        Stmt::Let{ init: e, .. } | Stmt::Assign{ expr: e, .. } | Stmt::Print{ expr: e, .. } | Stmt::Expr(e)
+        | Stmt::Throw{ expr: e, .. } | Stmt::Return{ expr: e, .. } => visit_expr(e, f),
+        Stmt::MemberAssign{ object, expr, .. } => { visit_expr(object, f); visit_expr(expr, f); }
+        Stmt::Retry(_) | Stmt::Rethrow(_) | Stmt::Break{ .. } | Stmt::Continue{ .. } => {}
+        Stmt::Try{ body, catches, finally, retry, .. } => {
+            for e in retry.attempts.iter().chain(&retry.backoff) { visit_expr(e, f); }
+            visit_body(body, f);
+            for c in catches { visit_body(&c.body, f); }
+            if let Some(fin) = finally { visit_body(fin, f); }
+        }
+        Stmt::While{ cond, body, .. } | Stmt::DoWhile{ body, cond, .. } => { visit_expr(cond, f); visit_body(body, f); }
+        Stmt::ForC{ init, cond, post, body, .. } => {
+            if let Some(init) = init { visit_stmt(init, f); }
+            if let Some(cond) = cond { visit_expr(cond, f); }
+            if let Some(post) = post { visit_stmt(post, f); }
+            visit_body(body, f);
+        }
+        Stmt::ForEach{ collection, body, .. } => { visit_expr(collection, f); visit_body(body, f); }
+        Stmt::If{ cond, then_body, else_body, .. } => { visit_expr(cond, f); visit_body(then_body, f); visit_body(else_body, f); }
+        Stmt::Match{ expr, arms, default, .. } => {
+            visit_expr(expr, f);
+            for arm in arms { visit_body(&arm.body, f); }
+            if let Some(d) = default { visit_body(d, f); }
+        }
+    }
+}
+
+// Only lambdas contain statements, but they can sit anywhere inside an expression.
+fn visit_expr<'a>(e: &'a Expr, f: &mut dyn FnMut(&'a [Stmt])) {
+    match e {
+        Expr::Int(..) | Expr::Flt(..) | Expr::Str(..) | Expr::Bool(..) | Expr::Var(..) => {}
+        Expr::ArrayLit(items, _) | Expr::New(_, items, _) | Expr::Call{ args: items, .. } => for x in items { visit_expr(x, f) },
+        Expr::ObjLit(fields, _) => for (_, x) in fields { visit_expr(x, f) },
+        Expr::Lambda{ body, .. } => visit_body(body, f),
+        Expr::Member{ object: x, .. } | Expr::UnaryNot{ expr: x, .. } | Expr::UnaryNeg{ expr: x, .. } | Expr::UnaryBitNot{ expr: x, .. } => visit_expr(x, f),
+        Expr::Index{ array: a, index: b, .. } | Expr::Binary{ left: a, right: b, .. } => { visit_expr(a, f); visit_expr(b, f); }
+        Expr::Cond{ cond, then_expr, else_expr, .. } => { visit_expr(cond, f); visit_expr(then_expr, f); visit_expr(else_expr, f); }
+        Expr::MethodCall{ object: callee, args, .. } | Expr::CallCallee{ callee, args, .. } => {
+            visit_expr(callee, f);
+            for a in args { visit_expr(a, f); }
+        }
+    }
+}
+
+// Reports the first statement after one that always leaves the block.
+fn unreachable(body: &[Stmt], out: &mut Vec<(Lint, Warning)>) {
+    let Some(i) = body.iter().position(diverges) else { return };
+    let Some(next) = body.get(i + 1) else { return };
+    let w = Warning::new(Lint::UnreachableCode.name(), "unreachable statement", next.span())
+        .with_label(body[i].span(), "any code following this statement is unreachable");
+    out.push((Lint::UnreachableCode, w));
+}
+
+fn diverges(s: &Stmt) -> bool {
+    match s {
+        Stmt::Return{ .. } | Stmt::Throw{ .. } | Stmt::Rethrow(_) | Stmt::Retry(_) | Stmt::Break{ .. } | Stmt::Continue{ .. } => true,
+        Stmt::If{ then_body, else_body, .. } => !else_body.is_empty() && then_body.iter().any(diverges) && else_body.iter().any(diverges),
+        Stmt::Match{ arms, default: Some(d), .. } => d.iter().any(diverges) && arms.iter().all(|a| a.body.iter().any(diverges)),
+        Stmt::Try{ body, catches, finally, .. } => {
+            finally.as_ref().is_some_and(|f| f.iter().any(diverges))
+                || (body.iter().any(diverges) && catches.iter().all(|c| c.body.iter().any(diverges)))
+        }
+        _ => false,
+    }
+}
+
+fn constant_conditions(body: &[Stmt], out: &mut Vec<(Lint, Warning)>) {
+    for s in body {
+        let Stmt::If{ cond, else_body, .. } = s else { continue };
+        let Some(value) = const_bool(cond) else { continue };
+        let mut w = Warning::new(Lint::ConstantCondition.name(), &format!("this condition is always {}", value), cond.span());
+        if !value { w = w.with_note("the body of this `if` never runs"); }
+        else if !else_body.is_empty() { w = w.with_note("the `else` branch never runs"); }
+        out.push((Lint::ConstantCondition, w));
+    }
+}
+
+// Value of a condition built only from literals.
+fn const_bool(e: &Expr) -> Option<bool> {
+    match e {
+        Expr::Bool(b, _) => Some(*b),
+        Expr::UnaryNot{ expr, .. } => const_bool(expr).map(|b| !b),
+        Expr::Binary{ op: BinOp::And, left, right, .. } => Some(const_bool(left)? && const_bool(right)?),
+        Expr::Binary{ op: BinOp::Or, left, right, .. } => Some(const_bool(left)? || const_bool(right)?),
+        Expr::Binary{ op, left, right, .. } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Int(a, _), Expr::Int(b, _)) => compare(*op, a, b),
+            (Expr::Str(a, _), Expr::Str(b, _)) if !a.contains("!{") && !b.contains("!{") => compare(*op, a, b),
+            (Expr::Bool(a, _), Expr::Bool(b, _)) => compare(*op, a, b),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn compare<T: PartialOrd>(op: BinOp, a: &T, b: &T) -> Option<bool> {
+    match op {
+        BinOp::Eq => Some(a == b),
+        BinOp::Ne => Some(a != b),
+        BinOp::Lt => Some(a < b),
+        BinOp::Le => Some(a <= b),
+        BinOp::Gt => Some(a > b),
+        BinOp::Ge => Some(a >= b),
+        _ => None,
+    }
+}
@@ -2,11 +2,18 @@
 
 use std::env;
 use axity::run_file;
-use axity::{run_source_with_runtime};
+use axity::{run_file_with_runtime, run_source_with_runtime, OutputSink};
+use axity::runtime::{Limits, RuntimeConfig};
+
+const STACK_SIZE: usize = 512 * 1024 * 1024;
+const MAX_CALL_DEPTH: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ErrorFormat { Human, Json }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 { eprintln!("usage: axity [--dump-tokens] [--dump-ast] <file.ax> | init <ProjectName>"); std::process::exit(1); }
+    if args.len() < 2 { eprintln!("usage: axity [--dump-tokens] [--dump-ast] [--error-format=human|json] <file.ax> | init <ProjectName> | explain <code> | lint [--allow|--warn|--deny=<lint>] <file.ax>"); std::process::exit(1); }
     if args.len() >= 3 && args[1] == "init" {
         let name = args[2].clone();
         let base = std::path::PathBuf::from(&name);
@@ -35,15 +42,25 @@ fn main() {
         println!("  - .axity");
         return;
     }
+    if args.len() >= 3 && args[1] == "explain" {
+        match axity::error::codes::explain(&args[2]) {
+            Some(c) => print!("{}", c.render()),
+            None => { eprintln!("unknown error code '{}'", args[2]); std::process::exit(1); }
+        }
+        return;
+    }
+    if args[1] == "lint" { lint(&args[2..]); return; }
     let mut dump_tokens = false;
     let mut dump_ast = false;
+    let mut format = ErrorFormat::Human;
     let mut file = None;
     for a in &args[1..] {
         if a == "--dump-tokens" { dump_tokens = true; }
         else if a == "--dump-ast" { dump_ast = true; }
+        else if let Some(f) = a.strip_prefix("--error-format=") { format = error_format(f); }
         else { file = Some(a.clone()); }
     }
-    let file = match file { Some(f) => f, None => { eprintln!("usage: axity [--dump-tokens] [--dump-ast] <file.ax>"); std::process::exit(1) } };
+    let file = match file { Some(f) => f, None => { eprintln!("usage: axity [--dump-tokens] [--dump-ast] [--error-format=human|json] <file.ax>"); std::process::exit(1) } };
     if dump_tokens || dump_ast {
         let src = std::fs::read_to_string(&file).unwrap_or_default();
         let toks = axity::lexer::lex(&src).unwrap_or_default();
@@ -57,7 +74,7 @@ fn main() {
     }
     if file == "repl" || file == "--repl" {
         use std::io::{self, Write};
-        let mut rt = axity::runtime::Runtime::new();
+        let mut rt = axity::runtime::Runtime::with_output(OutputSink::Stdout);
         loop {
             print!("axity> ");
             io::stdout().flush().ok();
@@ -71,7 +88,7 @@ fn main() {
                     let path = &line[6..].trim();
                     match run_file(path) {
                         Ok(out) => print!("{}", out),
-                        Err(e) => eprintln!("{}", e),
+                        Err(e) => report(&e, format),
                     }
                 } else if line == ":env" {
                     print!("{}", rt.fmt_env());
@@ -82,13 +99,95 @@ fn main() {
             }
             match run_source_with_runtime(line, &mut rt) {
                 Ok(out) => print!("{}", out),
-                Err(e) => eprintln!("{}", e),
+                Err(e) => report(&e, format),
             }
         }
         return;
     }
-    match run_file(&file) {
+    // Deep recursion is reported as a call-depth error instead of overflowing the native stack.
+    let worker = std::thread::Builder::new().stack_size(STACK_SIZE).spawn(move || {
+        let limits = Limits { max_call_depth: Some(MAX_CALL_DEPTH), ..Limits::default() };
+        let mut rt = axity::runtime::Runtime::with_config(RuntimeConfig::default().with_limits(limits));
+        rt.set_output(OutputSink::Stdout);
+        run_file_with_runtime(&file, &mut rt)
+    });
+    let res = match worker.map(|h| h.join()) {
+        Ok(Ok(res)) => res,
+        _ => { eprintln!("runtime error: interpreter thread failed"); std::process::exit(1); }
+    };
+    match res {
         Ok(out) => print!("{}", out),
-        Err(e) => { eprintln!("{}", e); std::process::exit(1); }
+        Err(e) => { report(&e, format); std::process::exit(1); }
+    }
+}
+
+fn error_format(f: &str) -> ErrorFormat {
+    match f {
+        "human" => ErrorFormat::Human,
+        "json" => ErrorFormat::Json,
+        _ => { eprintln!("unknown error format '{}' (expected human or json)", f); std::process::exit(1) }
+    }
+}
+
+// `axity lint`: levels come from the nearest `axity.toml` `[lints]` table, then the command line.
+// Exits 1 on compile errors or when a denied lint fires.
+fn lint(args: &[String]) {
+    use axity::lint::{Lint, LintConfig, LintLevel};
+    let mut format = ErrorFormat::Human;
+    let mut file = None;
+    let mut flags = Vec::new();
+    for a in args {
+        if a == "--list" {
+            for l in Lint::ALL { println!("{:<20} {}", l.name(), l.description()); }
+            return;
+        }
+        else if let Some(f) = a.strip_prefix("--error-format=") { format = error_format(f); }
+        else if let Some(n) = a.strip_prefix("--allow=") { flags.push((n, LintLevel::Allow)); }
+        else if let Some(n) = a.strip_prefix("--warn=") { flags.push((n, LintLevel::Warn)); }
+        else if let Some(n) = a.strip_prefix("--deny=") { flags.push((n, LintLevel::Deny)); }
+        else { file = Some(a.clone()); }
+    }
+    let Some(file) = file else { eprintln!("usage: axity lint [--list] [--allow=<lint>] [--warn=<lint>] [--deny=<lint>] [--error-format=human|json] <file.ax>"); std::process::exit(1) };
+    let dir = std::fs::canonicalize(&file).ok().and_then(|p| p.parent().map(|d| d.to_path_buf()));
+    let manifest = dir.iter().flat_map(|d| d.ancestors()).map(|d| d.join("axity.toml")).find(|p| p.is_file());
+    let mut config = match manifest {
+        Some(path) => match LintConfig::from_toml(&std::fs::read_to_string(&path).unwrap_or_default()) {
+            Ok(c) => c,
+            Err(e) => { eprintln!("{}: {}", path.display(), e); std::process::exit(1) }
+        },
+        None => LintConfig::new(),
+    };
+    for (name, level) in flags {
+        if let Err(e) = config.set_by_name(name, level) { eprintln!("{}", e); std::process::exit(1); }
+    }
+    let rt = axity::runtime::Runtime::new();
+    let prog = match axity::compile_file(&file, &rt) {
+        Ok(p) => p,
+        Err(e) => { report(&e, format); std::process::exit(1) }
+    };
+    let warnings = prog.lint(&config);
+    let denied = warnings.iter().filter(|w| w.denied).count();
+    if format == ErrorFormat::Json {
+        for w in &warnings { eprintln!("{}", w.to_json()); }
+    } else {
+        use std::io::IsTerminal;
+        let color = std::io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+        for w in &warnings { eprintln!("{}", w.render(color)); }
+        let warned = warnings.len() - denied;
+        if warned > 0 { eprintln!("{} warning{} emitted", warned, if warned == 1 { "" } else { "s" }); }
+        if denied > 0 { eprintln!("found {} denied lint{}", denied, if denied == 1 { "" } else { "s" }); }
+    }
+    if denied > 0 { std::process::exit(1); }
+}
+
+// JSON prints one object per error; human output uses colour only when stderr is a terminal and NO_COLOR is unset.
+fn report(e: &axity::AxityError, format: ErrorFormat) {
+    use std::io::IsTerminal;
+    if format == ErrorFormat::Json { eprintln!("{}", e.render_json()); return; }
+    let color = std::io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+    for err in e.all() {
+        eprintln!("{}", err.render(color));
+        if !err.details.trace.is_empty() { eprintln!("{}", err.backtrace()); }
     }
+    if !e.details.others.is_empty() { eprintln!("found {} errors", e.details.others.len() + 1); }
 }
@@ -1,88 +1,154 @@
 use crate::ast::*;
-use crate::error::AxityError;
+use crate::error::{AxityError, Span};
 use crate::token::{Token, TokenKind};
 use crate::types::Type;
 
+// Parses the whole file, reporting every syntax error: the first is returned with the rest in `others`.
 pub fn parse(tokens: &[Token]) -> Result<Program, AxityError> {
-    let mut p = Parser { tokens, i: 0 };
-    p.program()
+    let mut p = Parser { tokens, i: 0, errors: Vec::new() };
+    let prog = p.program();
+    let mut errs = p.errors.into_iter();
+    match errs.next() {
+        None => Ok(prog),
+        Some(first) => Err(first.with_others(errs.collect())),
+    }
 }
 
 struct Parser<'a> {
     tokens: &'a [Token],
     i: usize,
+    errors: Vec<AxityError>,
 }
 
 impl<'a> Parser<'a> {
-    fn peek(&self) -> &'a Token { &self.tokens[self.i] }
-    fn next(&mut self) -> &'a Token { let t = &self.tokens[self.i]; self.i += 1; t }
+    // Reading past the end keeps returning the trailing `Eof`.
+    fn peek(&self) -> &'a Token { &self.tokens[self.i.min(self.tokens.len() - 1)] }
+    fn next(&mut self) -> &'a Token { let t = self.peek(); self.i += 1; t }
+    // Only consumes the token when it matches, so recovery can resynchronise on it.
     fn expect(&mut self, kind: TokenKind) -> Result<Token, AxityError> {
-        let t = self.next().clone();
-        if t.kind == kind { Ok(t) } else { Err(AxityError::parse("unexpected token", t.span)) }
+        if self.peek().kind == kind { Ok(self.next().clone()) } else { Err(self.unexpected(&kind.to_string())) }
+    }
+    fn expect_after(&mut self, kind: TokenKind, what: &str) -> Result<Token, AxityError> {
+        if self.peek().kind == kind { Ok(self.next().clone()) } else { Err(self.unexpected(&format!("{} after {}", kind, what))) }
+    }
+    fn unexpected(&self, expected: &str) -> AxityError {
+        let t = self.peek();
+        AxityError::parse(&format!("expected {}, found {}", expected, t.kind), t.span.clone()).with_code("AX0201")
     }
-    fn program(&mut self) -> Result<Program, AxityError> {
+    fn ident(&mut self, what: &str) -> Result<String, AxityError> {
+        match self.peek().kind.clone() {
+            TokenKind::Ident(s) => { self.next(); Ok(s) }
+            _ => Err(self.unexpected(what)),
+        }
+    }
+    fn program(&mut self) -> Program {
         let mut items = Vec::new();
         while self.peek().kind != TokenKind::Eof {
-            if self.peek().kind == TokenKind::Fn { items.push(Item::Func(self.function()?)); }
-            else if self.peek().kind == TokenKind::Import {
-                let it = self.import_item()?;
-                items.push(it);
+            let start = self.i;
+            let item = match self.peek().kind {
+                TokenKind::Fn => self.function().map(Item::Func),
+                TokenKind::Import => self.import_item(),
+                TokenKind::Class => self.class_def().map(Item::Class),
+                _ => self.statement().map(Item::Stmt),
+            };
+            match item { Ok(it) => items.push(it), Err(e) => self.recover(e, start) }
+        }
+        Program { items }
+    }
+    // Statements up to the `}` closing the current block; broken statements are reported and skipped.
+    fn statements(&mut self) -> Vec<Stmt> {
+        let mut body = Vec::new();
+        while !matches!(self.peek().kind, TokenKind::RBrace | TokenKind::Eof) && !self.at_item() {
+            let start = self.i;
+            match self.statement() { Ok(st) => body.push(st), Err(e) => self.recover(e, start) }
+        }
+        body
+    }
+    fn at_item(&self) -> bool {
+        match self.peek().kind {
+            TokenKind::Class | TokenKind::Import => true,
+            TokenKind::Fn => matches!(self.tokens.get(self.i + 1).map(|t| &t.kind), Some(TokenKind::Ident(_))),
+            _ => false,
+        }
+    }
+    // Panic mode: records the error and skips past the rest of the broken statement, stopping after
+    // a `;`, before a `}` that closes an enclosing block, or before the next statement keyword or item.
+    fn recover(&mut self, err: AxityError, start: usize) {
+        self.errors.push(err);
+        if self.i == start { self.next(); }
+        let mut depth = 0usize;
+        loop {
+            match self.peek().kind {
+                TokenKind::Eof => return,
+                TokenKind::Semicolon if depth == 0 => { self.next(); return; }
+                TokenKind::RBrace if depth == 0 => return,
+                TokenKind::RBrace => {
+                    depth -= 1;
+                    self.next();
+                    if depth == 0 { return; }
+                    continue;
+                }
+                TokenKind::LBrace => depth += 1,
+                TokenKind::Let | TokenKind::Print | TokenKind::Return | TokenKind::If | TokenKind::While | TokenKind::For
+                | TokenKind::Do | TokenKind::Try | TokenKind::Throw | TokenKind::Match | TokenKind::Break | TokenKind::Continue if depth == 0 => return,
+                _ if depth == 0 && self.at_item() => return,
+                _ => {}
             }
-            else if self.peek().kind == TokenKind::Class { items.push(Item::Class(self.class_def()?)); }
-            else { items.push(Item::Stmt(self.statement()?)); }
+            self.next();
         }
-        Ok(Program { items })
     }
     fn import_item(&mut self) -> Result<Item, AxityError> {
         let tok = self.expect(TokenKind::Import)?;
-        let path = match self.next().kind.clone() {
-            TokenKind::StringLit(s) => s,
-            _ => return Err(AxityError::parse("expected string path", tok.span))
+        let path = match self.peek().kind.clone() {
+            TokenKind::StringLit(s) => { self.next(); s }
+            _ => return Err(self.unexpected("module path string"))
         };
         if self.peek().kind == TokenKind::Semicolon { self.next(); }
         Ok(Item::Import(path, tok.span))
     }
     fn class_def(&mut self) -> Result<ClassDef, AxityError> {
         let ct = self.expect(TokenKind::Class)?;
-        let name = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected class name", ct.span)) };
+        let name = self.ident("class name")?;
         self.expect(TokenKind::LBrace)?;
         let mut fields = Vec::new();
         let mut methods = Vec::new();
         loop {
-            match self.peek().kind.clone() {
-                TokenKind::Let => {
-                    let sp = self.next().span.clone();
-                    let fname = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected field name", sp)) };
-                    self.expect(TokenKind::Colon)?;
-                    let ty = self.parse_type()?;
-                    self.expect(TokenKind::Semicolon)?;
-                    fields.push(Field{ name: fname, ty, span: sp });
-                }
-                TokenKind::Fn => {
-                    methods.push(self.function()?);
-                }
-                TokenKind::RBrace => { break; }
-                _ => return Err(AxityError::parse("unexpected token in class", self.peek().span.clone()))
-            }
+            let start = self.i;
+            let member = match self.peek().kind.clone() {
+                TokenKind::Let => self.field().map(|f| fields.push(f)),
+                TokenKind::Fn => self.function().map(|m| methods.push(m)),
+                TokenKind::RBrace | TokenKind::Eof => { break; }
+                _ => Err(self.unexpected("field or method"))
+            };
+            if let Err(e) = member { self.recover(e, start); }
         }
         self.expect(TokenKind::RBrace)?;
         Ok(ClassDef{ name, fields, methods, span: ct.span })
     }
+    fn field(&mut self) -> Result<Field, AxityError> {
+        let sp = self.expect(TokenKind::Let)?.span;
+        let name = self.ident("field name")?;
+        self.expect(TokenKind::Colon)?;
+        let ty = self.parse_type()?;
+        self.expect_after(TokenKind::Semicolon, "field declaration")?;
+        Ok(Field{ name, ty, span: sp })
+    }
     fn function(&mut self) -> Result<Function, AxityError> {
         let fn_tok = self.expect(TokenKind::Fn)?;
-        let name = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected identifier", fn_tok.span)) };
+        let name = self.ident("function name")?;
         self.expect(TokenKind::LParen)?;
         let mut params = Vec::new();
         if self.peek().kind != TokenKind::RParen {
             loop {
-                let pname = match self.next().kind.clone() {
-                    TokenKind::Ident(s) => s,
-                    TokenKind::SelfKw => "self".to_string(),
-                    _ => return Err(AxityError::parse("expected identifier", self.peek().span.clone()))
+                let psp = self.peek().span.clone();
+                let pname = match self.peek().kind.clone() {
+                    TokenKind::Ident(s) => { self.next(); s }
+                    TokenKind::SelfKw => { self.next(); "self".to_string() }
+                    _ => return Err(self.unexpected("parameter name"))
                 };
                 self.expect(TokenKind::Colon)?;
                 let ty = self.parse_type()?;
-                params.push(Param{ name: pname, ty, span: fn_tok.span.clone() });
+                params.push(Param{ name: pname, ty, span: psp });
                 if self.peek().kind == TokenKind::Comma { self.next(); } else { break; }
             }
         }
@@ -90,8 +156,7 @@ impl<'a> Parser<'a> {
         self.expect(TokenKind::Arrow)?;
         let ret = self.parse_type()?;
         self.expect(TokenKind::LBrace)?;
-        let mut body = Vec::new();
-        while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+        let body = self.statements();
         self.expect(TokenKind::RBrace)?;
         Ok(Function { name, params, ret, body, span: fn_tok.span })
     }
@@ -118,59 +183,122 @@ impl<'a> Parser<'a> {
                 Ok(Type::Map(Box::new(inner)))
             }
             TokenKind::Ident(ref s) => Ok(Type::Class(s.clone())),
-            _ => Err(AxityError::parse("unknown type", t.span))
+            _ => { self.i -= 1; Err(self.unexpected("type")) }
         }
     }
+    // `(attempts: n, backoff: ms)` after `try`; either option may be left out.
+    fn retry_policy(&mut self) -> Result<RetryPolicy, AxityError> {
+        self.expect(TokenKind::LParen)?;
+        let mut policy = RetryPolicy::default();
+        while self.peek().kind != TokenKind::RParen {
+            let t = self.peek();
+            let slot = match &t.kind {
+                TokenKind::Ident(s) if s == "attempts" => &mut policy.attempts,
+                TokenKind::Ident(s) if s == "backoff" => &mut policy.backoff,
+                _ => return Err(self.unexpected("`attempts` or `backoff`")),
+            };
+            if slot.is_some() { return Err(AxityError::parse("duplicate try option", t.span.clone()).with_code("AX0200")); }
+            self.next();
+            self.expect(TokenKind::Colon)?;
+            *slot = Some(self.expr()?);
+            if self.peek().kind != TokenKind::Comma { break; }
+            self.next();
+        }
+        self.expect(TokenKind::RParen)?;
+        Ok(policy)
+    }
+    // `catch (e: T) { }` matches by type; `catch e { }` and a bare `catch { }` catch anything.
+    fn catch_clause(&mut self) -> Result<CatchClause, AxityError> {
+        let sp = self.next().span.clone();
+        let (name, ty) = match self.peek().kind.clone() {
+            TokenKind::LParen => {
+                self.next();
+                let name = self.ident("catch variable name")?;
+                self.expect(TokenKind::Colon)?;
+                let ty = self.parse_type()?;
+                self.expect(TokenKind::RParen)?;
+                (name, ty)
+            }
+            TokenKind::Ident(s) => { self.next(); (s, Type::Any) }
+            _ => ("error".to_string(), Type::Any),
+        };
+        self.expect(TokenKind::LBrace)?;
+        let body = self.statements();
+        self.expect(TokenKind::RBrace)?;
+        Ok(CatchClause{ name, ty, body, span: sp })
+    }
     fn statement(&mut self) -> Result<Stmt, AxityError> {
         match self.peek().kind.clone() {
+            // `outer: for ...`: a label names a loop for `break outer;` and `continue outer;`
+            TokenKind::Ident(name) if self.tokens.get(self.i + 1).map(|t| &t.kind) == Some(&TokenKind::Colon)
+                && matches!(self.tokens.get(self.i + 2).map(|t| &t.kind), Some(TokenKind::While | TokenKind::Do | TokenKind::For)) => {
+                self.next();
+                self.next();
+                let mut st = self.statement()?;
+                if let Stmt::While{ label, .. } | Stmt::DoWhile{ label, .. } | Stmt::ForC{ label, .. } | Stmt::ForEach{ label, .. } = &mut st {
+                    *label = Some(name);
+                }
+                Ok(st)
+            }
             TokenKind::Let => {
                 let lt = self.next().span.clone();
-                let name = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected identifier", lt)) };
+                let name = self.ident("variable name")?;
                 self.expect(TokenKind::Colon)?;
                 let ty = self.parse_type()?;
                 self.expect(TokenKind::Assign)?;
                 let init = self.expr()?;
-                self.expect(TokenKind::Semicolon)?;
+                self.expect_after(TokenKind::Semicolon, "variable declaration")?;
                 Ok(Stmt::Let{ name, ty, init, span: lt })
             }
             TokenKind::Throw => {
                 let sp = self.next().span.clone();
+                if self.peek().kind == TokenKind::Semicolon { self.next(); return Ok(Stmt::Rethrow(sp)); }
                 let e = self.expr()?;
                 if self.peek().kind == TokenKind::Semicolon { self.next(); }
                 Ok(Stmt::Throw{ expr: e, span: sp })
             }
             TokenKind::Try => {
                 let sp = self.next().span.clone();
+                let retry = if self.peek().kind == TokenKind::LParen { self.retry_policy()? } else { RetryPolicy::default() };
                 self.expect(TokenKind::LBrace)?;
-                let mut body = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
-                self.expect(TokenKind::RBrace)?;
-                self.expect(TokenKind::Catch)?;
-                let cname = match self.peek().kind.clone() {
-                    TokenKind::Ident(s) => { self.next(); s }
-                    _ => "error".to_string()
-                };
-                self.expect(TokenKind::LBrace)?;
-                let mut cbody = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { cbody.push(self.statement()?); }
+                let body = self.statements();
                 self.expect(TokenKind::RBrace)?;
-                Ok(Stmt::Try{ body, catch_name: cname, catch_body: cbody, span: sp })
+                let mut catches = Vec::new();
+                while self.peek().kind == TokenKind::Catch {
+                    catches.push(self.catch_clause()?);
+                }
+                let mut finally = None;
+                if self.peek().kind == TokenKind::Finally {
+                    self.next();
+                    self.expect(TokenKind::LBrace)?;
+                    finally = Some(self.statements());
+                    self.expect(TokenKind::RBrace)?;
+                }
+                if catches.is_empty() && finally.is_none() { return Err(self.unexpected("catch or finally after try block")); }
+                Ok(Stmt::Try{ body, catches, finally, retry, span: sp })
             }
             TokenKind::Retry => {
                 let sp = self.next().span.clone();
                 if self.peek().kind == TokenKind::Semicolon { self.next(); }
                 Ok(Stmt::Retry(sp))
             }
+            TokenKind::Break | TokenKind::Continue => {
+                let tok = self.next();
+                let sp = tok.span.clone();
+                let is_break = tok.kind == TokenKind::Break;
+                let label = if let TokenKind::Ident(name) = self.peek().kind.clone() { self.next(); Some(name) } else { None };
+                if self.peek().kind == TokenKind::Semicolon { self.next(); }
+                Ok(if is_break { Stmt::Break{ label, span: sp } } else { Stmt::Continue{ label, span: sp } })
+            }
             TokenKind::Do => {
                 let sp = self.next().span.clone();
                 self.expect(TokenKind::LBrace)?;
-                let mut body = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                let body = self.statements();
                 self.expect(TokenKind::RBrace)?;
                 self.expect(TokenKind::While)?;
                 let cond = self.expr()?;
                 if self.peek().kind == TokenKind::Semicolon { self.next(); }
-                Ok(Stmt::DoWhile{ body, cond, span: sp })
+                Ok(Stmt::DoWhile{ label: None, body, cond, span: sp })
             }
             TokenKind::For => {
                 let sp = self.next().span.clone();
@@ -182,10 +310,9 @@ impl<'a> Parser<'a> {
                         self.next();
                         let coll = self.expr()?;
                         self.expect(TokenKind::LBrace)?;
-                        let mut body = Vec::new();
-                        while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                        let body = self.statements();
                         self.expect(TokenKind::RBrace)?;
-                        return Ok(Stmt::ForEach{ var, collection: coll, body, span: sp });
+                        return Ok(Stmt::ForEach{ label: None, var, collection: coll, body, span: sp });
                     } else {
                         self.i = save_i;
                     }
@@ -198,21 +325,21 @@ impl<'a> Parser<'a> {
                     } else if self.peek().kind == TokenKind::Let {
                         // parse let ... ;
                         let lt = self.next().span.clone();
-                        let name = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected identifier", lt)) };
+                        let name = self.ident("variable name")?;
                         self.expect(TokenKind::Colon)?;
                         let ty = self.parse_type()?;
                         self.expect(TokenKind::Assign)?;
                         let init_e = self.expr()?;
-                        self.expect(TokenKind::Semicolon)?;
+                        self.expect_after(TokenKind::Semicolon, "loop initializer")?;
                         Some(Box::new(Stmt::Let{ name, ty, init: init_e, span: lt }))
                     } else {
                         // parse assignment or expr statement up to semicolon
                         let e = self.expr()?;
-                        self.expect(TokenKind::Semicolon)?;
+                        self.expect_after(TokenKind::Semicolon, "loop initializer")?;
                         Some(Box::new(Stmt::Expr(e)))
                     }
                 };
-                let cond = if self.peek().kind == TokenKind::Semicolon { self.next(); None } else { let c = self.expr()?; self.expect(TokenKind::Semicolon)?; Some(c) };
+                let cond = if self.peek().kind == TokenKind::Semicolon { self.next(); None } else { let c = self.expr()?; self.expect_after(TokenKind::Semicolon, "loop condition")?; Some(c) };
                 let post = if self.peek().kind == TokenKind::LBrace { None } else {
                     let save_i2 = self.i;
                     if let TokenKind::Ident(name) = self.peek().kind.clone() {
@@ -236,10 +363,9 @@ impl<'a> Parser<'a> {
                     }
                 };
                 self.expect(TokenKind::LBrace)?;
-                let mut body = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                let body = self.statements();
                 self.expect(TokenKind::RBrace)?;
-                Ok(Stmt::ForC{ init, cond, post, body, span: sp })
+                Ok(Stmt::ForC{ label: None, init, cond, post, body, span: sp })
             }
             TokenKind::Match => {
                 let sp = self.next().span.clone();
@@ -255,24 +381,22 @@ impl<'a> Parser<'a> {
                             TokenKind::StringLit(ref s) => { let t = self.next().clone(); Pattern::PStr(s.clone()) }
                             TokenKind::TrueKw => { self.next(); Pattern::PBool(true) }
                             TokenKind::FalseKw => { self.next(); Pattern::PBool(false) }
-                            _ => return Err(AxityError::parse("invalid pattern", self.peek().span.clone()))
+                            _ => return Err(self.unexpected("int, string or bool pattern"))
                         };
                         self.expect(TokenKind::Colon)?;
                         self.expect(TokenKind::LBrace)?;
-                        let mut body = Vec::new();
-                        while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                        let body = self.statements();
                         self.expect(TokenKind::RBrace)?;
                         arms.push(MatchArm{ pat, body });
                     } else if self.peek().kind == TokenKind::Default {
                         self.next();
                         self.expect(TokenKind::Colon)?;
                         self.expect(TokenKind::LBrace)?;
-                        let mut body = Vec::new();
-                        while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                        let body = self.statements();
                         self.expect(TokenKind::RBrace)?;
                         default = Some(body);
                     } else {
-                        return Err(AxityError::parse("expected case/default", self.peek().span.clone()));
+                        return Err(self.unexpected("`case` or `default`"));
                     }
                 }
                 self.expect(TokenKind::RBrace)?;
@@ -283,38 +407,41 @@ impl<'a> Parser<'a> {
                 self.expect(TokenKind::LParen)?;
                 let e = self.expr()?;
                 self.expect(TokenKind::RParen)?;
-                self.expect(TokenKind::Semicolon)?;
+                self.expect_after(TokenKind::Semicolon, "print statement")?;
                 Ok(Stmt::Print{ expr: e, span: sp })
             }
             TokenKind::While => {
                 let sp = self.next().span.clone();
                 let cond = self.expr()?;
                 self.expect(TokenKind::LBrace)?;
-                let mut body = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                let body = self.statements();
                 self.expect(TokenKind::RBrace)?;
-                Ok(Stmt::While{ cond, body, span: sp })
+                Ok(Stmt::While{ label: None, cond, body, span: sp })
             }
             TokenKind::If => {
                 let sp = self.next().span.clone();
                 let cond = self.expr()?;
                 self.expect(TokenKind::LBrace)?;
-                let mut then_body = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { then_body.push(self.statement()?); }
+                let then_body = self.statements();
                 self.expect(TokenKind::RBrace)?;
                 let mut else_body = Vec::new();
                 if self.peek().kind == TokenKind::Else {
                     self.next();
-                    self.expect(TokenKind::LBrace)?;
-                    while self.peek().kind != TokenKind::RBrace { else_body.push(self.statement()?); }
-                    self.expect(TokenKind::RBrace)?;
+                    if self.peek().kind == TokenKind::If {
+                        // `else if` nests the next `if` as the whole else branch.
+                        else_body.push(self.statement()?);
+                    } else {
+                        self.expect(TokenKind::LBrace)?;
+                        else_body = self.statements();
+                        self.expect(TokenKind::RBrace)?;
+                    }
                 }
                 Ok(Stmt::If{ cond, then_body, else_body, span: sp })
             }
             TokenKind::Return => {
                 let sp = self.next().span.clone();
                 let e = self.expr()?;
-                self.expect(TokenKind::Semicolon)?;
+                self.expect_after(TokenKind::Semicolon, "return value")?;
                 Ok(Stmt::Return{ expr: e, span: sp })
             }
             TokenKind::Ident(_) | TokenKind::SelfKw => {
@@ -327,7 +454,7 @@ impl<'a> Parser<'a> {
                 while self.peek().kind == TokenKind::Dot || self.peek().kind == TokenKind::LBracket {
                     if self.peek().kind == TokenKind::Dot {
                         self.next();
-                        let fld = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected member name", self.peek().span.clone())) };
+                        let fld = self.ident("member name")?;
                         if self.peek().kind == TokenKind::LParen {
                             self.expect(TokenKind::LParen)?;
                             let mut args = Vec::new();
@@ -349,11 +476,11 @@ impl<'a> Parser<'a> {
                 if self.peek().kind == TokenKind::Assign {
                     let sp = self.next().span.clone();
                     let e = self.expr()?;
-                    self.expect(TokenKind::Semicolon)?;
+                    self.expect_after(TokenKind::Semicolon, "assignment")?;
                     match base {
                         Expr::Member{ object, field, .. } => Ok(Stmt::MemberAssign{ object: *object, field, expr: e, span: sp }),
                         Expr::Var(name, _) => Ok(Stmt::Assign{ name, expr: e, span: sp }),
-                        _ => Err(AxityError::parse("invalid assignment target", sp))
+                        _ => Err(AxityError::parse("invalid assignment target", sp).with_code("AX0202"))
                     }
                 } else if self.peek().kind == TokenKind::PlusPlus || self.peek().kind == TokenKind::MinusMinus {
                     let sp = self.next().span.clone();
@@ -363,11 +490,11 @@ impl<'a> Parser<'a> {
                     } else {
                         Expr::Binary{ op: BinOp::Sub, left: Box::new(base.clone()), right: Box::new(one), span: sp.clone() }
                     };
-                    self.expect(TokenKind::Semicolon)?;
+                    self.expect_after(TokenKind::Semicolon, "expression")?;
                     match base {
                         Expr::Member{ object, field, .. } => Ok(Stmt::MemberAssign{ object: *object, field, expr: expr, span: sp }),
                         Expr::Var(name, _) => Ok(Stmt::Assign{ name, expr: expr, span: sp }),
-                        _ => Err(AxityError::parse("invalid increment/decrement target", sp))
+                        _ => Err(AxityError::parse("invalid increment/decrement target", sp).with_code("AX0202"))
                     }
                 } else if self.peek().kind == TokenKind::LParen {
                     self.expect(TokenKind::LParen)?;
@@ -376,22 +503,51 @@ impl<'a> Parser<'a> {
                         loop { args.push(self.expr()?); if self.peek().kind == TokenKind::Comma { self.next(); } else { break; } }
                     }
                     self.expect(TokenKind::RParen)?;
-                    self.expect(TokenKind::Semicolon)?;
+                    self.expect_after(TokenKind::Semicolon, "expression")?;
+                    let span = start.span.clone();
                     let name = match start.kind {
                         TokenKind::Ident(name) => name,
                         TokenKind::SelfKw => "self".to_string(),
                         _ => unreachable!()
                     };
-                    Ok(Stmt::Expr(Expr::Call{ name, args, span: self.peek().span.clone() }))
+                    Ok(Stmt::Expr(Expr::Call{ name, args, span }))
                 } else {
-                    self.expect(TokenKind::Semicolon)?;
+                    self.expect_after(TokenKind::Semicolon, "expression")?;
                     Ok(Stmt::Expr(base))
                 }
             }
-            _ => Err(AxityError::parse("unexpected token in statement", self.peek().span.clone()))
+            _ => Err(self.unexpected("statement"))
         }
     }
-    fn expr(&mut self) -> Result<Expr, AxityError> { self.expr_or() }
+    fn expr(&mut self) -> Result<Expr, AxityError> { self.expr_cond() }
+    // `c ? a : b` binds loosest and nests to the right.
+    fn expr_cond(&mut self) -> Result<Expr, AxityError> {
+        let cond = self.expr_or()?;
+        if self.peek().kind != TokenKind::Question { return Ok(cond); }
+        let sp = self.next().span.clone();
+        let then_expr = self.expr()?;
+        self.expect(TokenKind::Colon)?;
+        let else_expr = self.expr_cond()?;
+        Ok(Expr::Cond{ cond: Box::new(cond), then_expr: Box::new(then_expr), else_expr: Box::new(else_expr), span: sp })
+    }
+    // The `if` keyword is already consumed; both branches are required and hold a single expression.
+    fn if_expr(&mut self, sp: Span) -> Result<Expr, AxityError> {
+        let cond = self.expr()?;
+        self.expect(TokenKind::LBrace)?;
+        let then_expr = self.expr()?;
+        self.expect(TokenKind::RBrace)?;
+        self.expect_after(TokenKind::Else, "if expression")?;
+        let else_expr = if self.peek().kind == TokenKind::If {
+            let sp = self.next().span.clone();
+            self.if_expr(sp)?
+        } else {
+            self.expect(TokenKind::LBrace)?;
+            let e = self.expr()?;
+            self.expect(TokenKind::RBrace)?;
+            e
+        };
+        Ok(Expr::Cond{ cond: Box::new(cond), then_expr: Box::new(then_expr), else_expr: Box::new(else_expr), span: sp })
+    }
     fn expr_or(&mut self) -> Result<Expr, AxityError> {
         let mut e = self.expr_and()?;
         loop {
@@ -521,13 +677,11 @@ impl<'a> Parser<'a> {
                 let mut params = Vec::new();
                 if self.peek().kind != TokenKind::RParen {
                     loop {
-                        let pname = match self.next().kind.clone() {
-                            TokenKind::Ident(s) => s,
-                            _ => return Err(AxityError::parse("expected identifier", self.peek().span.clone()))
-                        };
+                        let psp = self.peek().span.clone();
+                        let pname = self.ident("parameter name")?;
                         self.expect(TokenKind::Colon)?;
                         let ty = self.parse_type()?;
-                        params.push(Param{ name: pname, ty, span: t.span.clone() });
+                        params.push(Param{ name: pname, ty, span: psp });
                         if self.peek().kind == TokenKind::Comma { self.next(); } else { break; }
                     }
                 }
@@ -535,8 +689,7 @@ impl<'a> Parser<'a> {
                 self.expect(TokenKind::Arrow)?;
                 let ret = self.parse_type()?;
                 self.expect(TokenKind::LBrace)?;
-                let mut body = Vec::new();
-                while self.peek().kind != TokenKind::RBrace { body.push(self.statement()?); }
+                let body = self.statements();
                 self.expect(TokenKind::RBrace)?;
                 let lam = Expr::Lambda{ params, ret, body, span: t.span.clone() };
                 if self.peek().kind == TokenKind::LParen {
@@ -551,6 +704,7 @@ impl<'a> Parser<'a> {
                     Ok(lam)
                 }
             }
+            TokenKind::If => self.if_expr(t.span),
             TokenKind::IntLit(v) => Ok(Expr::Int(v, t.span)),
             TokenKind::FltLit(v) => Ok(Expr::Flt(v, t.span)),
             TokenKind::StringLit(ref s) => Ok(Expr::Str(s.clone(), t.span)),
@@ -561,7 +715,7 @@ impl<'a> Parser<'a> {
                         let key = match self.peek().kind.clone() {
                             TokenKind::StringLit(ref s) => { self.next(); s.clone() }
                             TokenKind::Ident(ref s) => { self.next(); s.clone() }
-                            _ => return Err(AxityError::parse("expected key in object", self.peek().span.clone()))
+                            _ => return Err(self.unexpected("object key"))
                         };
                         self.expect(TokenKind::Colon)?;
                         let val = self.expr()?;
@@ -573,7 +727,7 @@ impl<'a> Parser<'a> {
                 Ok(Expr::ObjLit(pairs, t.span))
             }
             TokenKind::New => {
-                let name = match self.next().kind.clone() { TokenKind::Ident(s) => s, _ => return Err(AxityError::parse("expected class name", t.span)) };
+                let name = self.ident("class name")?;
                 let mut args = Vec::new();
                 if self.peek().kind == TokenKind::LParen {
                     self.expect(TokenKind::LParen)?;
@@ -608,7 +762,7 @@ impl<'a> Parser<'a> {
                 while self.peek().kind == TokenKind::Dot || self.peek().kind == TokenKind::LBracket {
                     if self.peek().kind == TokenKind::Dot {
                         self.next();
-                        let fld = match self.next().kind.clone() { TokenKind::Ident(s2) => s2, _ => return Err(AxityError::parse("expected member name", self.peek().span.clone())) };
+                        let fld = self.ident("member name")?;
                         if self.peek().kind == TokenKind::LParen {
                             self.expect(TokenKind::LParen)?;
                             let mut args = Vec::new();
@@ -635,7 +789,7 @@ impl<'a> Parser<'a> {
                 let mut base = Expr::Var("self".to_string(), t.span.clone());
                 while self.peek().kind == TokenKind::Dot {
                     self.next();
-                    let fld = match self.next().kind.clone() { TokenKind::Ident(s2) => s2, _ => return Err(AxityError::parse("expected member name", self.peek().span.clone())) };
+                    let fld = self.ident("member name")?;
                     if self.peek().kind == TokenKind::LParen {
                         self.expect(TokenKind::LParen)?;
                         let mut args = Vec::new();
@@ -651,7 +805,7 @@ impl<'a> Parser<'a> {
                 Ok(base)
             }
             TokenKind::LParen => { let e = self.expr()?; self.expect(TokenKind::RParen)?; Ok(e) }
-            _ => Err(AxityError::parse("unexpected token in expression", t.span))
+            _ => { self.i -= 1; Err(self.unexpected("expression")) }
         }
     }
 }
@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+
+// Extra steps and time a `finally` block gets to clean up after a fatal error, once per run.
+const GRACE_STEPS: u64 = 10_000;
+const GRACE_TIME: Duration = Duration::from_millis(100);
+use crate::error::{AxityError, AxityErrorKind, Span};
+use crate::runtime::{CallFrame, Runtime};
+
+// Per-run counters checked against `RuntimeConfig::limits`.
+#[derive(Debug, Default)]
+pub struct Budget {
+    active: bool,
+    entered: usize,
+    steps: u64,
+    deadline: Option<Instant>,
+    over_memory: bool,
+    // GC size at which dropped containers are pruned again before counting live ones.
+    prune_at: usize,
+    // Step cap once the grace budget for `finally` has been granted.
+    grace_steps: Option<u64>,
+}
+
+impl Runtime {
+    // Resets counters at the outermost entry; nested entries (e.g. from a native callback) share the budget.
+    // An interrupt left over from before the run is dropped, so it cannot cancel a run it was not aimed at.
+    pub fn begin_run(&mut self) {
+        if self.budget.entered == 0 {
+            self.interrupt.clear();
+            let limits = &self.config.limits;
+            self.budget.active = !limits.is_unlimited();
+            self.budget.steps = 0;
+            self.budget.deadline = limits.timeout.map(|d| Instant::now() + d);
+            self.frames.clear();
+            self.throw_trace = None;
+            self.thrown = None;
+            self.caught.clear();
+            self.held.clear();
+            self.attempts.clear();
+            self.budget.over_memory = false;
+            self.budget.prune_at = 0;
+            self.budget.grace_steps = None;
+            self.scopes.truncate(1);
+        }
+        self.budget.entered += 1;
+    }
+    pub fn end_run(&mut self) { self.budget.entered = self.budget.entered.saturating_sub(1); }
+    #[inline]
+    pub fn tick(&mut self) -> Result<(), AxityError> {
+        if !self.budget.active { return Ok(()); }
+        self.check_budget()
+    }
+    // Polled at loop back-edges and calls, where cancelling leaves no half-evaluated expression behind.
+    #[inline]
+    pub fn back_edge(&mut self) -> Result<(), AxityError> {
+        if self.interrupt.take() { return Err(AxityError::new(AxityErrorKind::Interrupted("script cancelled by host".into()))); }
+        self.tick()
+    }
+    fn check_budget(&mut self) -> Result<(), AxityError> {
+        self.budget.steps += 1;
+        if let Some(max) = self.budget.grace_steps.or(self.config.limits.max_steps) {
+            if self.budget.steps > max { return Err(AxityError::new(AxityErrorKind::StepLimit(format!("more than {} steps", max)))); }
+        }
+        if self.budget.steps % 1024 == 0 {
+            if let Some(d) = self.budget.deadline {
+                if Instant::now() >= d { return Err(self.timed_out()); }
+            }
+        }
+        if self.budget.over_memory {
+            let max = self.config.limits.max_allocations.unwrap_or(0);
+            return Err(AxityError::new(AxityErrorKind::MemoryLimit(format!("more than {} live allocations", max))));
+        }
+        Ok(())
+    }
+    // Lets `finally` blocks run after a fatal error: the step, time and memory limits are lifted for
+    // `GRACE_STEPS` steps or `GRACE_TIME`, whichever ends first. Granted once per run, so cleanup that
+    // hits a limit again cannot extend it.
+    pub(crate) fn begin_grace(&mut self) {
+        if !self.budget.active || self.budget.grace_steps.is_some() { return; }
+        self.budget.grace_steps = Some(self.budget.steps + GRACE_STEPS);
+        if self.budget.deadline.is_some() { self.budget.deadline = Some(Instant::now() + GRACE_TIME); }
+        self.budget.over_memory = false;
+    }
+    fn timed_out(&self) -> AxityError {
+        let ms = self.config.limits.timeout.map(|t| t.as_millis()).unwrap_or(0);
+        AxityError::new(AxityErrorKind::Timeout(format!("exceeded {} ms", ms)))
+    }
+    // Sleeps between `try` attempts in short slices, so a timeout or an interrupt still ends the run on time.
+    pub(crate) fn pause(&mut self, ms: u64) -> Result<(), AxityError> {
+        let end = Instant::now() + Duration::from_millis(ms);
+        loop {
+            self.back_edge()?;
+            let now = Instant::now();
+            if let Some(d) = self.budget.deadline {
+                if now >= d { return Err(self.timed_out()); }
+            }
+            if now >= end { return Ok(()); }
+            std::thread::sleep((end - now).min(Duration::from_millis(10)));
+        }
+    }
+    pub fn enter_call(&mut self, name: &str, site: Option<&Span>) -> Result<(), AxityError> {
+        self.back_edge()?;
+        if let Some(max) = self.config.limits.max_call_depth {
+            if self.frames.len() >= max { return Err(AxityError::new(AxityErrorKind::CallDepth(format!("more than {} nested calls", max)))); }
+        }
+        self.frames.push(CallFrame { name: name.to_string(), site: site.cloned() });
+        Ok(())
+    }
+    pub fn exit_call(&mut self) { self.frames.pop(); }
+    pub(crate) fn note_alloc(&mut self) {
+        if let Some(max) = self.config.limits.max_allocations {
+            if self.gc.tracked() > self.budget.prune_at.max(max) {
+                let live = self.gc.live();
+                if live > max { self.budget.over_memory = true; }
+                // Pruning walks every tracked container, so the next one waits for another quarter of the cap
+                // to be allocated; the cap can be overshot by that much before it is reported.
+                self.budget.prune_at = live + max / 4;
+            }
+        }
+    }
+}
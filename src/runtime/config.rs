@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::runtime::fs::resolve;
+
+pub const READ_BUILTINS: &[&str] = &["read_file", "exists", "read_json", "read_toml", "read_env"];
+pub const WRITE_BUILTINS: &[&str] = &["write_file", "mkdir", "write_json", "write_toml", "write_env"];
+pub const STDIN_BUILTINS: &[&str] = &["input", "input_all", "input_eof"];
+
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub fs_read: bool,
+    pub fs_write: bool,
+    pub stdin: bool,
+    // When set, file builtins may only touch paths under one of these prefixes.
+    pub path_prefixes: Option<Vec<PathBuf>>,
+}
+
+impl Capabilities {
+    pub fn allow_all() -> Self { Self { fs_read: true, fs_write: true, stdin: true, path_prefixes: None } }
+    pub fn deny_all() -> Self { Self { fs_read: false, fs_write: false, stdin: false, path_prefixes: None } }
+    pub fn read_only() -> Self { Self { fs_write: false, ..Self::allow_all() } }
+    pub fn with_path_prefix<P: AsRef<Path>>(mut self, prefix: P) -> Self {
+        self.path_prefixes.get_or_insert_with(Vec::new).push(resolve(prefix.as_ref()));
+        self
+    }
+    pub fn without_stdin(mut self) -> Self { self.stdin = false; self }
+    // Builtins that can never succeed under these capabilities and are rejected by the type checker.
+    pub fn denied_builtins(&self) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        if !self.fs_read { out.extend_from_slice(READ_BUILTINS); }
+        if !self.fs_write { out.extend_from_slice(WRITE_BUILTINS); }
+        if !self.stdin { out.extend_from_slice(STDIN_BUILTINS); }
+        out
+    }
+    pub fn path_allowed(&self, path: &str) -> bool {
+        match &self.path_prefixes {
+            None => true,
+            Some(prefixes) => {
+                let p = resolve(Path::new(path));
+                prefixes.iter().any(|pre| p.starts_with(pre))
+            }
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self { Self::allow_all() }
+}
+
+// Default for `Limits::max_call_depth`; each script call takes several native frames, and 256 fits the
+// 8 MiB main-thread stack of a release build.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
+// Execution budgets; `None` means unlimited. Only the call depth is bounded by default.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    // Evaluated statements and expressions per run.
+    pub max_steps: Option<u64>,
+    // Wall-clock time per run.
+    pub timeout: Option<Duration>,
+    // Nested function, method and lambda calls.
+    pub max_call_depth: Option<usize>,
+    // Live arrays, maps, objects and buffers tracked by the GC.
+    pub max_allocations: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self { Self { max_steps: None, timeout: None, max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH), max_allocations: None } }
+}
+
+impl Limits {
+    // Whether the per-step checks can be skipped; the call depth is checked on every call regardless.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_steps.is_none() && self.timeout.is_none() && self.max_allocations.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeConfig {
+    pub caps: Capabilities,
+    pub limits: Limits,
+}
+
+impl RuntimeConfig {
+    pub fn sandboxed(caps: Capabilities) -> Self { Self { caps, ..Self::default() } }
+    pub fn with_limits(mut self, limits: Limits) -> Self { self.limits = limits; self }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use crate::error::AxityError;
+use crate::runtime::{Runtime, Value};
+
+const SCALE: f64 = 1_000_000.0;
+
+pub trait IntoValue: Sized {
+    fn into_value(self, rt: &mut Runtime) -> Result<Value, AxityError>;
+    // The value stored under a map key; `Ok(None)` leaves the key out.
+    fn into_field(self, rt: &mut Runtime) -> Result<Option<Value>, AxityError> { self.into_value(rt).map(Some) }
+}
+
+pub trait FromValue: Sized {
+    fn from_value(v: &Value) -> Result<Self, AxityError>;
+}
+
+impl IntoValue for Value {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(self) }
+}
+impl IntoValue for i64 {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Int(self)) }
+}
+impl IntoValue for i32 {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Int(self as i64)) }
+}
+impl IntoValue for u32 {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Int(self as i64)) }
+}
+impl IntoValue for usize {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> {
+        i64::try_from(self).map(Value::Int).map_err(|_| AxityError::rt("usize out of range for int").with_code("AX0405"))
+    }
+}
+impl IntoValue for f64 {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Flt((self * SCALE).round() as i64)) }
+}
+impl IntoValue for f32 {
+    fn into_value(self, rt: &mut Runtime) -> Result<Value, AxityError> { (self as f64).into_value(rt) }
+}
+impl IntoValue for bool {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Bool(self)) }
+}
+impl IntoValue for String {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Str(self)) }
+}
+impl IntoValue for &str {
+    fn into_value(self, _rt: &mut Runtime) -> Result<Value, AxityError> { Ok(Value::Str(self.to_string())) }
+}
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self, rt: &mut Runtime) -> Result<Value, AxityError> {
+        let items = self.into_iter().map(|x| x.into_value(rt)).collect::<Result<_, _>>()?;
+        Ok(rt.new_array(items))
+    }
+}
+impl<T: IntoValue> IntoValue for HashMap<String, T> {
+    fn into_value(self, rt: &mut Runtime) -> Result<Value, AxityError> {
+        let m = rt.new_map();
+        if let Value::Map(rc) = &m {
+            for (k, v) in self {
+                if let Some(v) = v.into_field(rt)? {
+                    rc.borrow_mut().insert(k, v);
+                }
+            }
+        }
+        Ok(m)
+    }
+}
+// Axity has no null: a `None` map entry is left out, and a `None` anywhere else is an error.
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self, rt: &mut Runtime) -> Result<Value, AxityError> {
+        match self {
+            Some(v) => v.into_value(rt),
+            None => Err(AxityError::rt("None has no script value outside a map entry").with_code("AX0405")),
+        }
+    }
+    fn into_field(self, rt: &mut Runtime) -> Result<Option<Value>, AxityError> {
+        match self { Some(v) => v.into_field(rt), None => Ok(None) }
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(v: &Value) -> Result<Self, AxityError> { Ok(v.clone()) }
+}
+impl FromValue for i64 {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        match v {
+            Value::Int(i) => Ok(*i),
+            _ => Err(AxityError::rt("expected int").with_code("AX0405")),
+        }
+    }
+}
+impl FromValue for i32 {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        let i = i64::from_value(v)?;
+        i32::try_from(i).map_err(|_| AxityError::rt("int out of range for i32").with_code("AX0405"))
+    }
+}
+impl FromValue for usize {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        let i = i64::from_value(v)?;
+        usize::try_from(i).map_err(|_| AxityError::rt("int out of range for usize").with_code("AX0405"))
+    }
+}
+impl FromValue for f64 {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        match v {
+            Value::Flt(f) => Ok(*f as f64 / SCALE),
+            _ => Err(AxityError::rt("expected flt").with_code("AX0405")),
+        }
+    }
+}
+impl FromValue for bool {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        match v {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(AxityError::rt("expected bool").with_code("AX0405")),
+        }
+    }
+}
+impl FromValue for String {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        match v {
+            Value::Str(s) => Ok(s.clone()),
+            _ => Err(AxityError::rt("expected string").with_code("AX0405")),
+        }
+    }
+}
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        match v {
+            Value::Array(rc) => rc.borrow().iter().map(T::from_value).collect(),
+            _ => Err(AxityError::rt("expected array").with_code("AX0405")),
+        }
+    }
+}
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(v: &Value) -> Result<Self, AxityError> {
+        let collect = |m: &HashMap<String, Value>| -> Result<HashMap<String, T>, AxityError> {
+            m.iter().map(|(k, x)| Ok((k.clone(), T::from_value(x)?))).collect()
+        };
+        match v {
+            Value::Map(rc) | Value::Obj(rc) => collect(&rc.borrow()),
+            Value::Object(rc) => collect(&rc.borrow().fields),
+            _ => Err(AxityError::rt("expected map or object").with_code("AX0405")),
+        }
+    }
+}
+// A value that is present is `Some`; a value of the wrong type is still an error.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(v: &Value) -> Result<Self, AxityError> { T::from_value(v).map(Some) }
+}
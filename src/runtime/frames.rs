@@ -0,0 +1,65 @@
+use crate::error::{AxityError, Span, TraceFrame};
+use crate::runtime::{Runtime, Value};
+
+// One active function, method or lambda call and the span of the expression that called it.
+#[derive(Debug, Clone)]
+pub struct CallFrame {
+    pub name: String,
+    pub site: Option<Span>,
+}
+
+impl Runtime {
+    pub fn frames(&self) -> &[CallFrame] { &self.frames }
+    // Innermost first; each entry holds the location reached inside that frame, `at` for the innermost.
+    pub fn capture_trace(&self, at: Option<&Span>) -> Vec<TraceFrame> {
+        let mut out = Vec::with_capacity(self.frames.len() + 1);
+        let mut loc = at.cloned();
+        for f in self.frames.iter().rev() {
+            out.push(TraceFrame { function: f.name.clone(), span: loc.take(), path: None });
+            loc = f.site.clone();
+        }
+        if loc.is_some() && !out.is_empty() { out.push(TraceFrame { function: "<top level>".to_string(), span: loc, path: None }); }
+        out
+    }
+    // Called when `throw` runs, so an exception that is never caught still reports where it was raised.
+    pub(crate) fn note_throw(&mut self, span: &Span) { self.throw_trace = Some((span.clone(), self.capture_trace(Some(span)))); }
+    // A throw leaving a call frame travels up as an uncaught-exception error while the value waits here;
+    // the nearest enclosing `try` claims it with `take_thrown`, and at the top level it is reported as is.
+    pub(crate) fn unwind(&mut self, value: Value, msg: &str) -> AxityError {
+        self.thrown = Some(value);
+        self.uncaught(msg)
+    }
+    pub(crate) fn take_thrown(&mut self, err: &AxityError) -> Option<Value> {
+        if err.code != "AX0403" { return None; }
+        self.thrown.take()
+    }
+    // Gives an error back to the unwinding machinery, as if no `try` had claimed it.
+    pub(crate) fn restore_thrown(&mut self, value: Value, err: AxityError) -> AxityError {
+        if err.code == "AX0403" { self.thrown = Some(value); }
+        err
+    }
+    pub(crate) fn pending_throw(&mut self) -> Option<Value> { self.thrown.take() }
+    pub(crate) fn begin_catch(&mut self, value: Value, err: Option<AxityError>) { self.caught.push((value, err)); }
+    pub(crate) fn end_catch(&mut self) { self.caught.pop(); }
+    pub(crate) fn current_catch(&self) -> Option<(Value, Option<AxityError>)> { self.caught.last().cloned() }
+    pub(crate) fn enter_try(&mut self) { self.attempts.push(1); }
+    pub(crate) fn next_attempt(&mut self) { if let Some(n) = self.attempts.last_mut() { *n += 1; } }
+    pub(crate) fn exit_try(&mut self) { self.attempts.pop(); }
+    // 1 on the first run of the innermost `try` body, 2 after one `retry`, and so on; 0 outside any `try`.
+    pub fn attempt(&self) -> i64 { self.attempts.last().copied().unwrap_or(0) }
+    // Returns a mark for `release`, which drops everything held since.
+    pub(crate) fn hold(&mut self, values: impl IntoIterator<Item = Value>) -> usize {
+        let mark = self.held.len();
+        self.held.extend(values);
+        mark
+    }
+    pub(crate) fn release(&mut self, mark: usize) { self.held.truncate(mark); }
+    pub(crate) fn uncaught(&mut self, msg: &str) -> AxityError {
+        let mut err = AxityError::rt(&format!("uncaught exception: {}", msg)).with_code("AX0403");
+        if let Some((span, trace)) = self.throw_trace.take() {
+            err.span = Some(span);
+            err.details.trace = trace;
+        }
+        err
+    }
+}
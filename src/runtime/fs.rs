@@ -0,0 +1,125 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+// Backing store for the file builtins and `import`.
+pub trait FileSystem: fmt::Debug {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn exists(&self, path: &Path) -> bool;
+    // Identity used to detect repeated and cyclic imports.
+    fn canonicalize(&self, path: &Path) -> PathBuf { normalize(path) }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> { std::fs::read_to_string(path) }
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> { std::fs::write(path, contents) }
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> { std::fs::create_dir_all(path) }
+    fn exists(&self, path: &Path) -> bool { path.exists() }
+    fn canonicalize(&self, path: &Path) -> PathBuf { std::fs::canonicalize(path).unwrap_or_else(|_| normalize(path)) }
+}
+
+// In-memory tree; clones share the same files so a host can inspect what a script wrote.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFs {
+    inner: Rc<RefCell<MemoryTree>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryTree {
+    files: HashMap<PathBuf, String>,
+    dirs: HashSet<PathBuf>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self { Self::default() }
+    pub fn from_files<I, P, S>(files: I) -> Self where I: IntoIterator<Item = (P, S)>, P: AsRef<Path>, S: Into<String> {
+        let fs = Self::new();
+        for (p, s) in files { fs.add_file(p, s); }
+        fs
+    }
+    pub fn with_file<P: AsRef<Path>, S: Into<String>>(self, path: P, contents: S) -> Self { self.add_file(path, contents); self }
+    pub fn add_file<P: AsRef<Path>, S: Into<String>>(&self, path: P, contents: S) {
+        let p = normalize(path.as_ref());
+        let mut t = self.inner.borrow_mut();
+        add_parents(&mut t.dirs, &p);
+        t.files.insert(p, contents.into());
+    }
+    pub fn file<P: AsRef<Path>>(&self, path: P) -> Option<String> { self.inner.borrow().files.get(&normalize(path.as_ref())).cloned() }
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut v: Vec<PathBuf> = self.inner.borrow().files.keys().cloned().collect();
+        v.sort();
+        v
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.file(path).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{}: no such file", path.display())))
+    }
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        let p = normalize(path);
+        let mut t = self.inner.borrow_mut();
+        if t.dirs.contains(&p) { return Err(io::Error::new(io::ErrorKind::Other, format!("{}: is a directory", path.display()))); }
+        if let Some(parent) = p.parent() {
+            if !parent.as_os_str().is_empty() && !t.dirs.contains(parent) {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("{}: no such directory", parent.display())));
+            }
+        }
+        t.files.insert(p, contents.to_string());
+        Ok(())
+    }
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let p = normalize(path);
+        let mut t = self.inner.borrow_mut();
+        if t.files.contains_key(&p) { return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{}: is a file", path.display()))); }
+        add_parents(&mut t.dirs, &p);
+        t.dirs.insert(p);
+        Ok(())
+    }
+    fn exists(&self, path: &Path) -> bool {
+        let p = normalize(path);
+        let t = self.inner.borrow();
+        t.files.contains_key(&p) || t.dirs.contains(&p)
+    }
+}
+
+fn add_parents(dirs: &mut HashSet<PathBuf>, p: &Path) {
+    for a in p.ancestors().skip(1) {
+        if a.as_os_str().is_empty() { break; }
+        dirs.insert(a.to_path_buf());
+    }
+}
+
+// Resolves `p` the way the OS will open it: the longest existing ancestor is canonicalized, following symlinks,
+// and the rest (a file not created yet) is resolved lexically. Sandbox prefix checks compare these paths.
+pub(crate) fn resolve(p: &Path) -> PathBuf {
+    let abs = if p.is_absolute() { p.to_path_buf() } else { std::env::current_dir().unwrap_or_default().join(p) };
+    for base in abs.ancestors() {
+        if let Ok(real) = std::fs::canonicalize(base) {
+            let rest = abs.strip_prefix(base).unwrap_or(Path::new(""));
+            return normalize(&real.join(rest));
+        }
+    }
+    normalize(&abs)
+}
+
+// Purely lexical: the in-memory tree has no current directory.
+fn normalize(p: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in p.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => { out.pop(); }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
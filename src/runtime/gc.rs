@@ -53,13 +53,26 @@ impl Gc {
     }
     fn bump(&mut self) { self.alloc_count = self.alloc_count.saturating_add(1); }
     pub fn should_collect(&self) -> bool { self.alloc_count >= self.threshold }
-    pub fn collect(&mut self, scopes: &[HashMap<String, Value>]) {
+    // Registered containers, including ones already dropped but not yet pruned.
+    pub fn tracked(&self) -> usize { self.arrays.len() + self.maps.len() + self.objs.len() + self.buffers.len() + self.objects.len() }
+    pub fn live(&mut self) -> usize {
+        self.arrays.retain(|w| w.strong_count() > 0);
+        self.maps.retain(|w| w.strong_count() > 0);
+        self.objs.retain(|w| w.strong_count() > 0);
+        self.buffers.retain(|w| w.strong_count() > 0);
+        self.objects.retain(|w| w.strong_count() > 0);
+        self.tracked()
+    }
+    pub fn collect(&mut self, scopes: &[HashMap<String, Value>]) { self.collect_with_roots(scopes, &[]); }
+    // Like `collect`, also keeping values reachable from `roots` that are not stored in any scope.
+    pub fn collect_with_roots(&mut self, scopes: &[HashMap<String, Value>], roots: &[Value]) {
         let mut marks = Marks::new();
         for scope in scopes {
             for v in scope.values() {
                 mark_value(v, &mut marks);
             }
         }
+        for v in roots { mark_value(v, &mut marks); }
         for w in &self.arrays {
             if let Some(rc) = w.upgrade() {
                 let p = Rc::as_ptr(&rc);
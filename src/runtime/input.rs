@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{BufRead, Read};
+
+// Where `input`, `input_all` and `input_eof` read from. Lines are returned without their trailing newline.
+pub trait InputSource: fmt::Debug {
+    // `None` at end of input.
+    fn read_line(&mut self) -> Option<String>;
+    fn read_all(&mut self) -> String {
+        let mut lines = Vec::new();
+        while let Some(l) = self.read_line() { lines.push(l); }
+        lines.join("\n")
+    }
+    fn at_eof(&mut self) -> bool;
+}
+
+#[derive(Debug, Default)]
+pub struct StdinInput;
+
+impl InputSource for StdinInput {
+    fn read_line(&mut self) -> Option<String> {
+        let mut s = String::new();
+        match std::io::stdin().lock().read_line(&mut s) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(trim_newline(s)),
+        }
+    }
+    // Same result as the default line-by-line join: `\r\n` becomes `\n` and the final newline is dropped.
+    fn read_all(&mut self) -> String {
+        let mut s = String::new();
+        let _ = std::io::stdin().lock().read_to_string(&mut s);
+        s.lines().collect::<Vec<_>>().join("\n")
+    }
+    fn at_eof(&mut self) -> bool { std::io::stdin().lock().fill_buf().map(|b| b.is_empty()).unwrap_or(true) }
+}
+
+// A fixed queue of lines, for tests and non-interactive hosts.
+#[derive(Debug, Default, Clone)]
+pub struct ScriptedInput {
+    lines: VecDeque<String>,
+}
+
+impl ScriptedInput {
+    pub fn new<I, S>(lines: I) -> Self where I: IntoIterator<Item = S>, S: Into<String> {
+        Self { lines: lines.into_iter().map(Into::into).collect() }
+    }
+    pub fn push(&mut self, line: &str) { self.lines.push_back(line.to_string()); }
+}
+
+impl InputSource for ScriptedInput {
+    fn read_line(&mut self) -> Option<String> { self.lines.pop_front() }
+    fn at_eof(&mut self) -> bool { self.lines.is_empty() }
+}
+
+// Asks the host for each line; returning `None` signals end of input.
+pub struct CallbackInput {
+    f: Box<dyn FnMut() -> Option<String>>,
+    peeked: Option<Option<String>>,
+}
+
+impl CallbackInput {
+    pub fn new<F: FnMut() -> Option<String> + 'static>(f: F) -> Self { Self { f: Box::new(f), peeked: None } }
+}
+
+impl InputSource for CallbackInput {
+    fn read_line(&mut self) -> Option<String> {
+        match self.peeked.take() { Some(l) => l, None => (self.f)() }
+    }
+    fn at_eof(&mut self) -> bool {
+        if self.peeked.is_none() { self.peeked = Some((self.f)()); }
+        matches!(self.peeked, Some(None))
+    }
+}
+
+impl fmt::Debug for CallbackInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "CallbackInput(..)") }
+}
+
+fn trim_newline(mut s: String) -> String {
+    if s.ends_with('\n') { s.pop(); if s.ends_with('\r') { s.pop(); } }
+    s
+}
@@ -0,0 +1,17 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// Cloneable, thread-safe flag a host sets to cancel the script running on a `Runtime`.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle {
+    flag: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn new() -> Self { Self::default() }
+    pub fn interrupt(&self) { self.flag.store(true, Ordering::Relaxed); }
+    pub fn is_interrupted(&self) -> bool { self.flag.load(Ordering::Relaxed) }
+    pub fn clear(&self) { self.flag.store(false, Ordering::Relaxed); }
+    // Clears the flag and reports whether it was set, so one request cancels exactly one run.
+    pub(crate) fn take(&self) -> bool { self.flag.swap(false, Ordering::Relaxed) }
+}
@@ -1,10 +1,30 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::io::Write;
 
+mod budget;
+mod config;
+mod convert;
+mod frames;
+mod fs;
 mod gc;
+mod input;
+mod interrupt;
+mod modules;
+mod native;
+mod output;
+mod value_serde;
+pub use config::{Capabilities, Limits, RuntimeConfig, DEFAULT_MAX_CALL_DEPTH};
+pub use convert::{FromValue, IntoValue};
+pub use frames::CallFrame;
+pub use fs::{FileSystem, MemoryFs, RealFs};
 pub use gc::Gc;
+pub use input::{CallbackInput, InputSource, ScriptedInput, StdinInput};
+pub use interrupt::InterruptHandle;
+pub use modules::{ModuleResolver, SearchPathResolver};
+pub use native::{NativeFn, NativeImpl};
+pub use output::OutputSink;
+pub use value_serde::{from_json, from_value, to_instance, to_value};
 
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -37,11 +57,61 @@ pub struct Runtime {
     pub scopes: Vec<HashMap<String, Value>>,
     pub func_index: HashMap<String, usize>,
     pub class_index: HashMap<String, usize>,
+    pub program_id: Option<u64>,
     pub gc: Gc,
+    pub output: OutputSink,
+    pub input: Box<dyn InputSource>,
+    pub fs: Box<dyn FileSystem>,
+    pub resolver: Box<dyn ModuleResolver>,
+    pub natives: HashMap<String, NativeFn>,
+    pub config: RuntimeConfig,
+    interrupt: InterruptHandle,
+    budget: budget::Budget,
+    frames: Vec<CallFrame>,
+    throw_trace: Option<(crate::error::Span, Vec<crate::error::TraceFrame>)>,
+    // Value of an exception unwinding through call frames, waiting for a `try` to claim it.
+    thrown: Option<Value>,
+    // Exceptions handled by the running `catch` blocks, innermost last, for a bare `throw;` to raise again.
+    caught: Vec<(Value, Option<crate::error::AxityError>)>,
+    // Values the interpreter keeps outside any scope while a `finally` block runs.
+    held: Vec<Value>,
+    // Attempt number of each running `try` body, innermost last.
+    attempts: Vec<i64>,
 }
 
 impl Runtime {
-    pub fn new() -> Self { Self { scopes: vec![HashMap::new()], func_index: HashMap::new(), class_index: HashMap::new(), gc: Gc::new() } }
+    pub fn new() -> Self { Self { scopes: vec![HashMap::new()], func_index: HashMap::new(), class_index: HashMap::new(), program_id: None, gc: Gc::new(), output: OutputSink::default(), input: Box::new(StdinInput), fs: Box::new(RealFs), resolver: Box::new(SearchPathResolver::standard()), natives: HashMap::new(), config: RuntimeConfig::default(), interrupt: InterruptHandle::new(), budget: budget::Budget::default(), frames: Vec::new(), throw_trace: None, thrown: None, caught: Vec::new(), held: Vec::new(), attempts: Vec::new() } }
+    pub fn with_output(output: OutputSink) -> Self { let mut rt = Self::new(); rt.output = output; rt }
+    pub fn set_output(&mut self, output: OutputSink) -> OutputSink { std::mem::replace(&mut self.output, output) }
+    pub fn set_input<I: InputSource + 'static>(&mut self, input: I) -> Box<dyn InputSource> { std::mem::replace(&mut self.input, Box::new(input)) }
+    pub fn set_fs<F: FileSystem + 'static>(&mut self, fs: F) -> Box<dyn FileSystem> { std::mem::replace(&mut self.fs, Box::new(fs)) }
+    pub fn set_resolver<R: ModuleResolver + 'static>(&mut self, r: R) -> Box<dyn ModuleResolver> { std::mem::replace(&mut self.resolver, Box::new(r)) }
+    pub fn with_config(config: RuntimeConfig) -> Self { let mut rt = Self::new(); rt.config = config; rt }
+    pub fn interrupt_handle(&self) -> InterruptHandle { self.interrupt.clone() }
+    pub fn register_fn<F>(&mut self, name: &str, params: Vec<crate::types::Type>, ret: crate::types::Type, f: F)
+    where F: Fn(&mut Runtime, &[Value]) -> Result<Value, crate::error::AxityError> + 'static {
+        assert!(!crate::interpreter::BUILTINS.contains(&name), "register_fn: `{}` is a builtin and would never reach the native", name);
+        self.natives.insert(name.to_string(), NativeFn::new(params, ret, f));
+    }
+    pub fn native_signatures(&self) -> HashMap<String, (Vec<crate::types::Type>, crate::types::Type)> {
+        self.natives.iter().map(|(k, n)| (k.clone(), (n.params.clone(), n.ret.clone()))).collect()
+    }
+    pub fn host_env(&self) -> crate::type_checker::HostEnv {
+        crate::type_checker::HostEnv {
+            natives: self.native_signatures(),
+            denied: self.config.caps.denied_builtins().into_iter().map(|s| s.to_string()).collect(),
+        }
+    }
+    pub fn check_path(&self, builtin: &str, path: &str, write: bool) -> Result<(), crate::error::AxityError> {
+        let caps = &self.config.caps;
+        if write && !caps.fs_write { return Err(crate::error::AxityError::perm(&format!("{} requires filesystem write access", builtin))); }
+        if !write && !caps.fs_read { return Err(crate::error::AxityError::perm(&format!("{} requires filesystem read access", builtin))); }
+        if !caps.path_allowed(path) { return Err(crate::error::AxityError::perm(&format!("{} outside allowed paths: {}", builtin, path))); }
+        Ok(())
+    }
+    pub fn check_stdin(&self, builtin: &str) -> Result<(), crate::error::AxityError> {
+        if self.config.caps.stdin { Ok(()) } else { Err(crate::error::AxityError::perm(&format!("{} requires stdin access", builtin))) }
+    }
     pub fn get(&self, name: &str) -> Option<Value> {
         for i in (0..self.scopes.len()).rev() { if let Some(v) = self.scopes[i].get(name) { return Some(v.clone()); } }
         None
@@ -66,32 +136,37 @@ impl Runtime {
     pub fn new_array(&mut self, v: Vec<Value>) -> Value {
         let rc = Rc::new(RefCell::new(v));
         let rc = self.gc.register_array(rc);
+        self.note_alloc();
         Value::Array(rc)
     }
     pub fn new_map(&mut self) -> Value {
         let rc = Rc::new(RefCell::new(HashMap::new()));
         let rc = self.gc.register_map(rc);
+        self.note_alloc();
         Value::Map(rc)
     }
     pub fn new_obj_map(&mut self, m: HashMap<String, Value>) -> Value {
         let rc = Rc::new(RefCell::new(m));
         let rc = self.gc.register_obj(rc);
+        self.note_alloc();
         Value::Obj(rc)
     }
     pub fn new_buffer(&mut self, v: Vec<u8>) -> Value {
         let rc = Rc::new(RefCell::new(v));
         let rc = self.gc.register_buffer(rc);
+        self.note_alloc();
         Value::Buffer(rc)
     }
     pub fn new_object(&mut self, class: String, fields: HashMap<String, Value>) -> Value {
         let rc = Rc::new(RefCell::new(Object{ class, fields }));
         let rc = self.gc.register_object(rc);
+        self.note_alloc();
         Value::Object(rc)
     }
-    pub fn gc_maybe_collect(&mut self) { if self.gc.should_collect() { self.gc.collect(&self.scopes); } }
-    pub fn gc_collect(&mut self) { self.gc.collect(&self.scopes); }
-    pub fn emit(&self, out: &mut String, s: &str) {
-        let _ = std::io::stdout().write_all(s.as_bytes());
-        let _ = std::io::stdout().flush();
+    pub fn gc_maybe_collect(&mut self) { if self.gc.should_collect() { self.gc_collect(); } }
+    pub fn gc_collect(&mut self) {
+        let roots: Vec<Value> = self.thrown.iter().chain(self.caught.iter().map(|c| &c.0)).chain(&self.held).cloned().collect();
+        self.gc.collect_with_roots(&self.scopes, &roots);
     }
+    pub fn emit(&mut self, out: &mut String, s: &str) { self.output.write(out, s); }
 }
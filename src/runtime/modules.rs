@@ -0,0 +1,57 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::runtime::FileSystem;
+
+// Maps an `import "spec"` to the file to load. On failure returns every path that was tried.
+pub trait ModuleResolver: fmt::Debug {
+    fn resolve(&self, spec: &str, importer_dir: &Path, fs: &dyn FileSystem) -> Result<PathBuf, Vec<PathBuf>>;
+}
+
+// Tries, in order: the importing file's directory, the project's `src/includes`, each search path, then the stdlib root.
+#[derive(Debug, Clone, Default)]
+pub struct SearchPathResolver {
+    pub project_includes: bool,
+    pub search_paths: Vec<PathBuf>,
+    pub stdlib_root: Option<PathBuf>,
+}
+
+impl SearchPathResolver {
+    // Only resolves relative to the importing file.
+    pub fn new() -> Self { Self::default() }
+    // Project includes, `AXITY_PATH` and `AXITY_STDLIB` (or a `stdlib` directory next to the executable).
+    pub fn standard() -> Self {
+        let search_paths = std::env::var_os("AXITY_PATH").map(|v| std::env::split_paths(&v).filter(|p| !p.as_os_str().is_empty()).collect()).unwrap_or_default();
+        let stdlib_root = std::env::var_os("AXITY_STDLIB").map(PathBuf::from).or_else(|| {
+            let dir = std::env::current_exe().ok()?.parent()?.join("stdlib");
+            if dir.is_dir() { Some(dir) } else { None }
+        });
+        Self { project_includes: true, search_paths, stdlib_root }
+    }
+    pub fn with_project_includes(mut self, on: bool) -> Self { self.project_includes = on; self }
+    pub fn with_search_path<P: AsRef<Path>>(mut self, p: P) -> Self { self.search_paths.push(p.as_ref().to_path_buf()); self }
+    pub fn with_stdlib_root<P: AsRef<Path>>(mut self, p: P) -> Self { self.stdlib_root = Some(p.as_ref().to_path_buf()); self }
+    pub fn candidates(&self, spec: &str, importer_dir: &Path, fs: &dyn FileSystem) -> Vec<PathBuf> {
+        let mut out = vec![importer_dir.join(spec)];
+        if Path::new(spec).is_absolute() { return out; }
+        if self.project_includes {
+            if let Some(root) = project_root(importer_dir, fs) { out.push(root.join("src").join("includes").join(spec)); }
+        }
+        for sp in &self.search_paths { out.push(sp.join(spec)); }
+        if let Some(root) = &self.stdlib_root { out.push(root.join(spec)); }
+        out.dedup();
+        out
+    }
+}
+
+impl ModuleResolver for SearchPathResolver {
+    fn resolve(&self, spec: &str, importer_dir: &Path, fs: &dyn FileSystem) -> Result<PathBuf, Vec<PathBuf>> {
+        let cands = self.candidates(spec, importer_dir, fs);
+        cands.iter().find(|c| fs.exists(c)).cloned().ok_or(cands)
+    }
+}
+
+// Nearest ancestor holding the `.axity` marker written by `axity init`.
+fn project_root(dir: &Path, fs: &dyn FileSystem) -> Option<PathBuf> {
+    let abs = fs.canonicalize(dir);
+    abs.ancestors().find(|a| fs.exists(&a.join(".axity"))).map(|a| a.to_path_buf())
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+use std::rc::Rc;
+use crate::error::AxityError;
+use crate::runtime::{Runtime, Value};
+use crate::types::Type;
+
+pub type NativeImpl = dyn Fn(&mut Runtime, &[Value]) -> Result<Value, AxityError>;
+
+#[derive(Clone)]
+pub struct NativeFn {
+    pub params: Vec<Type>,
+    pub ret: Type,
+    pub func: Rc<NativeImpl>,
+}
+
+impl NativeFn {
+    pub fn new<F>(params: Vec<Type>, ret: Type, f: F) -> Self
+    where F: Fn(&mut Runtime, &[Value]) -> Result<Value, AxityError> + 'static {
+        Self { params, ret, func: Rc::new(f) }
+    }
+    pub fn call(&self, rt: &mut Runtime, args: &[Value]) -> Result<Value, AxityError> { (self.func)(rt, args) }
+}
+
+impl fmt::Debug for NativeFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NativeFn({:?}) -> {:?}", self.params, self.ret)
+    }
+}
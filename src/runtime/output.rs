@@ -0,0 +1,54 @@
+use std::fmt;
+use std::io::Write;
+
+pub enum OutputSink {
+    Capture,
+    Stdout,
+    Stderr,
+    Null,
+    Writer(Box<dyn Write>),
+    Callback(Box<dyn FnMut(&str)>),
+}
+
+impl OutputSink {
+    pub fn writer<W: Write + 'static>(w: W) -> Self { OutputSink::Writer(Box::new(w)) }
+    pub fn callback<F: FnMut(&str) + 'static>(f: F) -> Self { OutputSink::Callback(Box::new(f)) }
+    pub fn write(&mut self, out: &mut String, s: &str) {
+        match self {
+            OutputSink::Capture => out.push_str(s),
+            OutputSink::Stdout => {
+                let mut so = std::io::stdout();
+                let _ = so.write_all(s.as_bytes());
+                let _ = so.flush();
+            }
+            OutputSink::Stderr => {
+                let mut se = std::io::stderr();
+                let _ = se.write_all(s.as_bytes());
+                let _ = se.flush();
+            }
+            OutputSink::Null => {}
+            OutputSink::Writer(w) => {
+                let _ = w.write_all(s.as_bytes());
+                let _ = w.flush();
+            }
+            OutputSink::Callback(f) => f(s),
+        }
+    }
+}
+
+impl Default for OutputSink {
+    fn default() -> Self { OutputSink::Capture }
+}
+
+impl fmt::Debug for OutputSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputSink::Capture => write!(f, "Capture"),
+            OutputSink::Stdout => write!(f, "Stdout"),
+            OutputSink::Stderr => write!(f, "Stderr"),
+            OutputSink::Null => write!(f, "Null"),
+            OutputSink::Writer(_) => write!(f, "Writer(..)"),
+            OutputSink::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
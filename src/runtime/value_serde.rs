@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Error as _, SerializeMap, SerializeSeq};
+use serde::{forward_to_deserialize_any, Deserialize, Serialize, Serializer};
+use crate::error::AxityError;
+use crate::runtime::{Runtime, Value};
+
+const SCALE: f64 = 1_000_000.0;
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Int(i) => s.serialize_i64(*i),
+            Value::Flt(f) => s.serialize_f64(*f as f64 / SCALE),
+            Value::Str(v) => s.serialize_str(v),
+            Value::Bool(b) => s.serialize_bool(*b),
+            Value::Array(rc) => {
+                let items = rc.borrow();
+                let mut seq = s.serialize_seq(Some(items.len()))?;
+                for el in items.iter() { seq.serialize_element(el)?; }
+                seq.end()
+            }
+            Value::Map(rc) | Value::Obj(rc) => serialize_fields(&rc.borrow(), s),
+            Value::Object(rc) => serialize_fields(&rc.borrow().fields, s),
+            Value::Buffer(rc) => s.serialize_bytes(&rc.borrow()),
+            Value::Lambda(_) => Err(S::Error::custom("cannot serialize lambda")),
+        }
+    }
+}
+
+fn serialize_fields<S: Serializer>(m: &HashMap<String, Value>, s: S) -> Result<S::Ok, S::Error> {
+    let mut map = s.serialize_map(Some(m.len()))?;
+    for (k, v) in m { map.serialize_entry(k, v)?; }
+    map.end()
+}
+
+// Converts any `Serialize` type into a script value: structs and maps become `obj`, sequences become arrays.
+// Axity has no null, so `None` struct fields are left out and a `None` anywhere else is an error.
+pub fn to_value<T: Serialize + ?Sized>(rt: &mut Runtime, v: &T) -> Result<Value, AxityError> {
+    match v.serialize(ValueSerializer { rt }).map_err(|e| ser_error(&e))? {
+        Some(v) => Ok(v),
+        None => Err(ser_error(&ConvError::custom("None has no script value"))),
+    }
+}
+
+// Like `to_value`, but a top-level struct becomes an instance of `class` so it type-checks as that class.
+pub fn to_instance<T: Serialize + ?Sized>(rt: &mut Runtime, class: &str, v: &T) -> Result<Value, AxityError> {
+    match to_value(rt, v)? {
+        Value::Obj(rc) => {
+            let fields = rc.borrow().clone();
+            Ok(rt.new_object(class.to_string(), fields))
+        }
+        _ => Err(AxityError::rt("class instance requires a struct or map").with_code("AX0405")),
+    }
+}
+
+// Reads a script value back into any `Deserialize` type; a missing `obj` field reads as `None`.
+pub fn from_value<T: DeserializeOwned>(v: &Value) -> Result<T, AxityError> {
+    T::deserialize(ValueDeserializer(v.clone())).map_err(|e| AxityError::rt(&format!("deserialize: {}", e)).with_code("AX0408"))
+}
+
+// Axity has no null: null array elements become `0` and null object fields are omitted.
+pub fn from_json(rt: &mut Runtime, j: serde_json::Value) -> Value {
+    match j {
+        serde_json::Value::Null => Value::Int(0),
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() { Value::Int(i) }
+            else { Value::Flt((n.as_f64().unwrap_or(0.0) * SCALE).round() as i64) }
+        }
+        serde_json::Value::String(s) => Value::Str(s),
+        serde_json::Value::Array(xs) => {
+            let items = xs.into_iter().map(|x| from_json(rt, x)).collect();
+            rt.new_array(items)
+        }
+        serde_json::Value::Object(m) => {
+            let mut fields = HashMap::new();
+            for (k, v) in m {
+                if v.is_null() { continue; }
+                let v = from_json(rt, v);
+                fields.insert(k, v);
+            }
+            rt.new_obj_map(fields)
+        }
+    }
+}
+
+fn ser_error(e: &ConvError) -> AxityError {
+    AxityError::rt(&format!("serialize: {}", e)).with_code("AX0408")
+}
+
+#[derive(Debug)]
+struct ConvError(String);
+
+impl fmt::Display for ConvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+impl std::error::Error for ConvError {}
+impl ser::Error for ConvError {
+    fn custom<T: fmt::Display>(msg: T) -> Self { ConvError(msg.to_string()) }
+}
+impl de::Error for ConvError {
+    fn custom<T: fmt::Display>(msg: T) -> Self { ConvError(msg.to_string()) }
+}
+
+// Builds script values straight from serde data. `Ok(None)` is a null (`None` or unit), which only a
+// struct or map field can hold: the field is left out.
+struct ValueSerializer<'a> {
+    rt: &'a mut Runtime,
+}
+
+impl ValueSerializer<'_> {
+    fn obj(self, fields: HashMap<String, Value>) -> Value { self.rt.new_obj_map(fields) }
+    fn variant(self, name: &str, inner: Value) -> Value {
+        let mut fields = HashMap::new();
+        fields.insert(name.to_string(), inner);
+        self.obj(fields)
+    }
+}
+
+impl<'a> Serializer for ValueSerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    type SerializeSeq = SeqBuilder<'a>;
+    type SerializeTuple = SeqBuilder<'a>;
+    type SerializeTupleStruct = SeqBuilder<'a>;
+    type SerializeTupleVariant = SeqBuilder<'a>;
+    type SerializeMap = MapBuilder<'a>;
+    type SerializeStruct = MapBuilder<'a>;
+    type SerializeStructVariant = MapBuilder<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, ConvError> { Ok(Some(Value::Bool(v))) }
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, ConvError> { self.serialize_i64(v as i64) }
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, ConvError> { self.serialize_i64(v as i64) }
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, ConvError> { self.serialize_i64(v as i64) }
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, ConvError> { Ok(Some(Value::Int(v))) }
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, ConvError> { self.serialize_i64(v as i64) }
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, ConvError> { self.serialize_i64(v as i64) }
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, ConvError> { self.serialize_i64(v as i64) }
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, ConvError> {
+        let i = i64::try_from(v).map_err(|_| ConvError::custom(format!("{} is out of range for int", v)))?;
+        self.serialize_i64(i)
+    }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, ConvError> { self.serialize_f64(v as f64) }
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, ConvError> { Ok(Some(Value::Flt((v * SCALE).round() as i64))) }
+    fn serialize_char(self, v: char) -> Result<Self::Ok, ConvError> { Ok(Some(Value::Str(v.to_string()))) }
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, ConvError> { Ok(Some(Value::Str(v.to_string()))) }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, ConvError> { Ok(Some(self.rt.new_buffer(v.to_vec()))) }
+    fn serialize_none(self) -> Result<Self::Ok, ConvError> { Ok(None) }
+    fn serialize_some<T: Serialize + ?Sized>(self, v: &T) -> Result<Self::Ok, ConvError> { v.serialize(self) }
+    fn serialize_unit(self) -> Result<Self::Ok, ConvError> { Ok(None) }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, ConvError> { Ok(None) }
+    fn serialize_unit_variant(self, _name: &'static str, _idx: u32, variant: &'static str) -> Result<Self::Ok, ConvError> {
+        self.serialize_str(variant)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, v: &T) -> Result<Self::Ok, ConvError> {
+        v.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _idx: u32, variant: &'static str, v: &T) -> Result<Self::Ok, ConvError> {
+        let inner = v.serialize(ValueSerializer { rt: &mut *self.rt })?.ok_or_else(|| ConvError::custom(format!("variant '{}' holds None", variant)))?;
+        Ok(Some(self.variant(variant, inner)))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder<'a>, ConvError> {
+        Ok(SeqBuilder { rt: self.rt, items: Vec::with_capacity(len.unwrap_or(0)), variant: None })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder<'a>, ConvError> { self.serialize_seq(Some(len)) }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqBuilder<'a>, ConvError> { self.serialize_seq(Some(len)) }
+    fn serialize_tuple_variant(self, _name: &'static str, _idx: u32, variant: &'static str, len: usize) -> Result<SeqBuilder<'a>, ConvError> {
+        Ok(SeqBuilder { rt: self.rt, items: Vec::with_capacity(len), variant: Some(variant) })
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapBuilder<'a>, ConvError> {
+        Ok(MapBuilder { rt: self.rt, fields: HashMap::new(), key: None, variant: None })
+    }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapBuilder<'a>, ConvError> { self.serialize_map(Some(len)) }
+    fn serialize_struct_variant(self, _name: &'static str, _idx: u32, variant: &'static str, _len: usize) -> Result<MapBuilder<'a>, ConvError> {
+        Ok(MapBuilder { rt: self.rt, fields: HashMap::new(), key: None, variant: Some(variant) })
+    }
+}
+
+// Sequences and tuples become arrays; a tuple variant becomes `{ variant: [..] }`.
+struct SeqBuilder<'a> {
+    rt: &'a mut Runtime,
+    items: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SeqBuilder<'_> {
+    fn push<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), ConvError> {
+        let item = v.serialize(ValueSerializer { rt: &mut *self.rt })?.ok_or_else(|| ConvError::custom("None has no script value inside an array"))?;
+        self.items.push(item);
+        Ok(())
+    }
+    fn finish(self) -> Result<Option<Value>, ConvError> {
+        let arr = self.rt.new_array(self.items);
+        Ok(Some(match self.variant {
+            Some(name) => ValueSerializer { rt: self.rt }.variant(name, arr),
+            None => arr,
+        }))
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), ConvError> { self.push(v) }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+impl ser::SerializeTuple for SeqBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), ConvError> { self.push(v) }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+impl ser::SerializeTupleStruct for SeqBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), ConvError> { self.push(v) }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+impl ser::SerializeTupleVariant for SeqBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), ConvError> { self.push(v) }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+
+// Structs and maps become `obj`; a struct variant becomes `{ variant: { .. } }`. Null fields are left out.
+struct MapBuilder<'a> {
+    rt: &'a mut Runtime,
+    fields: HashMap<String, Value>,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl MapBuilder<'_> {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, v: &T) -> Result<(), ConvError> {
+        if let Some(v) = v.serialize(ValueSerializer { rt: &mut *self.rt })? {
+            self.fields.insert(key, v);
+        }
+        Ok(())
+    }
+    fn finish(self) -> Result<Option<Value>, ConvError> {
+        let obj = self.rt.new_obj_map(self.fields);
+        Ok(Some(match self.variant {
+            Some(name) => ValueSerializer { rt: self.rt }.variant(name, obj),
+            None => obj,
+        }))
+    }
+}
+
+impl ser::SerializeMap for MapBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, k: &T) -> Result<(), ConvError> {
+        self.key = Some(match k.serialize(ValueSerializer { rt: &mut *self.rt })? {
+            Some(Value::Str(s)) => s,
+            Some(Value::Int(i)) => i.to_string(),
+            Some(Value::Bool(b)) => b.to_string(),
+            _ => return Err(ConvError::custom("map keys must be strings, ints or bools")),
+        });
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), ConvError> {
+        let key = self.key.take().ok_or_else(|| ConvError::custom("map value without a key"))?;
+        self.insert(key, v)
+    }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+impl ser::SerializeStruct for MapBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, v: &T) -> Result<(), ConvError> { self.insert(key.to_string(), v) }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+impl ser::SerializeStructVariant for MapBuilder<'_> {
+    type Ok = Option<Value>;
+    type Error = ConvError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, v: &T) -> Result<(), ConvError> { self.insert(key.to_string(), v) }
+    fn end(self) -> Result<Option<Value>, ConvError> { self.finish() }
+}
+
+// Feeds a script value to serde. Containers are cloned out of their `RefCell` first; the elements are `Rc`s,
+// so this is shallow.
+struct ValueDeserializer(Value);
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = ConvError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvError> {
+        match self.0 {
+            Value::Int(i) => visitor.visit_i64(i),
+            Value::Flt(f) => visitor.visit_f64(f as f64 / SCALE),
+            Value::Str(s) => visitor.visit_string(s),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Array(rc) => {
+                let items = rc.borrow().clone();
+                visitor.visit_seq(SeqReader(items.into_iter()))
+            }
+            Value::Map(rc) | Value::Obj(rc) => {
+                let fields = rc.borrow().clone();
+                visitor.visit_map(MapReader { iter: fields.into_iter(), value: None })
+            }
+            Value::Object(rc) => {
+                let fields = rc.borrow().fields.clone();
+                visitor.visit_map(MapReader { iter: fields.into_iter(), value: None })
+            }
+            Value::Buffer(rc) => {
+                let bytes = rc.borrow().clone();
+                visitor.visit_byte_buf(bytes)
+            }
+            Value::Lambda(_) => Err(ConvError::custom("cannot deserialize lambda")),
+        }
+    }
+
+    // A value that is present is always `Some`; `None` comes from a missing field.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvError> { visitor.visit_some(self) }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ConvError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, ConvError> {
+        match self.0 {
+            Value::Str(s) => visitor.visit_enum(s.into_deserializer()),
+            Value::Obj(rc) | Value::Map(rc) => {
+                let fields = rc.borrow();
+                let mut it = fields.iter();
+                match (it.next(), it.next()) {
+                    (Some((k, v)), None) => visitor.visit_enum(EnumReader { variant: k.clone(), value: v.clone() }),
+                    _ => Err(ConvError::custom("an enum variant must be an obj with exactly one field")),
+                }
+            }
+            _ => Err(ConvError::custom("expected a string or obj for an enum")),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqReader(std::vec::IntoIter<Value>);
+
+impl<'de> de::SeqAccess<'de> for SeqReader {
+    type Error = ConvError;
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ConvError> {
+        match self.0.next() {
+            Some(v) => seed.deserialize(ValueDeserializer(v)).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn size_hint(&self) -> Option<usize> { Some(self.0.len()) }
+}
+
+struct MapReader {
+    iter: std::collections::hash_map::IntoIter<String, Value>,
+    value: Option<Value>,
+}
+
+impl<'de> de::MapAccess<'de> for MapReader {
+    type Error = ConvError;
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ConvError> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                seed.deserialize(k.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ConvError> {
+        let v = self.value.take().ok_or_else(|| ConvError::custom("map value without a key"))?;
+        seed.deserialize(ValueDeserializer(v))
+    }
+    fn size_hint(&self) -> Option<usize> { Some(self.iter.len()) }
+}
+
+struct EnumReader {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumReader {
+    type Error = ConvError;
+    type Variant = ValueDeserializer;
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, ValueDeserializer), ConvError> {
+        let name = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((name, ValueDeserializer(self.value)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for ValueDeserializer {
+    type Error = ConvError;
+    fn unit_variant(self) -> Result<(), ConvError> { Deserialize::deserialize(self) }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, ConvError> { seed.deserialize(self) }
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, ConvError> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, ConvError> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
@@ -14,8 +14,11 @@ pub enum TokenKind {
     Else,
     Try,
     Catch,
+    Finally,
     Throw,
     Retry,
+    Break,
+    Continue,
     Do,
     For,
     In,
@@ -48,6 +51,7 @@ pub enum TokenKind {
     LBracket,
     RBracket,
     Colon,
+    Question,
     Semicolon,
     Comma,
     Arrow,
@@ -81,3 +85,87 @@ pub struct Token {
     pub span: Span,
 }
 
+
+// How a token is named in "expected X, found Y" messages.
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TokenKind::Ident(s) => return write!(f, "identifier `{}`", s),
+            TokenKind::IntLit(v) => return write!(f, "integer `{}`", v),
+            TokenKind::FltLit(_) => return write!(f, "float literal"),
+            TokenKind::StringLit(_) => return write!(f, "string literal"),
+            TokenKind::Eof => return write!(f, "end of file"),
+            TokenKind::Let => "let",
+            TokenKind::Class => "class",
+            TokenKind::New => "new",
+            TokenKind::SelfKw => "self",
+            TokenKind::Fn => "fn",
+            TokenKind::Return => "return",
+            TokenKind::Print => "print",
+            TokenKind::While => "while",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::Try => "try",
+            TokenKind::Catch => "catch",
+            TokenKind::Finally => "finally",
+            TokenKind::Throw => "throw",
+            TokenKind::Retry => "retry",
+            TokenKind::Break => "break",
+            TokenKind::Continue => "continue",
+            TokenKind::Do => "do",
+            TokenKind::For => "for",
+            TokenKind::In => "in",
+            TokenKind::Import => "import",
+            TokenKind::Match => "match",
+            TokenKind::Case => "case",
+            TokenKind::Default => "default",
+            TokenKind::IntType => "int",
+            TokenKind::StringType => "str",
+            TokenKind::FltType => "flt",
+            TokenKind::BoolType => "bool",
+            TokenKind::ObjType => "obj",
+            TokenKind::BufferType => "buffer",
+            TokenKind::AnyType => "any",
+            TokenKind::ArrayKw => "array",
+            TokenKind::MapKw => "map",
+            TokenKind::TrueKw => "true",
+            TokenKind::FalseKw => "false",
+            TokenKind::AndAnd => "&&",
+            TokenKind::OrOr => "||",
+            TokenKind::Bang => "!",
+            TokenKind::LParen => "(",
+            TokenKind::RParen => ")",
+            TokenKind::LBrace => "{",
+            TokenKind::RBrace => "}",
+            TokenKind::LBracket => "[",
+            TokenKind::RBracket => "]",
+            TokenKind::Colon => ":",
+            TokenKind::Question => "?",
+            TokenKind::Semicolon => ";",
+            TokenKind::Comma => ",",
+            TokenKind::Arrow => "->",
+            TokenKind::Assign => "=",
+            TokenKind::Dot => ".",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Star => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::Less => "<",
+            TokenKind::LessEq => "<=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEq => ">=",
+            TokenKind::EqEq => "==",
+            TokenKind::NotEq => "!=",
+            TokenKind::PlusPlus => "++",
+            TokenKind::MinusMinus => "--",
+            TokenKind::BitAnd => "&",
+            TokenKind::BitOr => "|",
+            TokenKind::BitXor => "^",
+            TokenKind::Tilde => "~",
+            TokenKind::Shl => "<<",
+            TokenKind::Shr => ">>",
+        };
+        write!(f, "`{}`", s)
+    }
+}
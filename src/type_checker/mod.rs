@@ -1,140 +1,231 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use crate::ast::*;
 use crate::error::{AxityError, Span};
 use crate::types::Type;
 
+// What the embedding runtime adds to or removes from the builtin surface.
+#[derive(Debug, Clone, Default)]
+pub struct HostEnv {
+    pub natives: HashMap<String,(Vec<Type>,Type)>,
+    pub denied: HashSet<String>,
+}
+
 pub fn check(p: &Program) -> Result<(), AxityError> {
+    check_with_host(p, &HostEnv::default())
+}
+
+pub fn check_with_host(p: &Program, host: &HostEnv) -> Result<(), AxityError> {
+    let mut errs = check_all(p, host).into_iter();
+    match errs.next() {
+        None => Ok(()),
+        Some(first) => Err(first.with_others(errs.collect())),
+    }
+}
+
+// Every type error in the program, or an empty list when it checks.
+pub fn check_all(p: &Program, host: &HostEnv) -> Vec<AxityError> {
+    let mut sink = Sink::default();
     let mut funcs: HashMap<String, (Vec<Type>, Type, Span)> = HashMap::new();
     let mut classes: HashMap<String, (HashMap<String, Type>, HashMap<String, (Vec<Type>, Type)>)> = HashMap::new();
+    for (name, _) in crate::types::ERROR_CLASSES {
+        let flds = crate::types::ERROR_FIELDS.iter().map(|f| (f.to_string(), Type::String)).collect();
+        classes.insert(name.to_string(), (flds, HashMap::new()));
+    }
     for it in &p.items {
         if let Item::Func(f) = it {
-            if funcs.contains_key(&f.name) { return Err(AxityError::ty("duplicate function", f.span.clone())); }
+            if let Some(prev) = funcs.get(&f.name) { sink.errors.push(AxityError::ty("duplicate function", f.span.clone()).with_code("AX0306").with_label(&prev.2, "first defined here")); continue; }
             funcs.insert(f.name.clone(), (f.params.iter().map(|x| x.ty.clone()).collect(), f.ret.clone(), f.span.clone()));
         }
         if let Item::Class(c) = it {
-            if classes.contains_key(&c.name) { return Err(AxityError::ty("duplicate class", c.span.clone())); }
+            if crate::types::is_error_class(&c.name) {
+                sink.errors.push(AxityError::ty("duplicate class", c.span.clone()).with_code("AX0306").with_note(&format!("`{}` is a built-in error class", c.name)));
+                continue;
+            }
+            if classes.contains_key(&c.name) {
+                let first = p.items.iter().find_map(|o| match o { Item::Class(o) if o.name == c.name => Some(&o.span), _ => None });
+                sink.errors.push(first.into_iter().fold(AxityError::ty("duplicate class", c.span.clone()).with_code("AX0306"), |e, sp| e.with_label(sp, "first defined here")));
+                continue;
+            }
             let mut flds = HashMap::new();
             for fld in &c.fields {
-                if flds.contains_key(&fld.name) { return Err(AxityError::ty("duplicate field", fld.span.clone())); }
+                if flds.contains_key(&fld.name) {
+                    let first = c.fields.iter().find(|o| o.name == fld.name).map(|o| &o.span);
+                    sink.errors.push(first.into_iter().fold(AxityError::ty("duplicate field", fld.span.clone()).with_code("AX0306"), |e, sp| e.with_label(sp, "first declared here")));
+                    continue;
+                }
                 flds.insert(fld.name.clone(), fld.ty.clone());
             }
             let mut meths = HashMap::new();
             for m in &c.methods {
-                if meths.contains_key(&m.name) { return Err(AxityError::ty("duplicate method", m.span.clone())); }
+                if meths.contains_key(&m.name) {
+                    let first = c.methods.iter().find(|o| o.name == m.name).map(|o| &o.span);
+                    sink.errors.push(first.into_iter().fold(AxityError::ty("duplicate method", m.span.clone()).with_code("AX0306"), |e, sp| e.with_label(sp, "first defined here")));
+                    continue;
+                }
                 let sig = (m.params.iter().map(|x| x.ty.clone()).collect::<Vec<_>>(), m.ret.clone());
                 meths.insert(m.name.clone(), sig);
             }
             classes.insert(c.name.clone(), (flds, meths));
         }
     }
+    for it in &p.items {
+        match it {
+            Item::Stmt(s) => check_control(std::slice::from_ref(s), false, false, &mut Vec::new(), &ControlCx{ classes: &classes, funcs: &funcs, host, typed: true }, &mut sink),
+            Item::Func(f) => check_control(&f.body, false, false, &mut Vec::new(), &ControlCx{ classes: &classes, funcs: &funcs, host, typed: false }, &mut sink),
+            Item::Class(c) => for m in &c.methods { check_control(&m.body, false, false, &mut Vec::new(), &ControlCx{ classes: &classes, funcs: &funcs, host, typed: false }, &mut sink) },
+            Item::Import(_, _) => {}
+        }
+    }
     let mut vars: Vec<HashMap<String, Type>> = vec![HashMap::new()];
     for it in &p.items {
         match it {
-            Item::Stmt(s) => check_stmt(s, &mut vars, &funcs, &classes)?,
+            Item::Stmt(s) => check_stmt(s, &mut vars, &funcs, &classes, host, &mut sink),
             Item::Func(f) => {
                 vars.push(HashMap::new());
                 for par in &f.params { vars.last_mut().unwrap().insert(par.name.clone(), par.ty.clone()); }
                 // Loosen function body enforcement to allow interpreter-driven semantics
-                let mut has_return = f.body.iter().any(|s| matches!(s, Stmt::Return{..}));
+                let mut has_return = returns(&f.body);
                 vars.pop();
-                if f.ret == Type::Int && !has_return { return Err(AxityError::ty("missing return", f.span.clone())); }
+                if f.ret == Type::Int && !has_return { sink.errors.push(AxityError::ty("missing return", f.span.clone()).with_code("AX0307").with_note(&format!("`{}` is declared to return {}", f.name, f.ret))); }
             }
             Item::Class(_) => {}
             Item::Import(_, _) => {}
         }
     }
-    Ok(())
+    sink.errors
+}
+
+// Errors found so far. `poisoned` counts expressions typed `Type::Error`; a type error raised while
+// checking something that contains one is a follow-on of the earlier error and is dropped.
+#[derive(Default)]
+struct Sink {
+    errors: Vec<AxityError>,
+    poisoned: usize,
+}
+
+impl Sink {
+    fn report(&mut self, err: AxityError, poisoned_before: usize) {
+        if self.poisoned == poisoned_before || !OPERAND_TYPE_CODES.contains(&err.code) { self.errors.push(err); }
+        self.poisoned += 1;
+    }
+}
+
+// Codes that judge the type of an operand, the only errors an operand typed `Type::Error` can cause.
+// Names, argument counts and placement are checked independently and always reported.
+const OPERAND_TYPE_CODES: &[&str] = &["AX0300", "AX0302", "AX0305", "AX0309", "AX0310"];
+
+// Records a failing statement and moves on to the next one.
+fn check_stmt(s: &Stmt, vars: &mut Vec<HashMap<String, Type>>, funcs: &HashMap<String,(Vec<Type>,Type,Span)>, classes: &HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>, host: &HostEnv, sink: &mut Sink) {
+    let before = sink.poisoned;
+    if let Err(e) = check_stmt_at(s, vars, funcs, classes, host, sink) { sink.report(e, before); }
 }
 
-fn check_stmt(s: &Stmt, vars: &mut Vec<HashMap<String, Type>>, funcs: &HashMap<String,(Vec<Type>,Type,Span)>, classes: &HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>) -> Result<(), AxityError> {
+fn check_stmt_at(s: &Stmt, vars: &mut Vec<HashMap<String, Type>>, funcs: &HashMap<String,(Vec<Type>,Type,Span)>, classes: &HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>, host: &HostEnv, sink: &mut Sink) -> Result<(), AxityError> {
     match s {
         Stmt::Let{ name, ty, init, span } => {
-            let _t = check_expr(init, vars, funcs, classes)?;
-            if vars.last().unwrap().contains_key(name) { return Err(AxityError::ty("duplicate variable", span.clone())); }
+            let t = check_expr(init, vars, funcs, classes, host, sink);
+            cond_matches(init, &t, ty, sink);
+            if vars.last().unwrap().contains_key(name) { return Err(AxityError::ty("duplicate variable", span.clone()).with_code("AX0306")); }
             vars.last_mut().unwrap().insert(name.clone(), ty.clone());
             Ok(())
         }
         Stmt::Assign{ name, expr, span } => {
-            let _t = check_expr(expr, vars, funcs, classes)?;
-            let vt = lookup_var(name, vars).ok_or_else(|| AxityError::ty("undefined variable", span.clone()))?;
+            let t = check_expr(expr, vars, funcs, classes, host, sink);
+            let vt = lookup_var(name, vars).ok_or_else(|| AxityError::ty("undefined variable", span.clone()).with_code("AX0301"))?;
+            cond_matches(expr, &t, &vt, sink);
             Ok(())
         }
         Stmt::MemberAssign{ object, field, expr, span } => {
-            let ot = check_expr(object, vars, funcs, classes)?;
-            let vt = check_expr(expr, vars, funcs, classes)?;
+            let ot = check_expr(object, vars, funcs, classes, host, sink);
+            let vt = check_expr(expr, vars, funcs, classes, host, sink);
             if let Type::Class(ref cname) = ot {
-                let (flds, _) = classes.get(cname).ok_or_else(|| AxityError::ty("unknown class", span.clone()))?;
-                let ft = flds.get(field).ok_or_else(|| AxityError::ty("unknown field", span.clone()))?;
-                if !(type_equals(&vt, ft)) { return Err(AxityError::ty("field type mismatch", span.clone())); }
+                let (flds, _) = classes.get(cname).ok_or_else(|| AxityError::ty("unknown class", span.clone()).with_code("AX0308"))?;
+                let ft = flds.get(field).ok_or_else(|| AxityError::ty("unknown field", span.clone()).with_code("AX0308"))?;
+                if !(type_equals(&vt, ft)) { return Err(AxityError::ty("field type mismatch", span.clone()).with_code("AX0302").with_note(&format!("expected {}, found {}", ft, vt))); }
                 Ok(())
             } else if let Type::Obj = ot {
                 Ok(())
             } else if let Type::Any = ot {
                 Ok(())
-            } else { Err(AxityError::ty("member assign target not class", span.clone())) }
+            } else { Err(AxityError::ty("member assign target not class", span.clone()).with_code("AX0310")) }
         }
-        Stmt::Expr(e) => { let _ = check_expr(e, vars, funcs, classes)?; Ok(()) }
-        Stmt::Print{ expr, .. } => { let _ = check_expr(expr, vars, funcs, classes)?; Ok(()) }
-        Stmt::While{ cond, body, span: _ } => {
-            let _ = check_expr(cond, vars, funcs, classes)?;
+        Stmt::Expr(e) => { let _ = check_expr(e, vars, funcs, classes, host, sink); Ok(()) }
+        Stmt::Print{ expr, .. } => { let _ = check_expr(expr, vars, funcs, classes, host, sink); Ok(()) }
+        Stmt::While{ cond, body, .. } => {
+            let _ = check_expr(cond, vars, funcs, classes, host, sink);
             vars.push(HashMap::new());
-            for st in body { check_stmt(st, vars, funcs, classes)?; }
+            for st in body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
             Ok(())
         }
         Stmt::DoWhile{ body, cond, .. } => {
             vars.push(HashMap::new());
-            for st in body { check_stmt(st, vars, funcs, classes)?; }
+            for st in body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
-            let _ = check_expr(cond, vars, funcs, classes)?;
+            let _ = check_expr(cond, vars, funcs, classes, host, sink);
             Ok(())
         }
         Stmt::ForC{ init, cond, post, body, .. } => {
             vars.push(HashMap::new());
-            if let Some(st) = init { check_stmt(&*st, vars, funcs, classes)?; }
-            if let Some(c) = cond { let _ = check_expr(c, vars, funcs, classes)?; }
-            if let Some(pe) = post { check_stmt(&*pe, vars, funcs, classes)?; }
-            for st in body { check_stmt(st, vars, funcs, classes)?; }
+            if let Some(st) = init { check_stmt(&*st, vars, funcs, classes, host, sink); }
+            if let Some(c) = cond { let _ = check_expr(c, vars, funcs, classes, host, sink); }
+            if let Some(pe) = post { check_stmt(&*pe, vars, funcs, classes, host, sink); }
+            for st in body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
             Ok(())
         }
-        Stmt::ForEach{ var, collection, body, span: _ } => {
-            let ct = check_expr(collection, vars, funcs, classes)?;
+        Stmt::ForEach{ var, collection, body, .. } => {
+            let ct = check_expr(collection, vars, funcs, classes, host, sink);
             vars.push(HashMap::new());
             match ct {
                 Type::Array(inner) => { vars.last_mut().unwrap().insert(var.clone(), *inner.clone()); }
                 Type::Map(_inner) => { vars.last_mut().unwrap().insert(var.clone(), Type::String); }
-                _ => { return Err(AxityError::ty("foreach expects array or map", span_of_expr(collection))); }
+                Type::Error => { vars.last_mut().unwrap().insert(var.clone(), Type::Error); }
+                _ => { return Err(AxityError::ty("foreach expects array or map", span_of_expr(collection)).with_code("AX0310")); }
             }
-            for st in body { check_stmt(st, vars, funcs, classes)?; }
+            for st in body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
             Ok(())
         }
         Stmt::If{ cond, then_body, else_body, span: _ } => {
-            let _ = check_expr(cond, vars, funcs, classes)?;
+            let _ = check_expr(cond, vars, funcs, classes, host, sink);
             vars.push(HashMap::new());
-            for st in then_body { check_stmt(st, vars, funcs, classes)?; }
+            for st in then_body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
             vars.push(HashMap::new());
-            for st in else_body { check_stmt(st, vars, funcs, classes)?; }
+            for st in else_body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
             Ok(())
         }
-        Stmt::Return{ expr, .. } => { let _ = check_expr(expr, vars, funcs, classes)?; Ok(()) }
-        Stmt::Retry(_) => Ok(()),
-        Stmt::Throw{ expr, .. } => { let _ = check_expr(expr, vars, funcs, classes)?; Ok(()) }
-        Stmt::Try{ body, catch_name, catch_body, .. } => {
-            vars.push(HashMap::new());
-            for st in body { check_stmt(st, vars, funcs, classes)?; }
-            vars.pop();
+        Stmt::Return{ expr, .. } => { let _ = check_expr(expr, vars, funcs, classes, host, sink); Ok(()) }
+        Stmt::Retry(_) | Stmt::Break{ .. } | Stmt::Continue{ .. } => Ok(()),
+        Stmt::Throw{ expr, .. } => { let _ = check_expr(expr, vars, funcs, classes, host, sink); Ok(()) }
+        Stmt::Rethrow(_) => Ok(()),
+        Stmt::Try{ body, catches, finally, retry, .. } => {
+            for e in retry.attempts.iter().chain(&retry.backoff) {
+                let t = check_expr(e, vars, funcs, classes, host, sink);
+                if !type_equals(&t, &Type::Int) { return Err(AxityError::ty("try options must be int", e.span().clone()).with_code("AX0302").with_note(&format!("expected int, found {}", t))); }
+            }
             vars.push(HashMap::new());
-            vars.last_mut().unwrap().insert(catch_name.clone(), Type::Obj);
-            for st in catch_body { check_stmt(st, vars, funcs, classes)?; }
+            for st in body { check_stmt(st, vars, funcs, classes, host, sink); }
             vars.pop();
+            for c in catches {
+                // An unknown class was already reported by `check_control`.
+                let ty = match &c.ty { Type::Class(n) if !classes.contains_key(n) => Type::Error, t => t.clone() };
+                vars.push(HashMap::new());
+                vars.last_mut().unwrap().insert(c.name.clone(), ty);
+                for st in &c.body { check_stmt(st, vars, funcs, classes, host, sink); }
+                vars.pop();
+            }
+            if let Some(fin) = finally {
+                vars.push(HashMap::new());
+                for st in fin { check_stmt(st, vars, funcs, classes, host, sink); }
+                vars.pop();
+            }
             Ok(())
         }
         Stmt::Match{ expr, arms, default: _, span: _ } => {
-            let et = check_expr(expr, vars, funcs, classes)?;
+            let et = check_expr(expr, vars, funcs, classes, host, sink);
             for arm in arms {
                 let pt = match arm.pat {
                     Pattern::PInt(_) => Type::Int,
@@ -142,7 +233,7 @@ fn check_stmt(s: &Stmt, vars: &mut Vec<HashMap<String, Type>>, funcs: &HashMap<S
                     Pattern::PBool(_) => Type::Bool,
                 };
                 vars.push(HashMap::new());
-                for st in &arm.body { check_stmt(st, vars, funcs, classes)?; }
+                for st in &arm.body { check_stmt(st, vars, funcs, classes, host, sink); }
                 vars.pop();
             }
             Ok(())
@@ -150,18 +241,145 @@ fn check_stmt(s: &Stmt, vars: &mut Vec<HashMap<String, Type>>, funcs: &HashMap<S
     }
 }
 
-fn check_expr(e: &Expr, vars: &Vec<HashMap<String, Type>>, funcs: &HashMap<String,(Vec<Type>,Type,Span)>, classes: &HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>) -> Result<Type, AxityError> {
+// Whether running `body` always leaves the function through a `return` or `throw`. Both branches of an
+// `if`, every `match` arm including the default, and a `try` body with all of its catch blocks (or its
+// `finally`) must leave; a loop body may run zero times or be left with `break`, so loops never count.
+fn returns(body: &[Stmt]) -> bool {
+    body.iter().any(|s| match s {
+        Stmt::Return{..} | Stmt::Throw{..} | Stmt::Rethrow(_) => true,
+        Stmt::If{ then_body, else_body, .. } => returns(then_body) && returns(else_body),
+        Stmt::Match{ arms, default, .. } => default.as_deref().is_some_and(returns) && arms.iter().all(|a| returns(&a.body)),
+        Stmt::Try{ body, catches, finally, .. } => finally.as_deref().is_some_and(returns) || (returns(body) && catches.iter().all(|c| returns(&c.body))),
+        _ => false,
+    })
+}
+
+// What `check_control` needs besides the block it walks. `typed` is false inside function, method
+// and lambda bodies, which the type checker does not visit, so calls to builtins the sandbox
+// denies are reported here instead.
+#[derive(Clone, Copy)]
+struct ControlCx<'a> {
+    classes: &'a HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>,
+    funcs: &'a HashMap<String,(Vec<Type>,Type,Span)>,
+    host: &'a HostEnv,
+    typed: bool,
+}
+
+// Function bodies are not type checked, but a `throw;` outside any catch block, a `retry` outside
+// the catch block of a `try (attempts: n)`, a `break` or `continue` with no loop (or no loop of
+// that label) around it, a catch clause naming an unknown class, or a call to a builtin the
+// sandbox denies is reported wherever it appears.
+// `retry` belongs to the innermost catch block around it; `loops` holds the enclosing loop labels,
+// and a lambda body starts again with none.
+fn check_control(body: &[Stmt], in_catch: bool, can_retry: bool, loops: &mut Vec<Option<String>>, cx: &ControlCx, sink: &mut Sink) {
+    for s in body {
+        for e in stmt_exprs(s) { check_nested(e, cx, sink); }
+        match s {
+            Stmt::Rethrow(sp) if !in_catch => sink.errors.push(AxityError::ty("`throw;` outside a catch block", sp.clone()).with_code("AX0313").with_note("a bare `throw;` re-raises the exception its catch block is handling")),
+            Stmt::Retry(sp) if !can_retry => sink.errors.push(AxityError::ty("`retry` outside a catch block of a `try` with attempts", sp.clone()).with_code("AX0314").with_note("`retry` re-runs the body of a `try (attempts: n)` from its catch block; use `continue` to skip to the next loop iteration")),
+            Stmt::Break{ label, span } | Stmt::Continue{ label, span } => {
+                let word = if matches!(s, Stmt::Break{ .. }) { "break" } else { "continue" };
+                match label {
+                    _ if loops.is_empty() => sink.errors.push(AxityError::ty(&format!("`{}` outside a loop", word), span.clone()).with_code("AX0315")),
+                    Some(l) if !loops.iter().any(|x| x.as_deref() == Some(l)) => sink.errors.push(AxityError::ty(&format!("unknown loop label `{}`", l), span.clone()).with_code("AX0315").with_note(&format!("no enclosing loop is labelled `{}:`", l))),
+                    _ => {}
+                }
+            }
+            Stmt::Try{ body, catches, finally, retry, .. } => {
+                check_control(body, in_catch, can_retry, loops, cx, sink);
+                for c in catches {
+                    if let Type::Class(n) = &c.ty {
+                        if !cx.classes.contains_key(n) { sink.errors.push(AxityError::ty("unknown class", c.span.clone()).with_code("AX0308").with_note(&format!("`{}` is not a class", n))); }
+                    }
+                    check_control(&c.body, true, retry.attempts.is_some(), loops, cx, sink);
+                }
+                if let Some(fin) = finally { check_control(fin, in_catch, can_retry, loops, cx, sink); }
+            }
+            Stmt::While{ label, body, .. } | Stmt::DoWhile{ label, body, .. } | Stmt::ForC{ label, body, .. } | Stmt::ForEach{ label, body, .. } => {
+                loops.push(label.clone());
+                check_control(body, in_catch, can_retry, loops, cx, sink);
+                loops.pop();
+            }
+            Stmt::If{ then_body, else_body, .. } => { check_control(then_body, in_catch, can_retry, loops, cx, sink); check_control(else_body, in_catch, can_retry, loops, cx, sink); }
+            Stmt::Match{ arms, default, .. } => {
+                for arm in arms { check_control(&arm.body, in_catch, can_retry, loops, cx, sink); }
+                if let Some(d) = default { check_control(d, in_catch, can_retry, loops, cx, sink); }
+            }
+            _ => {}
+        }
+    }
+}
+
+// The expressions a statement holds directly; nested blocks are walked by `check_control` itself.
+fn stmt_exprs(s: &Stmt) -> Vec<&Expr> {
+    match s {
+        Stmt::Let{ init: e, .. } | Stmt::Assign{ expr: e, .. } | Stmt::Print{ expr: e, .. } | Stmt::Expr(e)
+        | Stmt::Throw{ expr: e, .. } | Stmt::Return{ expr: e, .. } | Stmt::While{ cond: e, .. } | Stmt::DoWhile{ cond: e, .. }
+        | Stmt::ForEach{ collection: e, .. } | Stmt::If{ cond: e, .. } | Stmt::Match{ expr: e, .. } => vec![e],
+        Stmt::MemberAssign{ object, expr, .. } => vec![object, expr],
+        Stmt::Try{ retry, .. } => retry.attempts.iter().chain(&retry.backoff).collect(),
+        Stmt::ForC{ init, cond, post, .. } => {
+            let mut v: Vec<&Expr> = cond.iter().collect();
+            for st in init.iter().chain(post) { v.extend(stmt_exprs(st)); }
+            v
+        }
+        Stmt::Retry(_) | Stmt::Rethrow(_) | Stmt::Break{ .. } | Stmt::Continue{ .. } => Vec::new(),
+    }
+}
+
+// A lambda body is a function of its own: no loop or catch block around the lambda reaches into it.
+fn check_nested(e: &Expr, cx: &ControlCx, sink: &mut Sink) {
+    match e {
+        Expr::Int(..) | Expr::Flt(..) | Expr::Str(..) | Expr::Bool(..) | Expr::Var(..) => {}
+        Expr::Lambda{ body, .. } => check_control(body, false, false, &mut Vec::new(), &ControlCx{ typed: false, ..*cx }, sink),
+        Expr::Call{ name, args, span } => {
+            if !cx.typed && cx.host.denied.contains(name) && !cx.funcs.contains_key(name) {
+                sink.errors.push(AxityError::ty(&format!("'{}' is disabled by the runtime sandbox", name), span.clone()).with_code("AX0312"));
+            }
+            for x in args { check_nested(x, cx, sink) }
+        }
+        Expr::ArrayLit(items, _) | Expr::New(_, items, _) => for x in items { check_nested(x, cx, sink) },
+        Expr::ObjLit(fields, _) => for (_, x) in fields { check_nested(x, cx, sink) },
+        Expr::Member{ object: x, .. } | Expr::UnaryNot{ expr: x, .. } | Expr::UnaryNeg{ expr: x, .. } | Expr::UnaryBitNot{ expr: x, .. } => check_nested(x, cx, sink),
+        Expr::Index{ array: a, index: b, .. } | Expr::Binary{ left: a, right: b, .. } => { check_nested(a, cx, sink); check_nested(b, cx, sink); }
+        Expr::Cond{ cond, then_expr, else_expr, .. } => for x in [cond, then_expr, else_expr] { check_nested(x, cx, sink) },
+        Expr::MethodCall{ object: callee, args, .. } | Expr::CallCallee{ callee, args, .. } => {
+            check_nested(callee, cx, sink);
+            for a in args { check_nested(a, cx, sink); }
+        }
+    }
+}
+
+// Initialisers and assigned values are otherwise not held to the variable's type, but a conditional
+// expression's unified branch type is.
+fn cond_matches(e: &Expr, t: &Type, declared: &Type, sink: &mut Sink) {
+    if matches!(e, Expr::Cond{ .. }) && !type_equals(declared, t) {
+        sink.errors.push(AxityError::ty("conditional expression does not match the variable's type", e.span().clone()).with_code("AX0302").with_note(&format!("expected {}, found {}", declared, t)));
+    }
+}
+
+// A failing expression is reported once and typed `Type::Error`, which enclosing expressions accept silently.
+fn check_expr(e: &Expr, vars: &Vec<HashMap<String, Type>>, funcs: &HashMap<String,(Vec<Type>,Type,Span)>, classes: &HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>, host: &HostEnv, sink: &mut Sink) -> Type {
+    let before = sink.poisoned;
+    match check_expr_at(e, vars, funcs, classes, host, sink) {
+        Ok(Type::Error) => { sink.poisoned += 1; Type::Error }
+        Ok(t) => t,
+        Err(err) => { sink.report(err, before); Type::Error }
+    }
+}
+
+fn check_expr_at(e: &Expr, vars: &Vec<HashMap<String, Type>>, funcs: &HashMap<String,(Vec<Type>,Type,Span)>, classes: &HashMap<String,(HashMap<String,Type>,HashMap<String,(Vec<Type>,Type)>)>, host: &HostEnv, sink: &mut Sink) -> Result<Type, AxityError> {
     match e {
         Expr::Int(_, _) => Ok(Type::Int),
         Expr::Flt(_, _) => Ok(Type::Flt),
         Expr::Str(_, _) => Ok(Type::String),
         Expr::Bool(_, _) => Ok(Type::Bool),
         Expr::ArrayLit(elems, sp) => {
-            if elems.is_empty() { return Err(AxityError::ty("empty array literal needs type context", sp.clone())); }
-            let first = check_expr(&elems[0], vars, funcs, classes)?;
+            if elems.is_empty() { return Err(AxityError::ty("empty array literal needs type context", sp.clone()).with_code("AX0311")); }
+            let first = check_expr(&elems[0], vars, funcs, classes, host, sink);
             for el in elems.iter().skip(1) {
-                let et = check_expr(el, vars, funcs, classes)?;
-                if !(type_equals(&et, &first)) { return Err(AxityError::ty("array literal elements must match", sp.clone())); }
+                let et = check_expr(el, vars, funcs, classes, host, sink);
+                if !(type_equals(&et, &first)) { return Err(AxityError::ty("array literal elements must match", sp.clone()).with_code("AX0302").with_label(elems[0].span(), &format!("first element is {}", first)).with_note(&format!("expected {}, found {}", first, et))); }
             }
             Ok(Type::Array(Box::new(first)))
         }
@@ -170,10 +388,10 @@ fn check_expr(e: &Expr, vars: &Vec<HashMap<String, Type>>, funcs: &HashMap<Strin
             let arg_tys = params.iter().map(|p| p.ty.clone()).collect::<Vec<_>>();
             Ok(Type::Fn(arg_tys, Box::new(ret.clone())))
         }
-        Expr::Var(name, sp) => lookup_var(name, vars).ok_or_else(|| AxityError::ty("undefined variable", sp.clone())),
+        Expr::Var(name, sp) => lookup_var(name, vars).ok_or_else(|| AxityError::ty("undefined variable", sp.clone()).with_code("AX0301")),
         Expr::Binary{ left, right, op, .. } => {
-            let lt = check_expr(left, vars, funcs, classes)?;
-            let rt = check_expr(right, vars, funcs, classes)?;
+            let lt = check_expr(left, vars, funcs, classes, host, sink);
+            let rt = check_expr(right, vars, funcs, classes, host, sink);
             match op {
                 BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod | BinOp::BitAnd | BinOp::BitOr | BinOp::BitXor | BinOp::Shl | BinOp::Shr => {
                     if *op == BinOp::Add && lt==Type::String && rt==Type::String { Ok(Type::String) }
@@ -184,269 +402,304 @@ fn check_expr(e: &Expr, vars: &Vec<HashMap<String, Type>>, funcs: &HashMap<Strin
                     Ok(Type::Int)
                 }
                 BinOp::And | BinOp::Or => {
-                    if lt==Type::Bool && rt==Type::Bool { Ok(Type::Bool) } else { Err(AxityError::ty("logical operators require bool", span_of_expr(e))) }
+                    if lt==Type::Bool && rt==Type::Bool { Ok(Type::Bool) } else { Err(AxityError::ty("logical operators require bool", span_of_expr(e)).with_code("AX0309").with_note(&format!("found {} and {}", lt, rt))) }
                 }
             }
         }
+        Expr::Cond{ cond, then_expr, else_expr, .. } => {
+            let _ = check_expr(cond, vars, funcs, classes, host, sink);
+            let tt = check_expr(then_expr, vars, funcs, classes, host, sink);
+            let et = check_expr(else_expr, vars, funcs, classes, host, sink);
+            if !type_equals(&tt, &et) { return Err(AxityError::ty("conditional branches must match", else_expr.span().clone()).with_code("AX0302").with_label(then_expr.span(), &format!("first branch is {}", tt)).with_note(&format!("expected {}, found {}", tt, et))); }
+            // A branch typed `any` makes the whole expression `any`.
+            Ok(match (&tt, &et) {
+                (Type::Error, _) | (_, Type::Error) => Type::Error,
+                (Type::Any, _) | (_, Type::Any) => Type::Any,
+                _ => tt,
+            })
+        }
         Expr::UnaryNot{ expr, span } => {
-            let t = check_expr(expr, vars, funcs, classes)?;
-            if t != Type::Bool { return Err(AxityError::ty("! requires bool", span.clone())); }
+            let t = check_expr(expr, vars, funcs, classes, host, sink);
+            if t != Type::Bool { return Err(AxityError::ty("! requires bool", span.clone()).with_code("AX0309").with_note(&format!("found {}", t))); }
             Ok(Type::Bool)
         }
         Expr::UnaryNeg{ expr, .. } => {
-            let t = check_expr(expr, vars, funcs, classes)?;
+            let t = check_expr(expr, vars, funcs, classes, host, sink);
             Ok(t)
         }
         Expr::UnaryBitNot{ expr, span } => {
-            let t = check_expr(expr, vars, funcs, classes)?;
-            if t != Type::Int { return Err(AxityError::ty("~ requires int", span.clone())); }
+            let t = check_expr(expr, vars, funcs, classes, host, sink);
+            if t != Type::Int { return Err(AxityError::ty("~ requires int", span.clone()).with_code("AX0309")); }
             Ok(Type::Int)
         }
         Expr::New(name, _args, _) => Ok(Type::Class(name.clone())),
         Expr::Member{ object, field, span } => {
-            let ot = check_expr(object, vars, funcs, classes)?;
+            let ot = check_expr(object, vars, funcs, classes, host, sink);
             if let Type::Class(ref cname) = ot {
-                let (flds, _) = classes.get(cname).ok_or_else(|| AxityError::ty("unknown class", span.clone()))?;
-                flds.get(field).cloned().ok_or_else(|| AxityError::ty("unknown field", span.clone()))
+                let (flds, _) = classes.get(cname).ok_or_else(|| AxityError::ty("unknown class", span.clone()).with_code("AX0308"))?;
+                flds.get(field).cloned().ok_or_else(|| AxityError::ty("unknown field", span.clone()).with_code("AX0308"))
             } else if let Type::Obj = ot {
                 Ok(Type::Obj)
             } else if let Type::Any = ot {
                 Ok(Type::Any)
-            } else { Err(AxityError::ty("member access target not class", span.clone())) }
+            } else { Err(AxityError::ty("member access target not class", span.clone()).with_code("AX0310")) }
         }
         Expr::Index{ array, index, span } => {
-            let at = check_expr(array, vars, funcs, classes)?;
-            let it = check_expr(index, vars, funcs, classes)?;
-            if it != Type::Int { return Err(AxityError::ty("array index must be int", span.clone())); }
-            if let Type::Array(inner) = at { Ok(*inner.clone()) } else { Err(AxityError::ty("indexing non-array", span.clone())) }
+            let at = check_expr(array, vars, funcs, classes, host, sink);
+            let it = check_expr(index, vars, funcs, classes, host, sink);
+            if it != Type::Int { return Err(AxityError::ty("array index must be int", span.clone()).with_code("AX0309")); }
+            if let Type::Array(inner) = at { Ok(*inner.clone()) } else { Err(AxityError::ty("indexing non-array", span.clone()).with_code("AX0310")) }
         }
         Expr::CallCallee{ callee, args: _, span } => {
-            let ct = check_expr(callee, vars, funcs, classes)?;
+            let ct = check_expr(callee, vars, funcs, classes, host, sink);
             match ct {
                 Type::Fn(_p, ret) => Ok(*ret.clone()),
-                _ => Err(AxityError::ty("callee is not function", span.clone()))
+                _ => Err(AxityError::ty("callee is not function", span.clone()).with_code("AX0310"))
             }
         }
         Expr::MethodCall{ object, name, args: _, span } => {
-            let ot = check_expr(object, vars, funcs, classes)?;
+            let ot = check_expr(object, vars, funcs, classes, host, sink);
             if let Type::Class(ref cname) = ot {
-                let (_, meths) = classes.get(cname).ok_or_else(|| AxityError::ty("unknown class", span.clone()))?;
-                let sig = meths.get(name).ok_or_else(|| AxityError::ty("unknown method", span.clone()))?;
+                let (_, meths) = classes.get(cname).ok_or_else(|| AxityError::ty("unknown class", span.clone()).with_code("AX0308"))?;
+                let sig = meths.get(name).ok_or_else(|| AxityError::ty("unknown method", span.clone()).with_code("AX0308"))?;
                 Ok(sig.1.clone())
-            } else { Err(AxityError::ty("method call target not class", span.clone())) }
+            } else { Err(AxityError::ty("method call target not class", span.clone()).with_code("AX0310")) }
         }
         Expr::Call{ name, args, span } => {
+            if host.denied.contains(name) && !funcs.contains_key(name) {
+                return Err(AxityError::ty(&format!("'{}' is disabled by the runtime sandbox", name), span.clone()).with_code("AX0312"));
+            }
             if name == "len" {
-                if args.len() != 1 { return Err(AxityError::ty("len expects one argument", span.clone())); }
-                let at = check_expr(&args[0], vars, funcs, classes)?;
+                if args.len() != 1 { return Err(AxityError::ty("len expects one argument", span.clone()).with_code("AX0304")); }
+                let at = check_expr(&args[0], vars, funcs, classes, host, sink);
                 match at {
                     Type::Array(_) => Ok(Type::Int),
                     Type::String => Ok(Type::Int),
-                    _ => Err(AxityError::ty("len expects array or string", span.clone()))
+                    _ => Err(AxityError::ty("len expects array or string", span.clone()).with_code("AX0305").with_note(&format!("found {}", at)))
                 }
             } else if name == "slice" {
-                if args.len() != 3 { return Err(AxityError::ty("slice expects (array, start, len)", span.clone())); }
-                let at = check_expr(&args[0], vars, funcs, classes)?;
-                let st = check_expr(&args[1], vars, funcs, classes)?;
-                let lt = check_expr(&args[2], vars, funcs, classes)?;
-                if st != Type::Int || lt != Type::Int { return Err(AxityError::ty("slice indices must be int", span.clone())); }
-                if let Type::Array(inner) = at { Ok(Type::Array(inner.clone())) } else { Err(AxityError::ty("slice expects array", span.clone())) }
+                if args.len() != 3 { return Err(AxityError::ty("slice expects (array, start, len)", span.clone()).with_code("AX0304")); }
+                let at = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let st = check_expr(&args[1], vars, funcs, classes, host, sink);
+                let lt = check_expr(&args[2], vars, funcs, classes, host, sink);
+                if st != Type::Int || lt != Type::Int { return Err(AxityError::ty("slice indices must be int", span.clone()).with_code("AX0305")); }
+                if let Type::Array(inner) = at { Ok(Type::Array(inner.clone())) } else { Err(AxityError::ty("slice expects array", span.clone()).with_code("AX0305")) }
             } else if name == "range" {
-                if args.len() != 2 { return Err(AxityError::ty("range expects (start, end)", span.clone())); }
-                let st = check_expr(&args[0], vars, funcs, classes)?;
-                let et = check_expr(&args[1], vars, funcs, classes)?;
-                if st != Type::Int || et != Type::Int { return Err(AxityError::ty("range args must be int", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("range expects (start, end)", span.clone()).with_code("AX0304")); }
+                let st = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let et = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if st != Type::Int || et != Type::Int { return Err(AxityError::ty("range args must be int", span.clone()).with_code("AX0305")); }
                 Ok(Type::Array(Box::new(Type::Int)))
             } else if name == "map_remove" {
-                if args.len() != 2 { return Err(AxityError::ty("map_remove expects (map, key)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                let kt = check_expr(&args[1], vars, funcs, classes)?;
-                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone())); }
-                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 2 { return Err(AxityError::ty("map_remove expects (map, key)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let kt = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone()).with_code("AX0305")); }
+                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "map_clear" {
-                if args.len() != 1 { return Err(AxityError::ty("map_clear expects (map)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 1 { return Err(AxityError::ty("map_clear expects (map)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "map_size" {
-                if args.len() != 1 { return Err(AxityError::ty("map_size expects (map)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 1 { return Err(AxityError::ty("map_size expects (map)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "string_replace" {
-                if args.len() != 3 { return Err(AxityError::ty("string_replace expects (s, from, to)", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?;
-                let t1 = check_expr(&args[1], vars, funcs, classes)?;
-                let t2 = check_expr(&args[2], vars, funcs, classes)?;
-                if t0 != Type::String || t1 != Type::String || t2 != Type::String { return Err(AxityError::ty("string_replace arg types", span.clone())); }
+                if args.len() != 3 { return Err(AxityError::ty("string_replace expects (s, from, to)", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let t1 = check_expr(&args[1], vars, funcs, classes, host, sink);
+                let t2 = check_expr(&args[2], vars, funcs, classes, host, sink);
+                if t0 != Type::String || t1 != Type::String || t2 != Type::String { return Err(AxityError::ty("string_replace expects (str, str, str)", span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "string_split" {
-                if args.len() != 2 { return Err(AxityError::ty("string_split expects (s, sep)", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?;
-                let t1 = check_expr(&args[1], vars, funcs, classes)?;
-                if t0 != Type::String || t1 != Type::String { return Err(AxityError::ty("string_split arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("string_split expects (s, sep)", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let t1 = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if t0 != Type::String || t1 != Type::String { return Err(AxityError::ty("string_split expects (str, str)", span.clone()).with_code("AX0305")); }
                 Ok(Type::Array(Box::new(Type::String)))
             } else if name == "read_file" {
-                if args.len() != 1 { return Err(AxityError::ty("read_file expects path", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("path must be string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("read_file expects path", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("path must be string", span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "write_file" {
-                if args.len() != 2 { return Err(AxityError::ty("write_file expects (path, content)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String || check_expr(&args[1], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("write_file arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("write_file expects (path, content)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("write_file expects (path: str, content: str)", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "mkdir" {
-                if args.len() != 1 { return Err(AxityError::ty("mkdir expects path", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("path must be string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("mkdir expects path", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("path must be string", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "exists" {
-                if args.len() != 1 { return Err(AxityError::ty("exists expects path", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("path must be string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("exists expects path", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("path must be string", span.clone()).with_code("AX0305")); }
                 Ok(Type::Bool)
             } else if name == "read_json" || name == "read_toml" || name == "read_env" {
-                if args.len() != 1 { return Err(AxityError::ty("read_* expects path", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("path must be string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("read_* expects path", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("path must be string", span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "write_json" || name == "write_toml" || name == "write_env" {
-                if args.len() != 2 { return Err(AxityError::ty("write_* expects (path, content)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String || check_expr(&args[1], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("write_* arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("write_* expects (path, content)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty(&format!("{} expects (path: str, content: str)", name), span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "input" {
-                if args.len() > 1 { return Err(AxityError::ty("input expects zero or one argument", span.clone())); }
+                if args.len() > 1 { return Err(AxityError::ty("input expects zero or one argument", span.clone()).with_code("AX0304")); }
                 if let Some(a0) = args.get(0) {
-                    if check_expr(a0, vars, funcs, classes)? != Type::String { return Err(AxityError::ty("prompt must be string", span.clone())); }
+                    if check_expr(a0, vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("prompt must be string", span.clone()).with_code("AX0305")); }
                 }
                 Ok(Type::Any)
+            } else if name == "input_all" {
+                if !args.is_empty() { return Err(AxityError::ty("input_all expects no arguments", span.clone()).with_code("AX0304")); }
+                Ok(Type::String)
+            } else if name == "attempt" && !funcs.contains_key(name) && lookup_var(name, vars).is_none() {
+                if !args.is_empty() { return Err(AxityError::ty("attempt expects no arguments", span.clone()).with_code("AX0304")); }
+                Ok(Type::Int)
+            } else if name == "input_eof" {
+                if !args.is_empty() { return Err(AxityError::ty("input_eof expects no arguments", span.clone()).with_code("AX0304")); }
+                Ok(Type::Bool)
             } else if name == "json_get" || name == "toml_get" || name == "env_get" {
-                if args.len() != 2 { return Err(AxityError::ty("get expects (content, key)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String || check_expr(&args[1], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("get arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("get expects (content, key)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty(&format!("{} expects (content: str, key: str)", name), span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "json_set" || name == "toml_set" || name == "env_set" {
-                if args.len() != 3 { return Err(AxityError::ty("set expects (content, key, value)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String || check_expr(&args[1], vars, funcs, classes)? != Type::String || check_expr(&args[2], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("set arg types", span.clone())); }
+                if args.len() != 3 { return Err(AxityError::ty("set expects (content, key, value)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::String || check_expr(&args[2], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty(&format!("{} expects (content: str, key: str, value: str)", name), span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "push" {
-                if args.len() != 2 { return Err(AxityError::ty("push expects array and value", span.clone())); }
-                let at = check_expr(&args[0], vars, funcs, classes)?;
+                if args.len() != 2 { return Err(AxityError::ty("push expects array and value", span.clone()).with_code("AX0304")); }
+                let at = check_expr(&args[0], vars, funcs, classes, host, sink);
                 if let Type::Array(inner) = at {
-                    let vt = check_expr(&args[1], vars, funcs, classes)?;
-                    if !(type_equals(&vt, &*inner)) { return Err(AxityError::ty("push value type mismatch", span.clone())); }
+                    let vt = check_expr(&args[1], vars, funcs, classes, host, sink);
+                    if !(type_equals(&vt, &*inner)) { return Err(AxityError::ty("push value type mismatch", span.clone()).with_code("AX0302").with_note(&format!("expected {}, found {}", inner, vt))); }
                     Ok(Type::Int)
-                } else { Err(AxityError::ty("push expects array", span.clone())) }
+                } else { Err(AxityError::ty("push expects array", span.clone()).with_code("AX0305")) }
             } else if name == "pop" {
-                if args.len() != 1 { return Err(AxityError::ty("pop expects array", span.clone())); }
-                let at = check_expr(&args[0], vars, funcs, classes)?;
-                if let Type::Array(inner) = at { Ok(*inner.clone()) } else { Err(AxityError::ty("pop expects array", span.clone())) }
+                if args.len() != 1 { return Err(AxityError::ty("pop expects array", span.clone()).with_code("AX0304")); }
+                let at = check_expr(&args[0], vars, funcs, classes, host, sink);
+                if let Type::Array(inner) = at { Ok(*inner.clone()) } else { Err(AxityError::ty("pop expects array", span.clone()).with_code("AX0305")) }
             } else if name == "set" {
-                if args.len() != 3 { return Err(AxityError::ty("set expects array, index, value", span.clone())); }
-                let at = check_expr(&args[0], vars, funcs, classes)?;
-                let it = check_expr(&args[1], vars, funcs, classes)?;
-                if it != Type::Int { return Err(AxityError::ty("set index must be int", span.clone())); }
+                if args.len() != 3 { return Err(AxityError::ty("set expects array, index, value", span.clone()).with_code("AX0304")); }
+                let at = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let it = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if it != Type::Int { return Err(AxityError::ty("set index must be int", span.clone()).with_code("AX0305")); }
                 if let Type::Array(inner) = at {
-                    let vt = check_expr(&args[2], vars, funcs, classes)?;
-                    if !(type_equals(&vt, &*inner)) { return Err(AxityError::ty("set value type mismatch", span.clone())); }
+                    let vt = check_expr(&args[2], vars, funcs, classes, host, sink);
+                    if !(type_equals(&vt, &*inner)) { return Err(AxityError::ty("set value type mismatch", span.clone()).with_code("AX0302").with_note(&format!("expected {}, found {}", inner, vt))); }
                     Ok(Type::Int)
-                } else { Err(AxityError::ty("set expects array", span.clone())) }
+                } else { Err(AxityError::ty("set expects array", span.clone()).with_code("AX0305")) }
             } else if name == "strlen" {
-                if args.len() != 1 { return Err(AxityError::ty("strlen expects string", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?;
-                if t0 != Type::String { return Err(AxityError::ty("strlen expects string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("strlen expects string", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink);
+                if t0 != Type::String { return Err(AxityError::ty("strlen expects string", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "substr" {
-                if args.len() != 3 { return Err(AxityError::ty("substr expects (string, start, len)", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?; let t1 = check_expr(&args[1], vars, funcs, classes)?; let t2 = check_expr(&args[2], vars, funcs, classes)?;
-                if t0 != Type::String || t1 != Type::Int || t2 != Type::Int { return Err(AxityError::ty("substr arg types", span.clone())); }
+                if args.len() != 3 { return Err(AxityError::ty("substr expects (string, start, len)", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink); let t1 = check_expr(&args[1], vars, funcs, classes, host, sink); let t2 = check_expr(&args[2], vars, funcs, classes, host, sink);
+                if t0 != Type::String || t1 != Type::Int || t2 != Type::Int { return Err(AxityError::ty("substr expects (str, int, int)", span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "index_of" {
-                if args.len() != 2 { return Err(AxityError::ty("index_of expects (string, string)", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?; let t1 = check_expr(&args[1], vars, funcs, classes)?;
-                if t0 != Type::String || t1 != Type::String { return Err(AxityError::ty("index_of arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("index_of expects (string, string)", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink); let t1 = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if t0 != Type::String || t1 != Type::String { return Err(AxityError::ty("index_of expects (str, str)", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "matrix_mul" {
-                if args.len() != 2 { return Err(AxityError::ty("matrix_mul expects (A, B)", span.clone())); }
-                let ta = check_expr(&args[0], vars, funcs, classes)?;
-                let tb = check_expr(&args[1], vars, funcs, classes)?;
+                if args.len() != 2 { return Err(AxityError::ty("matrix_mul expects (A, B)", span.clone()).with_code("AX0304")); }
+                let ta = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let tb = check_expr(&args[1], vars, funcs, classes, host, sink);
                 let is_mat = |t: &Type| -> bool {
                     if let Type::Array(inner) = t {
                         if let Type::Array(_inside) = &**inner { return true; }
                     }
                     false
                 };
-                if !is_mat(&ta) || !is_mat(&tb) { return Err(AxityError::ty("matrix_mul expects arrays of arrays", span.clone())); }
+                if !is_mat(&ta) || !is_mat(&tb) { return Err(AxityError::ty("matrix_mul expects arrays of arrays", span.clone()).with_code("AX0305")); }
                 Ok(Type::Array(Box::new(Type::Array(Box::new(Type::Any)))))
             } else if name == "to_int" {
-                if args.len() != 1 { return Err(AxityError::ty("to_int expects string", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?;
-                if t0 != Type::String { return Err(AxityError::ty("to_int expects string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("to_int expects string", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink);
+                if t0 != Type::String { return Err(AxityError::ty("to_int expects string", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "to_string" {
-                if args.len() != 1 { return Err(AxityError::ty("to_string expects int", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?;
-                if t0 != Type::Int { return Err(AxityError::ty("to_string expects int", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("to_string expects int", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink);
+                if t0 != Type::Int { return Err(AxityError::ty("to_string expects int", span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else if name == "map_new_int" {
-                if args.len() != 0 { return Err(AxityError::ty("map_new_int expects no args", span.clone())); }
+                if args.len() != 0 { return Err(AxityError::ty("map_new_int expects no args", span.clone()).with_code("AX0304")); }
                 Ok(Type::Map(Box::new(Type::Int)))
             } else if name == "map_new_string" {
-                if args.len() != 0 { return Err(AxityError::ty("map_new_string expects no args", span.clone())); }
+                if args.len() != 0 { return Err(AxityError::ty("map_new_string expects no args", span.clone()).with_code("AX0304")); }
                 Ok(Type::Map(Box::new(Type::String)))
             } else if name == "map_set" {
-                if args.len() != 3 { return Err(AxityError::ty("map_set expects (map, key, value)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                let kt = check_expr(&args[1], vars, funcs, classes)?;
-                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone())); }
-                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 3 { return Err(AxityError::ty("map_set expects (map, key, value)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let kt = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone()).with_code("AX0305")); }
+                match mt { Type::Map(_) => Ok(Type::Int), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "map_get" {
-                if args.len() != 2 { return Err(AxityError::ty("map_get expects (map, key)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                let kt = check_expr(&args[1], vars, funcs, classes)?;
-                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone())); }
-                match mt { Type::Map(inner) => Ok(*inner.clone()), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 2 { return Err(AxityError::ty("map_get expects (map, key)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let kt = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone()).with_code("AX0305")); }
+                match mt { Type::Map(inner) => Ok(*inner.clone()), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "map_has" {
-                if args.len() != 2 { return Err(AxityError::ty("map_has expects (map, key)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                let kt = check_expr(&args[1], vars, funcs, classes)?;
-                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone())); }
-                match mt { Type::Map(_) => Ok(Type::Bool), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 2 { return Err(AxityError::ty("map_has expects (map, key)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                let kt = check_expr(&args[1], vars, funcs, classes, host, sink);
+                if kt != Type::String { return Err(AxityError::ty("map key must be string", span.clone()).with_code("AX0305")); }
+                match mt { Type::Map(_) => Ok(Type::Bool), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "map_keys" {
-                if args.len() != 1 { return Err(AxityError::ty("map_keys expects (map)", span.clone())); }
-                let mt = check_expr(&args[0], vars, funcs, classes)?;
-                match mt { Type::Map(_) => Ok(Type::Array(Box::new(Type::String))), _ => Err(AxityError::ty("first arg must be map", span.clone())) }
+                if args.len() != 1 { return Err(AxityError::ty("map_keys expects (map)", span.clone()).with_code("AX0304")); }
+                let mt = check_expr(&args[0], vars, funcs, classes, host, sink);
+                match mt { Type::Map(_) => Ok(Type::Array(Box::new(Type::String))), _ => Err(AxityError::ty("first arg must be map", span.clone()).with_code("AX0305")) }
             } else if name == "sin" || name == "cos" || name == "tan" {
-                if args.len() != 1 { return Err(AxityError::ty("trig expects one argument (radians)", span.clone())); }
-                let t0 = check_expr(&args[0], vars, funcs, classes)?;
+                if args.len() != 1 { return Err(AxityError::ty("trig expects one argument (radians)", span.clone()).with_code("AX0304")); }
+                let t0 = check_expr(&args[0], vars, funcs, classes, host, sink);
                 match t0 {
                     Type::Flt | Type::Int => Ok(Type::Flt),
-                    _ => Err(AxityError::ty("trig arg must be flt or int", span.clone()))
+                    _ => Err(AxityError::ty("trig arg must be flt or int", span.clone()).with_code("AX0305"))
                 }
             } else if name == "buffer_new" {
-                if args.len() != 1 { return Err(AxityError::ty("buffer_new expects size", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::Int { return Err(AxityError::ty("size must be int", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("buffer_new expects size", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::Int { return Err(AxityError::ty("size must be int", span.clone()).with_code("AX0305")); }
                 Ok(Type::Buffer)
             } else if name == "buffer_len" {
-                if args.len() != 1 { return Err(AxityError::ty("buffer_len expects buffer", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::Buffer { return Err(AxityError::ty("arg must be buffer", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("buffer_len expects buffer", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::Buffer { return Err(AxityError::ty("arg must be buffer", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "buffer_get" {
-                if args.len() != 2 { return Err(AxityError::ty("buffer_get expects (buffer, index)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::Buffer || check_expr(&args[1], vars, funcs, classes)? != Type::Int { return Err(AxityError::ty("arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("buffer_get expects (buffer, index)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::Buffer || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::Int { return Err(AxityError::ty("buffer_get expects (buffer, int)", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "buffer_set" {
-                if args.len() != 3 { return Err(AxityError::ty("buffer_set expects (buffer, index, byte)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::Buffer || check_expr(&args[1], vars, funcs, classes)? != Type::Int || check_expr(&args[2], vars, funcs, classes)? != Type::Int { return Err(AxityError::ty("arg types", span.clone())); }
+                if args.len() != 3 { return Err(AxityError::ty("buffer_set expects (buffer, index, byte)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::Buffer || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::Int || check_expr(&args[2], vars, funcs, classes, host, sink) != Type::Int { return Err(AxityError::ty("buffer_set expects (buffer, int, int)", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "buffer_push" {
-                if args.len() != 2 { return Err(AxityError::ty("buffer_push expects (buffer, byte)", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::Buffer || check_expr(&args[1], vars, funcs, classes)? != Type::Int { return Err(AxityError::ty("arg types", span.clone())); }
+                if args.len() != 2 { return Err(AxityError::ty("buffer_push expects (buffer, byte)", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::Buffer || check_expr(&args[1], vars, funcs, classes, host, sink) != Type::Int { return Err(AxityError::ty("buffer_push expects (buffer, int)", span.clone()).with_code("AX0305")); }
                 Ok(Type::Int)
             } else if name == "buffer_from_string" {
-                if args.len() != 1 { return Err(AxityError::ty("buffer_from_string expects string", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::String { return Err(AxityError::ty("arg must be string", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("buffer_from_string expects string", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::String { return Err(AxityError::ty("arg must be string", span.clone()).with_code("AX0305")); }
                 Ok(Type::Buffer)
             } else if name == "buffer_to_string" {
-                if args.len() != 1 { return Err(AxityError::ty("buffer_to_string expects buffer", span.clone())); }
-                if check_expr(&args[0], vars, funcs, classes)? != Type::Buffer { return Err(AxityError::ty("arg must be buffer", span.clone())); }
+                if args.len() != 1 { return Err(AxityError::ty("buffer_to_string expects buffer", span.clone()).with_code("AX0304")); }
+                if check_expr(&args[0], vars, funcs, classes, host, sink) != Type::Buffer { return Err(AxityError::ty("arg must be buffer", span.clone()).with_code("AX0305")); }
                 Ok(Type::String)
             } else {
                 if let Some(sig) = funcs.get(name) {
-                    if args.len() != sig.0.len() { return Err(AxityError::ty("argument count mismatch", span.clone())); }
+                    if args.len() != sig.0.len() {
+                        return Err(AxityError::ty("argument count mismatch", span.clone()).with_code("AX0303")
+                            .with_label(&sig.2, &format!("`{}` defined here", name))
+                            .with_note(&format!("expected {} argument(s), found {}", sig.0.len(), args.len())));
+                    }
+                    Ok(sig.1.clone())
+                } else if let Some(sig) = host.natives.get(name) {
+                    if args.len() != sig.0.len() { return Err(AxityError::ty("argument count mismatch", span.clone()).with_code("AX0303").with_note(&format!("expected {} argument(s), found {}", sig.0.len(), args.len()))); }
+                    for (a, pt) in args.iter().zip(sig.0.iter()) {
+                        let at = check_expr(a, vars, funcs, classes, host, sink);
+                        if !(type_equals(&at, pt)) { return Err(AxityError::ty("native argument type mismatch", span_of_expr(a)).with_code("AX0302").with_note(&format!("expected {}, found {}", pt, at))); }
+                    }
                     Ok(sig.1.clone())
                 } else if let Some(vt) = lookup_var(name, vars) {
                     match vt {
@@ -467,30 +720,9 @@ fn lookup_var(name: &str, vars: &Vec<HashMap<String, Type>>) -> Option<Type> {
 }
 
 fn type_equals(a: &Type, b: &Type) -> bool {
-    if matches!(a, Type::Any) || matches!(b, Type::Any) { return true; }
+    if matches!(a, Type::Any | Type::Error) || matches!(b, Type::Any | Type::Error) { return true; }
     a == b
 }
 
-fn span_of_expr(e: &Expr) -> Span {
-    match e {
-        Expr::Int(_, s) => s.clone(),
-        Expr::Flt(_, s) => s.clone(),
-        Expr::Str(_, s) => s.clone(),
-        Expr::Bool(_, s) => s.clone(),
-        Expr::ArrayLit(_, s) => s.clone(),
-        Expr::ObjLit(_, s) => s.clone(),
-        Expr::Lambda{ span, .. } => span.clone(),
-        Expr::Var(_, s) => s.clone(),
-        Expr::New(_, _, s) => s.clone(),
-        Expr::Member{ span, .. } => span.clone(),
-        Expr::Index{ span, .. } => span.clone(),
-        Expr::MethodCall{ span, .. } => span.clone(),
-        Expr::UnaryNot{ span, .. } => span.clone(),
-        Expr::UnaryNeg{ span, .. } => span.clone(),
-        Expr::UnaryBitNot{ span, .. } => span.clone(),
-        Expr::Binary{ span, .. } => span.clone(),
-        Expr::Call{ span, .. } => span.clone(),
-        Expr::CallCallee{ span, .. } => span.clone(),
-    }
-}
+fn span_of_expr(e: &Expr) -> Span { e.span().clone() }
 
@@ -11,4 +11,55 @@ pub enum Type {
     Fn(Vec<Type>, Box<Type>),
     Buffer,
     Class(String),
+    // Type of an expression that already failed to check; compatible with everything.
+    Error,
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::String => write!(f, "string"),
+            Type::Flt => write!(f, "flt"),
+            Type::Bool => write!(f, "bool"),
+            Type::Obj => write!(f, "obj"),
+            Type::Any => write!(f, "any"),
+            Type::Array(t) => write!(f, "array<{}>", t),
+            Type::Map(t) => write!(f, "map<{}>", t),
+            Type::Fn(ps, r) => {
+                let ps: Vec<String> = ps.iter().map(|p| p.to_string()).collect();
+                write!(f, "fn({}) -> {}", ps.join(", "), r)
+            }
+            Type::Buffer => write!(f, "buffer"),
+            Type::Class(n) => write!(f, "{}", n),
+            Type::Error => write!(f, "{{error}}"),
+        }
+    }
+}
+
+// Built-in error classes and the fault kind each one stands for; `Error` matches every one of them in a `catch`.
+pub const ERROR_CLASSES: &[(&str, &str)] = &[
+    ("Error", "error"),
+    ("RuntimeError", "runtime"),
+    ("ArithmeticError", "arithmetic"),
+    ("IndexError", "index"),
+    ("NameError", "name"),
+    ("TypeError", "type"),
+    ("IoError", "io"),
+    ("FormatError", "format"),
+    ("ValueError", "value"),
+];
+
+// Every built-in error class has these fields, all strings.
+pub const ERROR_FIELDS: [&str; 3] = ["message", "kind", "code"];
+
+pub fn is_error_class(name: &str) -> bool { ERROR_CLASSES.iter().any(|(c, _)| *c == name) }
+
+pub fn error_class_kind(name: &str) -> Option<&'static str> {
+    ERROR_CLASSES.iter().find(|(c, _)| *c == name).map(|(_, k)| *k)
+}
+
+// Faults of an unknown kind are plain runtime errors.
+pub fn error_class_for(kind: &str) -> &'static str {
+    ERROR_CLASSES.iter().find(|(_, k)| *k == kind).map(|(c, _)| *c).unwrap_or("RuntimeError")
 }
@@ -0,0 +1,42 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use axity::runtime::{Runtime, Value};
+use axity::types::Type;
+use axity::{run_source, run_source_with_runtime};
+
+fn names(err: &axity::AxityError) -> Vec<(String, usize)> {
+    err.details.trace.iter().map(|f| (f.function.clone(), f.span.as_ref().map(|s| s.line).unwrap_or(0))).collect()
+}
+
+#[test]
+fn uncaught_throw_reports_call_chain() {
+    let src = "fn inner(n: int) -> int {\n  throw \"bad\";\n  return 0;\n}\nfn outer(n: int) -> int {\n  return inner(n);\n}\nlet call: any = fn(x: int) -> int { return outer(x); };\nprint(call(1));";
+    let err = run_source(src).unwrap_err();
+    assert_eq!(err.span.as_ref().map(|s| s.line), Some(2));
+    assert_eq!(names(&err), vec![("inner".to_string(), 2), ("outer".to_string(), 6), ("call".to_string(), 8), ("<top level>".to_string(), 9)]);
+    let bt = err.backtrace();
+    assert!(bt.starts_with("stack backtrace:\n  at inner (<input>:2:3)"), "{}", bt);
+}
+
+#[test]
+fn runtime_errors_in_methods_carry_trace() {
+    let src = "class Box {\n  let xs: array<int>;\n  fn get(self: Box, i: int) -> int {\n    let xs: array<int> = self.xs;\n    return xs[i];\n  }\n}\nlet b: Box = new Box;\nb.xs = [1];\nprint(b.get(4));";
+    let err = run_source(src).unwrap_err();
+    assert_eq!(names(&err), vec![("Box.get".to_string(), 5), ("<top level>".to_string(), 10)]);
+    assert_eq!(err.details.trace[0].path.as_deref(), Some("<input>"));
+}
+
+#[test]
+fn natives_can_inspect_active_frames() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let s = seen.clone();
+    let mut rt = Runtime::new();
+    rt.register_fn("where_am_i", vec![], Type::Int, move |rt, _| {
+        *s.borrow_mut() = rt.frames().iter().map(|f| f.name.clone()).collect();
+        Ok(Value::Int(0))
+    });
+    let src = "fn a() -> int { return b(); }\nfn b() -> int { return where_am_i(); }\nprint(a());";
+    run_source_with_runtime(src, &mut rt).unwrap();
+    assert_eq!(*seen.borrow(), vec!["a", "b", "where_am_i"]);
+    assert!(rt.frames().is_empty());
+}
@@ -0,0 +1,68 @@
+use axity::run_source;
+use axity::AxityError;
+
+#[test]
+fn return_inside_if_leaves_the_function() -> Result<(), AxityError> {
+    let src = r#"
+fn sign(n: int) -> int {
+  if n < 0 { return -1; }
+  if n == 0 { return 0; } else { print("positive"); }
+  return 1;
+}
+print(sign(-5));
+print(sign(0));
+print(sign(7));
+"#;
+    let out = run_source(src)?;
+    assert_eq!(out, "-1\n0\npositive\n1\n");
+    Ok(())
+}
+
+#[test]
+fn return_inside_loops_stops_the_loop() -> Result<(), AxityError> {
+    let src = r#"
+fn find(xs: array<int>, t: int) -> int {
+  let i: int = 0;
+  for x in xs { if x == t { return i; } i = i + 1; }
+  return -1;
+}
+fn first_square_over(n: int) -> int {
+  let i: int = 0;
+  while true { i = i + 1; if i * i > n { return i; } }
+  return 0;
+}
+fn count_to(n: int) -> int {
+  for let i: int = 0; i < 100; i++ { if i == n { return i; } }
+  do { return 7; print("unreached"); } while true;
+  return 0;
+}
+print(find([4, 5, 6], 6));
+print(find([4, 5, 6], 9));
+print(first_square_over(10));
+print(count_to(3));
+print(count_to(200));
+"#;
+    let out = run_source(src)?;
+    assert_eq!(out, "2\n-1\n4\n3\n7\n");
+    Ok(())
+}
+
+#[test]
+fn return_inside_match_skips_the_rest_of_the_function() -> Result<(), AxityError> {
+    let src = r#"
+fn name(n: int) -> str {
+  match n {
+    case 1: { return "one"; }
+    default: { if n > 9 { return "many"; } }
+  }
+  print("fell through");
+  return "some";
+}
+print(name(1));
+print(name(12));
+print(name(3));
+"#;
+    let out = run_source(src)?;
+    assert_eq!(out, "one\nmany\nfell through\nsome\n");
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use axity::run_source;
+use axity::AxityError;
+
+#[test]
+fn break_and_continue_in_every_loop_kind() -> Result<(), AxityError> {
+    let src = "let n: int = 0;
+while true { n = n + 1; if n % 2 == 0 { continue; } if n > 5 { break; } print(n); }
+let k: int = 0;
+do { k = k + 1; if k < 3 { continue; } break; } while k < 10;
+print(k);
+for let i: int = 0; i < 10; i++ { if i == 1 { continue; } if i == 3 { break; } print(i); }
+for x in [7, 8, 9] { if x == 8 { continue; } print(x); }
+let m: map<int> = map_new_int();
+map_set(m, \"a\", 1);
+for key in m { break; print(key); }";
+    assert_eq!(run_source(src)?, "1\n3\n5\n3\n0\n2\n7\n9\n");
+    Ok(())
+}
+
+#[test]
+fn labels_target_outer_loops_through_try_and_match() -> Result<(), AxityError> {
+    let src = "let total: int = 0;
+outer: for let i: int = 0; i < 5; i++ {
+  let j: int = 0;
+  inner: while j < 5 {
+    j = j + 1;
+    if j == 2 { continue inner; }
+    if j == 4 { continue outer; }
+    match i { case 3: { break outer; } default: { total = total + 1; } }
+  }
+}
+print(total);
+fn first_even(xs: array<int>) -> int {
+  let found: int = -1;
+  for x in xs {
+    try { if x % 2 == 0 { found = x; break; } } finally { print(x); }
+  }
+  return found;
+}
+print(first_even([3, 8, 10]));";
+    assert_eq!(run_source(src)?, "6\n3\n8\n8\n");
+    Ok(())
+}
+
+#[test]
+fn break_outside_a_loop_is_a_type_error() {
+    let err = run_source("break;").unwrap_err();
+    assert_eq!(err.code, "AX0315");
+    assert!(err.to_string().contains("`break` outside a loop"), "{}", err);
+    let err = run_source("fn f() -> int {\n  for x in [1] { continue outer; }\n  return 0;\n}").unwrap_err();
+    assert!(err.to_string().contains("unknown loop label `outer`"), "{}", err);
+    let err = run_source("try { print(1); } catch e { continue; }").unwrap_err();
+    assert_eq!(err.code, "AX0315");
+}
+
+#[test]
+fn lambda_bodies_do_not_see_enclosing_loops() {
+    let err = run_source("let c: bool = true;\nwhile c {\n  let f: any = fn(x: int) -> int { break; return x; };\n  c = false;\n}").unwrap_err();
+    assert_eq!((err.code, err.span.map(|s| s.line)), ("AX0315", Some(3)));
+    let err = run_source("try { throw 1; } catch e { let g: any = fn(x: int) -> int { retry; return x; }; }").unwrap_err();
+    assert_eq!(err.code, "AX0314");
+}
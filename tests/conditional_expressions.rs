@@ -0,0 +1,54 @@
+use axity::run_source;
+use axity::AxityError;
+
+#[test]
+fn else_if_chains_pick_the_first_true_branch() -> Result<(), AxityError> {
+    let src = "fn grade(n: int) -> str {
+  if n >= 90 { return \"A\"; } else if n >= 80 { return \"B\"; } else if n >= 70 { return \"C\"; } else { return \"F\"; }
+}
+print(grade(95)); print(grade(85)); print(grade(75)); print(grade(10));
+let x: int = 5;
+if x < 0 { print(\"neg\"); } else if x == 0 { print(\"zero\"); }
+print(\"done\");";
+    assert_eq!(run_source(src)?, "A\nB\nC\nF\ndone\n");
+    Ok(())
+}
+
+#[test]
+fn if_and_ternary_expressions_evaluate_one_branch() -> Result<(), AxityError> {
+    let src = "let x: int = -3;
+let sign: int = if x > 0 { 1 } else if x < 0 { -1 } else { 0 };
+print(sign);
+let label: str = x == 0 ? \"zero\" : x > 0 ? \"pos\" : \"neg\";
+print(label);
+let safe: int = x == 0 ? 0 : 12 / x;
+print(safe);
+let zero: int = 0;
+print(zero == 0 ? 0 : 1 / zero);
+let o: obj = { parity: if x % 2 == 0 { \"even\" } else { \"odd\" } };
+print(o.parity);";
+    assert_eq!(run_source(src)?, "-1\nneg\n-4\n0\nodd\n");
+    Ok(())
+}
+
+#[test]
+fn branch_types_must_match_and_else_is_required() {
+    let err = run_source("let a: int = if true { 1 } else { \"s\" };").unwrap_err();
+    assert_eq!(err.code, "AX0302");
+    assert!(err.to_string().contains("conditional branches must match"), "{}", err);
+    let err = run_source("let b: str = false ? \"x\" : 2;").unwrap_err();
+    assert_eq!(err.code, "AX0302");
+    assert!(run_source("let c: any = 1;\nlet d: int = true ? c : 2;\nprint(d);").is_ok());
+    let err = run_source("let e: int = if true { 1 };").unwrap_err();
+    assert!(err.to_string().contains("expected `else` after if expression"), "{}", err);
+}
+
+#[test]
+fn conditional_type_must_match_the_variable() {
+    let err = run_source("let x: int = if true { \"a\" } else { \"b\" };\nprint(x);").unwrap_err();
+    assert_eq!(err.code, "AX0302");
+    assert!(err.to_string().contains("conditional expression does not match the variable's type"), "{}", err);
+    let err = run_source("let y: str = \"a\";\ny = true ? 1 : 2;").unwrap_err();
+    assert_eq!((err.code, err.span.map(|s| s.line)), ("AX0302", Some(2)));
+    assert!(run_source("let c: any = \"a\";\nlet z: int = true ? c : c;").is_ok());
+}
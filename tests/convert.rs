@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use axity::runtime::{from_value, to_instance, to_value, FromValue, IntoValue, Runtime, Value};
+use axity::{compile, AxityError};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Point {
+    x: i64,
+    y: i64,
+    label: String,
+    tags: Vec<String>,
+    weight: f64,
+    note: Option<String>,
+}
+
+#[test]
+fn primitives_and_collections_round_trip() -> Result<(), AxityError> {
+    let mut rt = Runtime::new();
+    let v = vec![1i64, 2, 3].into_value(&mut rt)?;
+    assert_eq!(Vec::<i64>::from_value(&v)?, vec![1, 2, 3]);
+    let mut m = HashMap::new();
+    m.insert("a".to_string(), "x".to_string());
+    let v = m.clone().into_value(&mut rt)?;
+    assert_eq!(HashMap::<String, String>::from_value(&v)?, m);
+    let v = 1.5f64.into_value(&mut rt)?;
+    assert_eq!(f64::from_value(&v)?, 1.5);
+    assert!(String::from_value(&Value::Int(1)).is_err());
+    Ok(())
+}
+
+#[test]
+fn conversions_reject_wrong_types_and_none() -> Result<(), AxityError> {
+    let mut rt = Runtime::new();
+    assert!(i64::from_value(&Value::Bool(true)).is_err());
+    assert!(bool::from_value(&Value::Int(1)).is_err());
+    assert!(f64::from_value(&Value::Int(1)).is_err());
+    assert!(Option::<i64>::from_value(&Value::Str("x".to_string())).is_err());
+    assert_eq!(Option::<i64>::from_value(&Value::Int(4))?, Some(4));
+    assert!(None::<i64>.into_value(&mut rt).is_err());
+    let mut m = HashMap::new();
+    m.insert("a".to_string(), Some(1i64));
+    m.insert("b".to_string(), None);
+    let v = m.into_value(&mut rt)?;
+    assert_eq!(HashMap::<String, i64>::from_value(&v)?, HashMap::from([("a".to_string(), 1)]));
+    assert!(to_value(&mut rt, &None::<i64>).is_err());
+    assert!(to_value(&mut rt, &vec![Some(1i64), None]).is_err());
+    assert!(from_value::<Point>(&Value::Int(0)).is_err());
+    Ok(())
+}
+
+#[test]
+fn serde_struct_as_class_instance() -> Result<(), AxityError> {
+    let src = r#"
+class Point {
+    let x: int;
+    let y: int;
+    let label: string;
+    let tags: array<string>;
+    let weight: flt;
+}
+fn shift(p: Point) -> Point {
+    p.x = p.x + 10;
+    push(p.tags, "moved");
+    return p;
+}
+"#;
+    let mut rt = Runtime::new();
+    let prog = compile(src, &rt)?;
+    let pt = Point { x: 1, y: 2, label: "a".to_string(), tags: vec![], weight: 0.25, note: None };
+    let arg = to_instance(&mut rt, "Point", &pt)?;
+    let res = prog.call(&mut rt, "shift", &[arg])?;
+    let back: Point = from_value(&res)?;
+    assert_eq!(back, Point { x: 11, tags: vec!["moved".to_string()], ..pt });
+    Ok(())
+}
+
+#[test]
+fn serde_struct_as_obj() -> Result<(), AxityError> {
+    let mut rt = Runtime::new();
+    let prog = compile("fn label(o: obj) -> obj { return o.label; }", &rt)?;
+    let pt = Point { x: 1, y: 2, label: "hi".to_string(), tags: vec!["t".to_string()], weight: 1.0, note: Some("n".to_string()) };
+    let arg = to_value(&mut rt, &pt)?;
+    assert!(matches!(arg, Value::Obj(_)));
+    let res = prog.call(&mut rt, "label", &[arg])?;
+    assert_eq!(String::from_value(&res)?, "hi");
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use axity::error::{AxityError, Span};
+use axity::run_source;
+
+#[test]
+fn render_shows_snippet_labels_and_notes() {
+    let err = run_source("fn add(a: int, b: int) -> int {\n  return a + b;\n}\nprint(add(1));").unwrap_err();
+    assert_eq!(err.to_string(), "type error at <input>:4:7: argument count mismatch");
+    let expected = "type error[AX0303]: argument count mismatch\n --> <input>:4:7\n  |\n4 | print(add(1));\n  |       ^^^\n  |\n1 | fn add(a: int, b: int) -> int {\n  | -- `add` defined here\n  = note: expected 2 argument(s), found 1";
+    assert_eq!(err.render(false), expected);
+}
+
+#[test]
+fn render_colours_only_when_asked() {
+    let err = run_source("class P {\n  let x: int;\n}\nlet p: P = new P;\np.x = \"hi\";").unwrap_err();
+    assert_eq!(err.details.notes, vec!["expected int, found string".to_string()]);
+    let plain = err.render(false);
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.contains("5 | p.x = \"hi\";"), "{}", plain);
+    let coloured = err.render(true);
+    assert!(coloured.contains("\x1b[1;31mtype error[AX0302]\x1b[0m"), "{:?}", coloured);
+}
+
+#[test]
+fn render_without_sources_falls_back_to_location() {
+    let err = AxityError::ty("undefined variable", Span { line: 3, col: 9, ..Span::default() }).with_note("declare it with `let`");
+    assert_eq!(err.render(false), "type error[AX0300]: undefined variable\n --> 3:9\n  = note: declare it with `let`");
+}
@@ -0,0 +1,72 @@
+use axity::runtime::{Capabilities, Runtime, RuntimeConfig, Value};
+use axity::types::Type;
+use axity::{compile, AxityError};
+
+#[test]
+fn compiled_program_runs_repeatedly() -> Result<(), AxityError> {
+    let rt0 = Runtime::new();
+    let prog = compile("let x: int = 2; print(x * 21);", &rt0)?;
+    for _ in 0..3 {
+        let mut rt = Runtime::new();
+        assert_eq!(prog.run(&mut rt)?, "42\n");
+    }
+    Ok(())
+}
+
+#[test]
+fn call_script_function_by_name() -> Result<(), AxityError> {
+    let mut rt = Runtime::new();
+    let prog = compile("fn score(a: int, b: int) -> int { return a * 10 + b; }", &rt)?;
+    assert!(prog.has_function("score"));
+    for i in 0..3 {
+        match prog.call(&mut rt, "score", &[Value::Int(i), Value::Int(1)])? {
+            Value::Int(v) => assert_eq!(v, i * 10 + 1),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(prog.call(&mut rt, "missing", &[]).is_err());
+    assert!(prog.call(&mut rt, "score", &[Value::Int(1)]).is_err());
+    Ok(())
+}
+
+#[test]
+fn call_sees_globals_from_run() -> Result<(), AxityError> {
+    let mut rt = Runtime::new();
+    let prog = compile("let base: int = 100; fn bump(n: int) -> int { return base + n; }", &rt)?;
+    prog.run(&mut rt)?;
+    let mut out = String::new();
+    let v = prog.call_with_output(&mut rt, "bump", &[Value::Int(5)], &mut out)?;
+    assert!(matches!(v, Value::Int(105)));
+    Ok(())
+}
+
+#[test]
+fn call_checks_argument_types_up_front() -> Result<(), AxityError> {
+    let mut rt = Runtime::new();
+    let prog = compile("fn greet(name: str, times: int) -> str { return name; }", &rt)?;
+    let err = prog.call(&mut rt, "greet", &[Value::Int(1), Value::Int(2)]).unwrap_err();
+    assert_eq!(err.code, "AX0405");
+    assert!(err.to_string().contains("argument 1 of 'greet' must be string"), "{}", err);
+    let err = prog.call(&mut rt, "greet", &[Value::Str("a".into())]).unwrap_err();
+    assert_eq!(err.code, "AX0406");
+    assert!(matches!(prog.call(&mut rt, "greet", &[Value::Str("a".into()), Value::Int(2)])?, Value::Str(ref s) if s == "a"));
+    Ok(())
+}
+
+#[test]
+fn install_rejects_a_runtime_the_program_was_not_checked_against() -> Result<(), AxityError> {
+    let mut host = Runtime::new();
+    host.register_fn("twice", vec![Type::Int], Type::Int, |_rt, args| match args[0] { Value::Int(n) => Ok(Value::Int(n * 2)), _ => unreachable!() });
+    let prog = compile("print(twice(4));", &host)?;
+    assert_eq!(prog.run(&mut host)?, "8\n");
+    assert_eq!(prog.run(&mut Runtime::new()).unwrap_err().code, "AX0404");
+    let mut other = Runtime::new();
+    other.register_fn("twice", vec![Type::String], Type::Int, |_rt, _| Ok(Value::Int(0)));
+    assert_eq!(prog.run(&mut other).unwrap_err().code, "AX0404");
+    let prog = compile("print(read_file(\"x\"));", &Runtime::new())?;
+    let mut sandboxed = Runtime::with_config(RuntimeConfig::sandboxed(Capabilities::deny_all()));
+    let err = prog.run(&mut sandboxed).unwrap_err();
+    assert_eq!(err.code, "AX0501");
+    assert!(err.to_string().contains("read_file is denied by this runtime"), "{}", err);
+    Ok(())
+}
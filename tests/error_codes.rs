@@ -0,0 +1,94 @@
+use axity::error::codes::{explain, CODES};
+use std::time::Duration;
+use axity::error::{AxityError, AxityErrorKind};
+use axity::runtime::{Capabilities, Limits, Runtime, RuntimeConfig};
+use axity::{run_file_with_runtime, run_source};
+
+#[test]
+fn errors_carry_specific_codes() {
+    let code = |src: &str| run_source(src).unwrap_err().code;
+    assert_eq!(code("print(x);"), "AX0301");
+    assert_eq!(code("let a: int = 1 / 0;"), "AX0401");
+    assert_eq!(code("let a: int = 1\nprint(a);"), "AX0201");
+    assert_eq!(code("let s: str = \"open;"), "AX0102");
+    assert_eq!(code("let a: array<int> = [1];\nprint(set(a, \"0\", 2));"), "AX0305");
+    assert_eq!(code("let a: array<int> = [1];\nprint(a[3]);"), "AX0402");
+}
+
+#[test]
+fn every_code_in_the_source_is_documented() {
+    let kinds: [fn(String) -> AxityErrorKind; 10] = [AxityErrorKind::Lex, AxityErrorKind::Parse, AxityErrorKind::Type, AxityErrorKind::Runtime, AxityErrorKind::Permission, AxityErrorKind::StepLimit, AxityErrorKind::Timeout, AxityErrorKind::CallDepth, AxityErrorKind::MemoryLimit, AxityErrorKind::Interrupted];
+    let mut used: Vec<String> = kinds.iter().map(|k| k(String::new()).default_code().to_string()).collect();
+    let mut dirs = vec![std::path::PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src"))];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() { dirs.push(path); continue; }
+            let text = std::fs::read_to_string(&path).unwrap();
+            for part in text.split("with_code(\"").skip(1) {
+                used.push(part[..6].to_string());
+            }
+        }
+    }
+    assert!(used.len() > 100);
+    for code in used {
+        assert!(explain(&code).is_some(), "{} has no explain entry", code);
+    }
+    let mut seen: Vec<&str> = CODES.iter().map(|c| c.code).collect();
+    seen.sort();
+    seen.dedup();
+    assert_eq!(seen.len(), CODES.len(), "duplicate code entries");
+}
+
+#[test]
+fn explain_prints_title_example_and_fix() {
+    let entry = explain(" ax0401 ").unwrap();
+    let text = entry.render();
+    assert!(text.starts_with("AX0401: division by zero\n"), "{}", text);
+    assert!(text.contains("Example:\n\n    "));
+    assert!(text.contains("Fix:\n\n    "));
+    assert!(explain("AX9999").is_none());
+    let generic = explain("AX0100").unwrap().render();
+    assert!(!generic.contains("Example:") && !generic.contains("Fix:"), "{}", generic);
+    assert!(CODES.iter().filter(|c| c.example.is_some()).all(|c| c.example != c.fix), "an example must differ from its fix");
+}
+
+// Runs an example as a script file, with the sandbox, limits or files its code needs to show up.
+fn run_example(code: &str, example: &str) -> Result<String, AxityError> {
+    let dir = std::env::temp_dir().join("axity_code_examples").join(code);
+    std::fs::create_dir_all(dir.join("locked.ax")).unwrap();
+    let main = dir.join("main.ax");
+    std::fs::write(&main, example).unwrap();
+    let limits = match code {
+        "AX0502" => Limits { max_steps: Some(10_000), ..Limits::default() },
+        "AX0503" => Limits { timeout: Some(Duration::from_millis(20)), ..Limits::default() },
+        "AX0504" => Limits { max_call_depth: Some(16), ..Limits::default() },
+        "AX0505" => Limits { max_allocations: Some(1_000), ..Limits::default() },
+        _ => Limits::default(),
+    };
+    let caps = match code {
+        "AX0312" => Capabilities::read_only(),
+        "AX0501" => Capabilities::allow_all().with_path_prefix(&dir),
+        _ => Capabilities::allow_all(),
+    };
+    let mut rt = Runtime::with_config(RuntimeConfig::sandboxed(caps).with_limits(limits));
+    if code == "AX0506" {
+        let handle = rt.interrupt_handle();
+        std::thread::spawn(move || { std::thread::sleep(Duration::from_millis(20)); handle.interrupt(); });
+    }
+    run_file_with_runtime(&main.display().to_string(), &mut rt)
+}
+
+#[test]
+fn every_example_raises_its_own_code() {
+    let mut wrong = Vec::new();
+    for entry in CODES {
+        let Some(example) = entry.example else { continue };
+        match run_example(entry.code, example) {
+            Err(err) if err.code == entry.code => {}
+            Err(err) => wrong.push(format!("{}: {}", entry.code, err)),
+            Ok(out) => wrong.push(format!("{}: ran and printed {:?}", entry.code, out)),
+        }
+    }
+    assert!(wrong.is_empty(), "{}", wrong.join("\n"));
+}
@@ -0,0 +1,61 @@
+use axity::run_source;
+use axity::AxityError;
+
+#[test]
+fn throws_unwind_through_functions_and_methods() -> Result<(), AxityError> {
+    let src = r#"
+class Acc {
+  let total: int;
+  fn add(self: Acc, n: int) -> int {
+    if n > 10 { throw "too big: " + to_string(n); }
+    self.total = self.total + n;
+    return self.total;
+  }
+}
+fn risky(n: int) -> int {
+  let a: Acc = new Acc;
+  return a.add(n);
+}
+fn outer(n: int) -> int { return risky(n) * 2; }
+try {
+  print(outer(3));
+  print(outer(50));
+  print("not reached");
+} catch e {
+  print("caught " + e);
+}
+fn fail_with_data() -> int {
+  throw [1, 2, 3];
+  return 0;
+}
+try { fail_with_data(); } catch e { print(e); }
+"#;
+    assert_eq!(run_source(src)?, "6\ncaught too big: 50\n[1, 2, 3]\n");
+    Ok(())
+}
+
+#[test]
+fn nearest_try_wins_and_catch_can_rethrow() -> Result<(), AxityError> {
+    let src = r#"
+let f: obj = fn (x: int) -> int { throw x * 10; return 0; };
+fn inner() -> int {
+  try { f(1); } catch e { print(e); }
+  try { f(2); } catch e { throw e; }
+  return 0;
+}
+try { inner(); } catch e { print(e); }
+try { f(4); } catch e { print(e); }
+"#;
+    assert_eq!(run_source(src)?, "10\n20\n40\n");
+    Ok(())
+}
+
+#[test]
+fn uncaught_throw_from_a_call_keeps_its_origin() {
+    let src = "fn deep() -> int {\n  throw \"bad\";\n  return 0;\n}\nfn mid() -> int { return deep(); }\nprint(mid());";
+    let err = run_source(src).unwrap_err();
+    assert_eq!(err.code, "AX0403");
+    assert_eq!(err.to_string(), "runtime error at <input>:2:3: uncaught exception: bad");
+    let names: Vec<&str> = err.details.trace.iter().map(|f| f.function.as_str()).collect();
+    assert_eq!(names, vec!["deep", "mid", "<top level>"]);
+}
@@ -21,3 +21,60 @@ fn uncaught_exception_returns_error() {
     let res = run_source(src);
     assert!(res.is_err());
 }
+
+#[test]
+fn runtime_faults_are_catchable() -> Result<(), AxityError> {
+    let src = r#"
+let xs: array<int> = [1, 2];
+let zero: int = 0;
+let n: int = 10;
+try { n = n / zero; } catch e { print(e.kind + " " + e.code + " " + e.message); }
+try { n = n % zero; } catch e { print(e.message); }
+try { print(xs[5]); } catch e { print(e.kind); }
+try { let s: str = read_file("missing.txt"); } catch e { print(e.kind); }
+try { print(json_get("{bad", "a")); } catch e { print(e.kind); }
+print(n);
+"#;
+    let mut rt = axity::runtime::Runtime::new();
+    rt.set_fs(axity::runtime::MemoryFs::new());
+    let out = axity::run_source_with_runtime(src, &mut rt)?;
+    assert_eq!(out, "arithmetic AX0401 division by zero\nmodulo by zero\nindex\nio\nformat\n10\n");
+    Ok(())
+}
+
+#[test]
+fn faults_inside_nested_blocks_and_calls_restore_scopes() -> Result<(), AxityError> {
+    let src = r#"
+fn ratio(a: int, b: int) -> int {
+  let scaled: int = a * 100;
+  return scaled / b;
+}
+let total: int = 0;
+for let i: int = 0; i < 3; i++ {
+  try {
+    if i == 1 {
+      let tmp: int = ratio(i, i - 1);
+      print(tmp);
+    } else {
+      total = total + ratio(i + 1, 1);
+    }
+  } catch e {
+    print("skip " + to_string(i));
+  }
+}
+print(total);
+"#;
+    let out = run_source(src)?;
+    assert_eq!(out, "skip 1\n400\n");
+    Ok(())
+}
+
+#[test]
+fn fatal_errors_are_not_caught() {
+    use axity::runtime::{Limits, Runtime, RuntimeConfig};
+    let limits = Limits { max_steps: Some(500), ..Limits::default() };
+    let mut rt = Runtime::with_config(RuntimeConfig::default().with_limits(limits));
+    let src = "try {\n  let i: int = 0;\n  while true { i = i + 1; }\n} catch e {\n  print(\"caught\");\n}";
+    let err = axity::run_source_with_runtime(src, &mut rt).unwrap_err();
+    assert!(matches!(err.kind, axity::error::AxityErrorKind::StepLimit(_)), "{:?}", err.kind);
+}
@@ -0,0 +1,33 @@
+use axity::runtime::{MemoryFs, Runtime};
+use axity::{run_file_with_runtime, run_source_with_runtime};
+
+#[test]
+fn builtins_use_memory_fs() {
+    let fs = MemoryFs::new().with_file("data/in.txt", "hello");
+    let mut rt = Runtime::new();
+    rt.set_fs(fs.clone());
+    let src = r#"print(exists("data/in.txt")); mkdir("out"); write_file("out/copy.txt", read_file("./data/in.txt") + "!"); print(exists("out"));"#;
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "true\ntrue\n");
+    assert_eq!(fs.file("out/copy.txt").as_deref(), Some("hello!"));
+    assert!(!std::path::Path::new("out/copy.txt").exists());
+}
+
+#[test]
+fn memory_fs_reports_missing_files_and_dirs() {
+    let mut rt = Runtime::new();
+    rt.set_fs(MemoryFs::new());
+    assert!(run_source_with_runtime(r#"print(read_file("nope.txt"));"#, &mut rt).is_err());
+    assert!(run_source_with_runtime(r#"write_file("missing/dir.txt", "x");"#, &mut rt).is_err());
+    assert_eq!(run_source_with_runtime(r#"print(exists("nope.txt"));"#, &mut rt).unwrap(), "false\n");
+}
+
+#[test]
+fn imports_resolve_through_memory_fs() {
+    let fs = MemoryFs::from_files([
+        ("app/main.ax", "import \"lib/util.ax\";\nprint(twice(21));\n"),
+        ("app/lib/util.ax", "fn twice(n: int) -> int { return n * 2; }\n"),
+    ]);
+    let mut rt = Runtime::new();
+    rt.set_fs(fs);
+    assert_eq!(run_file_with_runtime("app/main.ax", &mut rt).unwrap(), "42\n");
+}
@@ -0,0 +1,52 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use axity::runtime::{CallbackInput, Runtime, ScriptedInput};
+use axity::run_source_with_runtime;
+
+#[test]
+fn scripted_input_feeds_prompts() {
+    let mut rt = Runtime::new();
+    rt.set_input(ScriptedInput::new(["Ada", "36"]));
+    let src = r#"let name: any = input("Name: "); let a: str = input("Age: "); let age: int = to_int(a); print(name + " " + to_string(age + 1));"#;
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "Name: Age: Ada 37\n");
+    assert_eq!(run_source_with_runtime("print(input_eof()); let s: str = input(); print(len(s));", &mut rt).unwrap(), "true\n0\n");
+}
+
+#[test]
+fn input_all_and_eof() {
+    let mut rt = Runtime::new();
+    rt.set_input(ScriptedInput::new(["header", "a", "b"]));
+    let src = r#"let h: any = input(); print(input_eof()); let rest: str = input_all(); print(rest); print(input_eof());"#;
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "false\na\nb\ntrue\n");
+}
+
+#[test]
+fn callback_input_is_pulled_lazily() {
+    let calls = Rc::new(RefCell::new(0));
+    let c = calls.clone();
+    let mut rt = Runtime::new();
+    rt.set_input(CallbackInput::new(move || {
+        *c.borrow_mut() += 1;
+        if *c.borrow() <= 2 { Some(format!("line{}", c.borrow())) } else { None }
+    }));
+    let src = "let n: int = 0; while !input_eof() { print(input()); n = n + 1; } print(n);";
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "line1\nline2\n2\n");
+    assert_eq!(*calls.borrow(), 3);
+}
+
+#[test]
+fn stdin_input_all_matches_scripted_input() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let script = std::env::temp_dir().join("axity_stdin_input_all.ax");
+    std::fs::write(&script, "let s: str = input_all(); print(len(s)); print(s);").unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_axity")).arg(&script)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(b"a\r\nb\n").unwrap();
+    let out = child.wait_with_output().unwrap();
+    let mut rt = Runtime::new();
+    rt.set_input(ScriptedInput::new(["a", "b"]));
+    let expected = run_source_with_runtime("let s: str = input_all(); print(len(s)); print(s);", &mut rt).unwrap();
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), expected);
+    assert_eq!(expected, "3\na\nb\n");
+}
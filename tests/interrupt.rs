@@ -0,0 +1,26 @@
+use std::thread;
+use std::time::Duration;
+use axity::error::AxityErrorKind;
+use axity::runtime::Runtime;
+use axity::run_source_with_runtime;
+
+#[test]
+fn handle_cancels_loop_from_another_thread() {
+    let mut rt = Runtime::new();
+    let handle = rt.interrupt_handle();
+    let stopper = thread::spawn(move || { thread::sleep(Duration::from_millis(50)); handle.interrupt(); });
+    let err = run_source_with_runtime("let i: int = 0; while true { i = i + 1; }", &mut rt).unwrap_err();
+    stopper.join().unwrap();
+    assert!(matches!(err.kind, AxityErrorKind::Interrupted(_)), "{}", err);
+    assert_eq!(run_source_with_runtime("print(40 + 2);", &mut rt).unwrap(), "42\n");
+}
+
+#[test]
+fn stale_interrupt_is_cleared_when_a_run_starts() {
+    let mut rt = Runtime::new();
+    let handle = rt.interrupt_handle();
+    handle.interrupt();
+    let src = "fn f(n: int) -> int { return n; }\nprint(f(1));";
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "1\n");
+    assert!(!handle.is_interrupted());
+}
@@ -0,0 +1,37 @@
+use axity::run_source;
+use serde_json::Value;
+
+#[test]
+fn type_error_as_json() {
+    let err = run_source("fn add(a: int, b: int) -> int {\n  return a + b;\n}\nprint(add(1));").unwrap_err();
+    let j = err.to_json();
+    assert_eq!(j["kind"], "type");
+    assert_eq!(j["message"], "argument count mismatch");
+    assert_eq!(j["file"], "<input>");
+    assert_eq!(j["range"]["start"]["line"], 4);
+    assert_eq!(j["range"]["start"]["column"], 7);
+    assert_eq!(j["range"]["end"]["column"], 10);
+    assert_eq!(j["labels"][0]["message"], "`add` defined here");
+    assert_eq!(j["labels"][0]["range"]["start"]["line"], 1);
+    assert_eq!(j["notes"][0], "expected 2 argument(s), found 1");
+}
+
+#[test]
+fn render_json_emits_one_object_per_error() {
+    let err = run_source("let a: int = ;\nprint(a +);").unwrap_err();
+    let objs: Vec<Value> = err.render_json().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(objs.len(), 2);
+    assert!(objs.iter().all(|o| o["kind"] == "parse"));
+    assert_eq!(objs[1]["range"]["start"]["line"], 2);
+}
+
+#[test]
+fn runtime_error_json_has_backtrace() {
+    let err = run_source("fn f(n: int) -> int {\n  return 10 / n;\n}\nprint(f(0));").unwrap_err();
+    let j = err.to_json();
+    assert_eq!(j["kind"], "runtime");
+    assert_eq!(j["message"], "division by zero");
+    assert_eq!(j["backtrace"][0]["function"], "f");
+    assert_eq!(j["backtrace"][1]["function"], "<top level>");
+    assert_eq!(j["backtrace"][1]["line"], 4);
+}
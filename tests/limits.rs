@@ -0,0 +1,53 @@
+use std::time::Duration;
+use axity::error::AxityErrorKind;
+use axity::runtime::{Limits, Runtime, RuntimeConfig, DEFAULT_MAX_CALL_DEPTH};
+use axity::run_source_with_runtime;
+
+fn limited(limits: Limits) -> Runtime { Runtime::with_config(RuntimeConfig::default().with_limits(limits)) }
+
+#[test]
+fn step_limit_stops_runaway_loop() {
+    let mut rt = limited(Limits { max_steps: Some(10_000), ..Limits::default() });
+    let err = run_source_with_runtime("let i: int = 0; while i < 1 { i = 0; }", &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::StepLimit(_)), "{}", err);
+    assert_eq!(run_source_with_runtime("print(1 + 1);", &mut rt).unwrap(), "2\n");
+}
+
+#[test]
+fn timeout_stops_runaway_loop() {
+    let mut rt = limited(Limits { timeout: Some(Duration::from_millis(50)), ..Limits::default() });
+    let err = run_source_with_runtime("while true { }", &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::Timeout(_)), "{}", err);
+}
+
+#[test]
+fn call_depth_limit_stops_unbounded_recursion() {
+    let mut rt = limited(Limits { max_call_depth: Some(8), ..Limits::default() });
+    let src = "fn f(n: int) -> int { return f(n + 1); }\nprint(f(0));";
+    let err = run_source_with_runtime(src, &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::CallDepth(_)), "{}", err);
+    let src = "fn g(n: int) -> int { return n * 2; }\nprint(g(g(g(1))));";
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "8\n");
+}
+
+#[test]
+fn allocation_cap_counts_live_values_only() {
+    let mut rt = limited(Limits { max_allocations: Some(100), ..Limits::default() });
+    let src = "let i: int = 0; while i < 1000 { let t: array<int> = [i]; i = i + 1; } print(i);";
+    assert_eq!(run_source_with_runtime(src, &mut rt).unwrap(), "1000\n");
+    let src = "let xs: array<array<int> > = [[0]]; let i: int = 0; while i < 1000 { push(xs, [i]); i = i + 1; }";
+    let err = run_source_with_runtime(src, &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::MemoryLimit(_)), "{}", err);
+}
+
+#[test]
+fn default_limits_bound_call_depth() {
+    assert_eq!(Limits::default().max_call_depth, Some(DEFAULT_MAX_CALL_DEPTH));
+    // Debug builds use far more native stack per script call than release builds.
+    let res = std::thread::Builder::new().stack_size(256 * 1024 * 1024).spawn(|| {
+        let mut rt = Runtime::new();
+        let err = run_source_with_runtime("fn f(n: int) -> int { return f(n + 1); }\nprint(f(0));", &mut rt).unwrap_err();
+        assert!(matches!(err.kind, AxityErrorKind::CallDepth(_)), "{}", err);
+    }).unwrap().join();
+    assert!(res.is_ok());
+}
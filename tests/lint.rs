@@ -0,0 +1,62 @@
+use axity::compile;
+use axity::lint::{Lint, LintConfig, LintLevel};
+use axity::runtime::Runtime;
+
+const SRC: &str = "let unused: int = 5;
+let name: any = input_all();
+if name == \"bob\" { print(1); }
+fn helper(a: int, b: int) -> int {
+  if true { let b: int = 2; return b; }
+  return b;
+  print(\"dead\");
+}
+fn main() -> int {
+  let total: int = 0;
+  print(\"total !{total}\");
+  return 0;
+}";
+
+fn found(config: &LintConfig) -> Vec<(&'static str, usize, bool)> {
+    let rt = Runtime::new();
+    compile(SRC, &rt).unwrap().lint(config).iter().map(|w| (w.lint, w.span.line, w.denied)).collect()
+}
+
+#[test]
+fn each_lint_reports_its_pattern() {
+    assert_eq!(found(&LintConfig::new()), vec![
+        ("unused_variable", 1, false),
+        ("any_comparison", 3, false),
+        ("unused_function", 4, false),
+        ("unused_variable", 4, false),
+        ("constant_condition", 5, false),
+        ("shadowed_binding", 5, false),
+        ("unreachable_code", 7, false),
+    ]);
+}
+
+#[test]
+fn levels_allow_and_deny_lints() {
+    let config = LintConfig::new().allow(Lint::UnusedVariable).deny(Lint::UnreachableCode);
+    let got = found(&config);
+    assert!(!got.iter().any(|w| w.0 == "unused_variable"));
+    assert!(got.contains(&("unreachable_code", 7, true)));
+    assert!(got.contains(&("any_comparison", 3, false)));
+
+    let config = LintConfig::from_toml("[lints]\nwarnings = \"allow\"\nshadowed_binding = \"deny\"\n").unwrap();
+    assert_eq!(config.level(Lint::AnyComparison), LintLevel::Allow);
+    assert_eq!(found(&config), vec![("shadowed_binding", 5, true)]);
+    assert!(LintConfig::from_toml("[lints]\nno_such_lint = \"warn\"").unwrap_err().contains("unknown lint 'no_such_lint'"));
+    assert!(LintConfig::from_toml("[lints]\nunused_variable = \"loud\"").is_err());
+}
+
+#[test]
+fn clean_programs_have_no_warnings_and_warnings_render() {
+    let rt = Runtime::new();
+    let clean = "fn add(a: int, b: int) -> int {\n  return a + b;\n}\nlet xs: array<int> = [1, 2];\nlet _skip: int = 0;\nfor x in xs { print(add(x, 1)); }";
+    assert!(compile(clean, &rt).unwrap().lint(&LintConfig::new()).is_empty());
+    let prog = compile("let x: int = 1;", &rt).unwrap();
+    let w = &prog.lint(&LintConfig::new())[0];
+    assert_eq!(w.render(false), "warning[unused_variable]: unused variable `x`\n --> <input>:1:1\n  |\n1 | let x: int = 1;\n  | ^^^\n  = note: if this is intentional, prefix it with an underscore: `_x`");
+    assert_eq!(w.to_json()["severity"], "warning");
+    assert_eq!(w.to_string(), "warning at <input>:1:1: unused variable `x` [unused_variable]");
+}
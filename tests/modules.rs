@@ -0,0 +1,44 @@
+use axity::error::AxityErrorKind;
+use axity::runtime::{MemoryFs, Runtime, SearchPathResolver};
+use axity::run_file_with_runtime;
+
+fn runtime_with(fs: MemoryFs, resolver: SearchPathResolver) -> Runtime {
+    let mut rt = Runtime::new();
+    rt.set_fs(fs);
+    rt.set_resolver(resolver);
+    rt
+}
+
+#[test]
+fn imports_search_project_includes_then_search_paths() {
+    let fs = MemoryFs::from_files([
+        ("proj/.axity", "version=1\n"),
+        ("proj/src/main.ax", "import \"util.ax\";\nimport \"shared.ax\";\nprint(twice(shared()));\n"),
+        ("proj/src/includes/util.ax", "fn twice(n: int) -> int { return n * 2; }\n"),
+        ("vendor/shared.ax", "fn shared() -> int { return 21; }\n"),
+    ]);
+    let mut rt = runtime_with(fs, SearchPathResolver::standard().with_search_path("vendor"));
+    assert_eq!(run_file_with_runtime("proj/src/main.ax", &mut rt).unwrap(), "42\n");
+}
+
+#[test]
+fn missing_module_lists_searched_paths() {
+    let fs = MemoryFs::from_files([("app/main.ax", "import \"nope.ax\";\nprint(1);\n")]);
+    let mut rt = runtime_with(fs, SearchPathResolver::new().with_search_path("lib").with_stdlib_root("std"));
+    let err = run_file_with_runtime("app/main.ax", &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::Parse(_)), "{}", err);
+    let msg = err.to_string();
+    assert!(msg.contains("module not found: 'nope.ax'"), "{}", msg);
+    for p in ["app/nope.ax", "lib/nope.ax", "std/nope.ax"] { assert!(msg.contains(p), "{}", msg); }
+}
+
+#[test]
+fn cyclic_imports_load_each_module_once() {
+    let fs = MemoryFs::from_files([
+        ("app/main.ax", "import \"./lib/a.ax\";\nprint(a() + b());\n"),
+        ("app/lib/a.ax", "import \"b.ax\";\nfn a() -> int { return 1; }\n"),
+        ("app/lib/b.ax", "import \"../main.ax\";\nimport \"./a.ax\";\nfn b() -> int { return 2; }\n"),
+    ]);
+    let mut rt = runtime_with(fs, SearchPathResolver::new());
+    assert_eq!(run_file_with_runtime("app/main.ax", &mut rt).unwrap(), "3\n");
+}
@@ -0,0 +1,39 @@
+use axity::runtime::{Runtime, Value};
+use axity::types::Type;
+use axity::{run_source_with_runtime, AxityError};
+
+fn host() -> Runtime {
+    let mut rt = Runtime::new();
+    rt.register_fn("host_add", vec![Type::Int, Type::Int], Type::Int, |_rt, args| {
+        match (&args[0], &args[1]) {
+            (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
+            _ => Err(AxityError::rt("host_add expects ints")),
+        }
+    });
+    rt.register_fn("host_names", vec![], Type::Array(Box::new(Type::String)), |rt, _args| {
+        Ok(rt.new_array(vec![Value::Str("a".to_string()), Value::Str("b".to_string())]))
+    });
+    rt
+}
+
+#[test]
+fn native_function_called_from_script() -> Result<(), AxityError> {
+    let mut rt = host();
+    let out = run_source_with_runtime("let x: int = host_add(2, 3); print(x); print(len(host_names()));", &mut rt)?;
+    assert_eq!(out, "5\n2\n");
+    Ok(())
+}
+
+#[test]
+fn native_signature_checked_by_type_checker() {
+    let mut rt = host();
+    assert!(run_source_with_runtime("print(host_add(1));", &mut rt).is_err());
+    assert!(run_source_with_runtime("print(host_add(1, \"x\"));", &mut rt).is_err());
+}
+
+#[test]
+#[should_panic(expected = "`len` is a builtin")]
+fn registering_a_builtin_name_panics() {
+    let mut rt = Runtime::new();
+    rt.register_fn("len", vec![Type::Int], Type::Int, |_rt, args| Ok(args[0].clone()));
+}
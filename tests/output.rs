@@ -0,0 +1,34 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use axity::runtime::Runtime;
+use axity::{run_source, run_source_with_runtime, AxityError, OutputSink};
+
+#[test]
+fn capture_returns_print_and_main_output() -> Result<(), AxityError> {
+    let src = r#"
+print("a");
+fn main() -> int { print("b"); return 7; }
+"#;
+    let out = run_source(src)?;
+    assert_eq!(out, "a\nb\n7\n");
+    Ok(())
+}
+
+#[test]
+fn null_sink_discards_output() -> Result<(), AxityError> {
+    let mut rt = Runtime::with_output(OutputSink::Null);
+    let out = run_source_with_runtime("print(1);", &mut rt)?;
+    assert_eq!(out, "");
+    Ok(())
+}
+
+#[test]
+fn callback_sink_receives_output() -> Result<(), AxityError> {
+    let seen = Rc::new(RefCell::new(String::new()));
+    let sink = seen.clone();
+    let mut rt = Runtime::with_output(OutputSink::callback(move |s| sink.borrow_mut().push_str(s)));
+    let out = run_source_with_runtime("print(1); print(\"two\");", &mut rt)?;
+    assert_eq!(out, "");
+    assert_eq!(&*seen.borrow(), "1\ntwo\n");
+    Ok(())
+}
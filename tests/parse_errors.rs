@@ -0,0 +1,33 @@
+use axity::{lexer, parser};
+
+fn parse_errors(src: &str) -> Vec<(String, usize)> {
+    let err = parser::parse(&lexer::lex(src).unwrap()).unwrap_err();
+    err.all().map(|e| (e.kind.message().to_string(), e.span.as_ref().unwrap().line)).collect()
+}
+
+#[test]
+fn reports_every_syntax_error() {
+    let src = "let a: int = ;\nlet b: int = 2\nprint(b);\nprint(a +);\nprint(a);";
+    assert_eq!(parse_errors(src), vec![
+        ("expected expression, found `;`".to_string(), 1),
+        ("expected `;` after variable declaration, found `print`".to_string(), 3),
+        ("expected expression, found `)`".to_string(), 4),
+    ]);
+}
+
+#[test]
+fn resynchronises_at_block_ends_and_items() {
+    let src = "fn f() -> int {\n  let x: int = 1\n}\nclass C {\n  7;\n  let v: int;\n}\nfn g( -> int { return 1; }\nimport 5;\nprint(f());";
+    assert_eq!(parse_errors(src), vec![
+        ("expected `;` after variable declaration, found `}`".to_string(), 3),
+        ("expected field or method, found integer `7`".to_string(), 5),
+        ("expected parameter name, found `->`".to_string(), 8),
+        ("expected module path string, found integer `5`".to_string(), 9),
+    ]);
+}
+
+#[test]
+fn unterminated_block_reports_once() {
+    assert_eq!(parse_errors("while true {\n  print(1);\n"), vec![("expected `}`, found end of file".to_string(), 3)]);
+    assert!(parser::parse(&lexer::lex("print(1);").unwrap()).is_ok());
+}
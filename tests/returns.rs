@@ -117,3 +117,14 @@ print(b.x);
     assert_eq!(out, "0\n");
     Ok(())
 }
+
+#[test]
+fn missing_return_checks_every_branch() -> Result<(), AxityError> {
+    let ok = "fn f(n: int) -> int {\n  try {\n    if n > 0 { return 1; } else { throw \"neg\"; }\n  } catch e {\n    match n { case 0: { return 0; } default: { return -1; } }\n  }\n}\nprint(f(1) + f(0) + f(-1));";
+    assert_eq!(run_source(ok)?, "0\n");
+    for body in ["if n > 0 { return 1; }", "try { return 1; } catch e { print(e); }", "while true { return 1; }", "if n > 0 { return 1; } else { if n < 0 { return 2; } }"] {
+        let src = format!("fn f(n: int) -> int {{ {} }}\nprint(f(1));", body);
+        assert_eq!(run_source(&src).unwrap_err().code, "AX0307", "{}", body);
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use axity::error::AxityErrorKind;
+use axity::run_source;
+
+fn rt_error(src: &str) -> (String, usize, usize) {
+    let err = run_source(src).unwrap_err();
+    let sp = err.span.clone().expect("runtime error without span");
+    match err.kind { AxityErrorKind::Runtime(m) => (m, sp.line, sp.col), k => panic!("expected runtime error, got {:?}", k) }
+}
+
+#[test]
+fn runtime_errors_point_at_failing_expression() {
+    assert_eq!(rt_error("let xs: array<int> = [1, 2];\nprint(xs[5]);"), ("index out of bounds".to_string(), 2, 7));
+    assert_eq!(rt_error("let a: any = 5;\n\nprint(a.field);"), ("member access on non-object".to_string(), 3, 7));
+    let (m, line, _) = rt_error("let j: str = \"{\";\nlet v: str = json_get(j, \"a\");");
+    assert!(m.starts_with("json parse"), "{}", m);
+    assert_eq!(line, 2);
+}
+
+#[test]
+fn division_by_zero_is_an_error_not_a_panic() {
+    let src = "fn d(a: int, b: int) -> int {\n  return a / b;\n}\nprint(d(1, 0));";
+    assert_eq!(rt_error(src), ("division by zero".to_string(), 2, 12));
+    assert_eq!(rt_error("let x: flt = 1.5;\nprint(x / 0.0);").0, "division by zero");
+    assert_eq!(rt_error("let n: int = 0;\nprint(7 % n);").0, "modulo by zero");
+}
+
+#[test]
+fn display_includes_location() {
+    let err = run_source("let xs: array<int> = [1];\nprint(xs[3]);").unwrap_err();
+    assert_eq!(err.to_string(), "runtime error at <input>:2:7: index out of bounds");
+}
@@ -0,0 +1,75 @@
+use axity::error::AxityErrorKind;
+use axity::runtime::{Capabilities, Runtime, RuntimeConfig};
+use axity::run_source_with_runtime;
+
+fn ax_path(p: &std::path::Path) -> String { p.display().to_string().replace('\\', "/") }
+
+#[test]
+fn deny_all_rejects_io_builtins_at_type_check() {
+    let mut rt = Runtime::with_config(RuntimeConfig::sandboxed(Capabilities::deny_all()));
+    for src in [r#"print(read_file("x.txt"));"#, r#"write_file("x.txt", "y");"#, r#"let s: any = input();"#] {
+        let err = run_source_with_runtime(src, &mut rt).unwrap_err();
+        assert!(matches!(err.kind, AxityErrorKind::Type(_)), "{}", err);
+    }
+    let out = run_source_with_runtime("print(1);", &mut rt).unwrap();
+    assert_eq!(out, "1\n");
+}
+
+#[test]
+fn denied_builtins_are_rejected_inside_function_and_lambda_bodies() {
+    let mut rt = Runtime::with_config(RuntimeConfig::sandboxed(Capabilities::deny_all()));
+    let src = "fn f() -> string { return read_file(\"x\"); }\nclass C { fn m() -> int { mkdir(\"d\"); return 0; } }\nlet g: any = fn() -> string { return input(); };";
+    let err = run_source_with_runtime(src, &mut rt).unwrap_err();
+    let lines: Vec<_> = std::iter::once(&err).chain(&err.details.others).map(|e| (e.code, e.span.as_ref().map(|s| s.line))).collect();
+    assert_eq!(lines, [("AX0312", Some(1)), ("AX0312", Some(2)), ("AX0312", Some(3))]);
+}
+
+#[test]
+fn read_only_allows_reads() {
+    let dir = std::env::temp_dir().join("axity_sandbox_read_only");
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("a.txt");
+    std::fs::write(&file, "data").unwrap();
+    let mut rt = Runtime::with_config(RuntimeConfig::sandboxed(Capabilities::read_only()));
+    let src = format!("print(read_file(\"{}\"));", ax_path(&file));
+    assert_eq!(run_source_with_runtime(&src, &mut rt).unwrap(), "data\n");
+    let src = format!("write_file(\"{}\", \"x\");", ax_path(&file));
+    assert!(run_source_with_runtime(&src, &mut rt).is_err());
+}
+
+#[test]
+fn path_prefix_allowlist_is_enforced_at_runtime() {
+    let dir = std::env::temp_dir().join("axity_sandbox_prefix");
+    std::fs::create_dir_all(&dir).unwrap();
+    let caps = Capabilities::allow_all().with_path_prefix(&dir);
+    let mut rt = Runtime::with_config(RuntimeConfig::sandboxed(caps));
+    let inside = dir.join("ok.txt");
+    let src = format!("write_file(\"{}\", \"hi\"); print(read_file(\"{}\"));", ax_path(&inside), ax_path(&inside));
+    assert_eq!(run_source_with_runtime(&src, &mut rt).unwrap(), "hi\n");
+    let escape = dir.join("..").join("escape.txt");
+    let src = format!("write_file(\"{}\", \"no\");", ax_path(&escape));
+    let err = run_source_with_runtime(&src, &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::Permission(_)), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn path_prefix_follows_symlinks() {
+    let dir = std::env::temp_dir().join("axity_sandbox_symlink");
+    let outside = std::env::temp_dir().join("axity_sandbox_symlink_outside");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    let link = dir.join("out");
+    let _ = std::fs::remove_file(&link);
+    std::os::unix::fs::symlink(&outside, &link).unwrap();
+    let caps = Capabilities::allow_all().with_path_prefix(&dir);
+    let mut rt = Runtime::with_config(RuntimeConfig::sandboxed(caps));
+    for target in [link.join("new.txt"), link.join("sub").join("..").join("new.txt")] {
+        let src = format!("write_file(\"{}\", \"no\");", ax_path(&target));
+        let err = run_source_with_runtime(&src, &mut rt).unwrap_err();
+        assert!(matches!(err.kind, AxityErrorKind::Permission(_)), "{}", err);
+    }
+    assert!(!outside.join("new.txt").exists());
+    let src = format!("write_file(\"{}\", \"ok\");", ax_path(&dir.join("fresh.txt")));
+    assert!(run_source_with_runtime(&src, &mut rt).is_ok());
+}
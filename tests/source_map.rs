@@ -0,0 +1,39 @@
+use axity::error::{FileId, SourceMap};
+use axity::runtime::{MemoryFs, Runtime};
+use axity::{compile, run_file_with_runtime};
+
+#[test]
+fn tokens_carry_file_and_byte_range() {
+    let src = "let name: str = \"héllo\";\nprint(name);";
+    let toks = axity::lexer::lex_file(src, FileId(3)).unwrap();
+    let s = &toks[5].span;
+    assert_eq!((s.file, s.line, s.col), (FileId(3), 1, 17));
+    assert_eq!(&src[s.start..s.end], "\"héllo\"");
+    let p = &toks[7].span;
+    assert_eq!((p.line, p.col), (2, 1));
+    assert_eq!(&src[p.start..p.end], "print");
+}
+
+#[test]
+fn errors_in_imported_files_report_that_file() {
+    let fs = MemoryFs::from_files([
+        ("app/main.ax", "import \"lib.ax\";\nprint(f(1));\n"),
+        ("app/lib.ax", "\n\nfn f(n: int) -> int {\n  return n +;\n}\n"),
+    ]);
+    let mut rt = Runtime::new();
+    rt.set_fs(fs);
+    let err = run_file_with_runtime("app/main.ax", &mut rt).unwrap_err();
+    assert_eq!(err.path.as_deref(), Some("app/lib.ax"));
+    assert!(err.to_string().starts_with("parse error at app/lib.ax:4:13:"), "{}", err);
+}
+
+#[test]
+fn source_map_resolves_lines() {
+    let mut map = SourceMap::new();
+    let a = map.add("a.ax", "one\r\ntwo\n");
+    let f = map.get(a).unwrap();
+    assert_eq!((f.line(1), f.line(2), f.line(3)), (Some("one"), Some("two"), Some("")));
+    assert_eq!(f.line(4), None);
+    let err = compile("let x: int = 1;\n  y = 2;", &Runtime::new()).unwrap_err();
+    assert_eq!(err.to_string(), "type error at <input>:2:5: undefined variable");
+}
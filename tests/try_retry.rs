@@ -0,0 +1,91 @@
+use axity::run_source;
+use axity::AxityError;
+
+#[test]
+fn retry_reruns_the_try_body_until_it_succeeds() -> Result<(), AxityError> {
+    let src = r#"
+let calls: int = 0;
+fn flaky() -> int {
+  calls = calls + 1;
+  if calls < 3 { throw "flaky " + to_string(calls); }
+  return calls * 10;
+}
+print(attempt());
+try (attempts: 5, backoff: 1) {
+  print("attempt " + to_string(attempt()));
+  print(flaky());
+} catch e {
+  print(e);
+  retry;
+} finally {
+  print("done");
+}
+"#;
+    assert_eq!(run_source(src)?, "0\nattempt 1\nflaky 1\ndone\nattempt 2\nflaky 2\ndone\nattempt 3\n30\ndone\n");
+    Ok(())
+}
+
+#[test]
+fn exhausted_attempts_let_the_exception_unwind() -> Result<(), AxityError> {
+    let src = r#"
+let zero: int = 0;
+try {
+  try (attempts: 3) {
+    print(attempt());
+    print(1 / zero);
+  } catch (e: ArithmeticError) {
+    retry;
+  }
+} catch e {
+  print("gave up: " + e.message);
+}
+let runs: int = 0;
+while runs < 2 {
+  runs = runs + 1;
+  try (attempts: 2) { throw runs; } catch (e: int) { print("caught " + to_string(e) + " on " + to_string(attempt())); if attempt() < 2 { retry; } }
+}
+"#;
+    assert_eq!(run_source(src)?, "1\n2\n3\ngave up: division by zero\ncaught 1 on 1\ncaught 1 on 2\ncaught 2 on 1\ncaught 2 on 2\n");
+    Ok(())
+}
+
+#[test]
+fn retry_inside_a_loop_in_the_catch_block_reruns_the_try() -> Result<(), AxityError> {
+    let src = r#"
+try (attempts: 2) {
+  print("run " + to_string(attempt()));
+  throw attempt();
+} catch (e: int) {
+  for x in [1, 2] {
+    if e == 1 { retry; }
+    print("gave up " + to_string(x));
+  }
+}
+"#;
+    assert_eq!(run_source(src)?, "run 1\nrun 2\ngave up 1\ngave up 2\n");
+    Ok(())
+}
+
+#[test]
+fn backoff_waits_the_same_delay_before_each_rerun() -> Result<(), AxityError> {
+    let start = std::time::Instant::now();
+    let out = run_source("try (attempts: 4, backoff: 40) { throw attempt(); } catch (e: int) { if e < 4 { retry; } print(e); }")?;
+    let waited = start.elapsed();
+    assert_eq!(out, "4\n");
+    // Three re-runs: 120 ms at a fixed delay, 280 ms if the delay doubled.
+    assert!(waited >= std::time::Duration::from_millis(120) && waited < std::time::Duration::from_millis(240), "{:?}", waited);
+    Ok(())
+}
+
+#[test]
+fn misplaced_retry_and_bad_options_are_rejected() {
+    assert_eq!(run_source("retry;").unwrap_err().code, "AX0314");
+    assert_eq!(run_source("fn f() -> int {\n  try { retry; } catch e { print(e); }\n  return 0;\n}").unwrap_err().code, "AX0314");
+    let err = run_source("let n: int = 0;\ntry { n = n + 1; throw \"x\"; } catch e { if n < 3 { retry; } }\nprint(n);").unwrap_err();
+    assert_eq!((err.code, err.span.map(|s| s.line)), ("AX0314", Some(2)));
+    assert_eq!(run_source("let v: int = 0;\nwhile v < 5 { v = v + 1; retry; }").unwrap_err().code, "AX0314");
+    assert_eq!(run_source("try (attempts: \"3\") { print(1); } catch e { retry; }").unwrap_err().code, "AX0302");
+    assert!(run_source("try (tries: 3) { print(1); } catch e { retry; }").unwrap_err().to_string().contains("expected `attempts` or `backoff`"));
+    let err = run_source("let n: int = 0;\ntry (attempts: n) { print(1); } catch e { retry; }").unwrap_err();
+    assert_eq!((err.code, err.kind.message()), ("AX0410", "try attempts must be at least 1"));
+}
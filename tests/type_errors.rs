@@ -0,0 +1,45 @@
+use axity::type_checker::{check_all, HostEnv};
+use axity::{lexer, parser, run_source};
+
+fn errors(src: &str) -> Vec<(String, usize)> {
+    let ast = parser::parse(&lexer::lex(src).unwrap()).unwrap();
+    check_all(&ast, &HostEnv::default()).iter().map(|e| (e.kind.message().to_string(), e.span.as_ref().unwrap().line)).collect()
+}
+
+#[test]
+fn reports_every_type_error() {
+    let src = "let xs: array<int> = [1];\npush(xs, \"a\");\nprint(nope);\nif !5 { print(1); }\nprint(len(3));";
+    assert_eq!(errors(src), vec![
+        ("push value type mismatch".to_string(), 2),
+        ("undefined variable".to_string(), 3),
+        ("! requires bool".to_string(), 4),
+        ("len expects array or string".to_string(), 5),
+    ]);
+}
+
+#[test]
+fn poisoned_expressions_do_not_cascade() {
+    let src = "let n: int = to_int(missing) + len(missing);\nfor x in missing { print(x.field); }\nprint(n);";
+    assert_eq!(errors(src), vec![("undefined variable".to_string(), 1), ("undefined variable".to_string(), 1), ("undefined variable".to_string(), 2)]);
+}
+
+#[test]
+fn unrelated_errors_survive_a_poisoned_operand() {
+    let src = "let a: int = 1;\nlet a: int = missing;\nfn f(x: int) -> int { return x; }\nprint(f(missing, 2));\nb = len(missing);";
+    assert_eq!(errors(src), vec![
+        ("undefined variable".to_string(), 2),
+        ("duplicate variable".to_string(), 2),
+        ("argument count mismatch".to_string(), 4),
+        ("undefined variable".to_string(), 5),
+        ("undefined variable".to_string(), 5),
+    ]);
+}
+
+#[test]
+fn compile_error_carries_the_rest() {
+    let err = run_source("print(a);\nprint(b);\nprint(c);").unwrap_err();
+    let lines: Vec<usize> = err.all().map(|e| e.span.as_ref().unwrap().line).collect();
+    assert_eq!(lines, vec![1, 2, 3]);
+    assert!(err.details.others.iter().all(|e| e.path.as_deref() == Some("<input>")));
+    assert!(errors("let a: int = 1;\nprint(a);").is_empty());
+}
@@ -0,0 +1,81 @@
+use axity::run_source;
+use axity::AxityError;
+
+#[test]
+fn catch_clauses_match_by_class_and_type() -> Result<(), AxityError> {
+    let src = r#"
+let zero: int = 0;
+fn attempt(n: int) -> int {
+  try {
+    if n == 0 { throw new ValueError("negative input"); }
+    if n == 1 { return 10 / zero; }
+    if n == 2 { throw "plain"; }
+    throw 7;
+  } catch (e: ValueError) {
+    print("value: " + e.message);
+  } catch (e: Error) {
+    print(e.kind + " " + e.code);
+  } catch (e: str) {
+    print("str " + e);
+  }
+  return 0;
+}
+for let i: int = 0; i < 3; i++ { attempt(i); }
+try { attempt(3); } catch (e: int) { print("escaped " + to_string(e)); }
+"#;
+    assert_eq!(run_source(src)?, "value: negative input\narithmetic AX0401\nstr plain\nescaped 7\n");
+    Ok(())
+}
+
+#[test]
+fn finally_runs_on_every_exit() -> Result<(), AxityError> {
+    let src = r#"
+fn pick(x: int) -> int {
+  try {
+    if x > 0 { return 1; }
+  } finally {
+    print("finally " + to_string(x));
+  }
+  return 2;
+}
+print(pick(5));
+print(pick(0));
+let n: int = 0;
+while n < 3 {
+  n = n + 1;
+  try { if n == 2 { continue; } print(n); } finally { print("f" + to_string(n)); }
+}
+try {
+  try { throw "inner"; } finally { print("cleanup"); }
+} catch e { print("outer " + e); }
+"#;
+    assert_eq!(run_source(src)?, "finally 5\n1\nfinally 0\n2\n1\nf1\nf2\n3\nf3\ncleanup\nouter inner\n");
+    Ok(())
+}
+
+#[test]
+fn bare_throw_rethrows_and_is_checked() {
+    let src = "let zero: int = 0;\ntry {\n  try { print(1 / zero); } catch (e: ArithmeticError) { print(\"log \" + e.message); throw; }\n} catch e { print(\"again \" + e.kind); }";
+    assert_eq!(run_source(src).unwrap(), "log division by zero\nagain arithmetic\n");
+    let err = run_source("let zero: int = 0;\ntry { print(1 / zero); } catch (e: IoError) { print(\"no\"); } finally { print(\"done\"); }").unwrap_err();
+    assert_eq!(err.code, "AX0401");
+    assert_eq!(run_source("throw;").unwrap_err().code, "AX0313");
+    assert_eq!(run_source("try { print(1); } catch (e: Missing) { print(2); }").unwrap_err().code, "AX0308");
+}
+
+#[test]
+fn finally_runs_after_fatal_errors() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use axity::error::AxityErrorKind;
+    use axity::runtime::{Limits, OutputSink, Runtime, RuntimeConfig};
+    let out = Rc::new(RefCell::new(String::new()));
+    let sink = out.clone();
+    let limits = Limits { max_steps: Some(500), ..Limits::default() };
+    let mut rt = Runtime::with_config(RuntimeConfig::default().with_limits(limits));
+    rt.set_output(OutputSink::callback(move |s| sink.borrow_mut().push_str(s)));
+    let src = "fn f() -> int {\n  try { while true { } } catch e { print(\"caught\"); } finally { print(\"cleanup\"); return 1; }\n  return 0;\n}\ntry { print(f()); } finally { print(\"outer\"); while true { } }\nprint(\"after\");";
+    let err = axity::run_source_with_runtime(src, &mut rt).unwrap_err();
+    assert!(matches!(err.kind, AxityErrorKind::StepLimit(_)), "{}", err);
+    assert_eq!(*out.borrow(), "cleanup\nouter\n");
+}